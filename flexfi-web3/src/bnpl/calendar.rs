@@ -0,0 +1,122 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hashv,
+    program::{invoke_signed, set_return_data},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::bnpl::{MicroBNPLAccount, InstallmentCalendarAccount};
+use crate::constants::INSTALLMENT_CALENDAR_SEED;
+
+// Hash the upcoming installments of the given micro loans and store the digest for the app to compare.
+// Entries are sorted by (due date, loan) and hashed as loan (32 bytes) || due date (i64 LE) || amount (u64 LE);
+// loans on hold have no due dates and are left out.
+pub fn process_anchor_installment_calendar(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let calendar_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let contract_accounts = account_info_iter.as_slice(); // The user's micro loans
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let (calendar_pda, calendar_bump) = Pubkey::find_program_address(
+        &[INSTALLMENT_CALENDAR_SEED, user_account.key.as_ref()],
+        program_id
+    );
+
+    if *calendar_account.key != calendar_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Collect every upcoming installment
+    let mut entries: Vec<(i64, Pubkey, u64)> = Vec::new();
+
+    for (index, contract_account) in contract_accounts.iter().enumerate() {
+        if contract_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        if contract_accounts[..index].iter().any(|other| other.key == contract_account.key) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let micro_bnpl_data = MicroBNPLAccount::try_from_slice(&contract_account.data.borrow())?;
+
+        if micro_bnpl_data.borrower != *user_account.key {
+            return Err(FlexfiError::Unauthorized.into());
+        }
+
+        let installments = if micro_bnpl_data.is_held() {
+            Vec::new()
+        } else {
+            micro_bnpl_data.upcoming_installments()
+        };
+
+        entries.extend(installments.into_iter().map(|(due, amount)| (due, *contract_account.key, amount)));
+    }
+
+    entries.sort_by_key(|entry| (entry.0, entry.1.to_bytes()));
+
+    let mut encoded = Vec::with_capacity(entries.len() * 48);
+    for (due, contract, amount) in entries.iter() {
+        encoded.extend_from_slice(contract.as_ref());
+        encoded.extend_from_slice(&due.to_le_bytes());
+        encoded.extend_from_slice(&amount.to_le_bytes());
+    }
+
+    let digest = hashv(&[&encoded]).to_bytes();
+    let installment_count = u16::try_from(entries.len()).map_err(|_| FlexfiError::MathOverflow)?;
+
+    // Create the anchor on first use
+    if calendar_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let space = InstallmentCalendarAccount::SIZE;
+        let rent_lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                user_account.key,
+                &calendar_pda,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[user_account.clone(), calendar_account.clone(), system_program.clone()],
+            &[&[INSTALLMENT_CALENDAR_SEED, user_account.key.as_ref(), &[calendar_bump]]],
+        )?;
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let calendar_data = InstallmentCalendarAccount {
+        owner: *user_account.key,
+        digest,
+        installment_count,
+        next_due: entries.first().map(|entry| entry.0).unwrap_or(0),
+        computed_at: clock.unix_timestamp,
+        bump: calendar_bump,
+    };
+
+    calendar_data.serialize(&mut *calendar_account.data.borrow_mut())?;
+
+    set_return_data(&digest);
+
+    msg!("Installment calendar anchored: {} installments, next due at {}", installment_count, calendar_data.next_due);
+    Ok(())
+}
@@ -3,17 +3,101 @@ use solana_program::{
     entrypoint::ProgramResult,
     program_error::ProgramError,
     pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
     msg,
 };
 use borsh::BorshDeserialize;
 
 use crate::error::FlexfiError;
-use crate::state::{staking::{StakingAccount, StakingStatus}, wallet::WalletAccount};
+use crate::state::{staking::{StakingAccount, StakingStatus}, wallet::WalletAccount, config::ConfigAccount};
 use crate::constants::{STAKING_SEED, get_card_config};
+use crate::core::config::load_config;
+use crate::score::query::load_user_score;
+use crate::core::staking_tier::get_staking_tier;
+use crate::core::mint_registry::get_mint_price_feed;
+use crate::oracle::get_usd_value;
+use crate::math::{apply_bps, Rounding};
+use crate::error_context::amount_mismatch;
+use crate::bnpl::registry::get_outstanding_debt;
 
 pub struct BNPLChecker {}
 
 impl BNPLChecker {
+    // Get the collateral ratio (LTV in basis points) for a card type and the user's score
+    fn get_ltv_bps(
+        program_id: &Pubkey,
+        user_account: &AccountInfo,
+        card_type: u8,
+        score_account: &AccountInfo,
+        config_account: &AccountInfo,
+    ) -> Result<u16, ProgramError> {
+        let score_data = load_user_score(program_id, user_account.key, score_account)?;
+        let config_data = load_config(program_id, config_account)?;
+
+        Ok(config_data.get_ltv_bps(card_type, score_data.score))
+    }
+
+    // Check the user's score against the minimum configured for the card type
+    pub(crate) fn require_min_score(
+        program_id: &Pubkey,
+        user_account: &AccountInfo,
+        card_type: u8,
+        score_account: &AccountInfo,
+        config_data: &ConfigAccount,
+    ) -> ProgramResult {
+        let score_data = load_user_score(program_id, user_account.key, score_account)?;
+        let min_score = config_data.get_min_bnpl_score(card_type);
+
+        if !score_data.meets_threshold(min_score) {
+            msg!("Score {} is below the minimum of {} for card type {}", score_data.score, min_score, card_type);
+            return Err(amount_mismatch(Some(score_account), min_score as u64, score_data.score as u64, FlexfiError::ScoreTooLow));
+        }
+
+        Ok(())
+    }
+
+    // USD value of the stake, priced by the oracle for non-stablecoin mints
+    pub(crate) fn get_staking_value(
+        program_id: &Pubkey,
+        staking_data: &StakingAccount,
+        staking_mint: &AccountInfo,
+        accepted_mints_account: &AccountInfo,
+        price_feed_account: &AccountInfo,
+    ) -> Result<u64, ProgramError> {
+        if *staking_mint.key != staking_data.usdc_mint {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let price_feed = get_mint_price_feed(program_id, staking_mint.key, accepted_mints_account)?;
+        let clock = Clock::get()?;
+
+        get_usd_value(staking_mint, &price_feed, price_feed_account, staking_data.amount_staked, clock.unix_timestamp)
+    }
+
+    // Apply a collateral ratio to a staked amount
+    pub(crate) fn apply_ltv(amount_staked: u64, ltv_bps: u16) -> u64 {
+        apply_bps(amount_staked, ltv_bps, Rounding::Down).unwrap_or(u64::MAX)
+    }
+
+    // Check a new loan together with the borrower's outstanding BNPL debt against what the stake covers
+    pub(crate) fn require_total_exposure(
+        program_id: &Pubkey,
+        user_account: &AccountInfo,
+        registry_account: &AccountInfo,
+        loan_amount: u64,
+        max_loan: u64,
+    ) -> ProgramResult {
+        let outstanding_debt = get_outstanding_debt(program_id, user_account.key, registry_account)?;
+        let total_exposure = outstanding_debt.saturating_add(loan_amount);
+
+        if total_exposure > max_loan {
+            msg!("Total exposure {} ({} outstanding) exceeds the {} the stake covers", total_exposure, outstanding_debt, max_loan);
+            return Err(amount_mismatch(Some(registry_account), max_loan, total_exposure, FlexfiError::InsufficientStaking));
+        }
+
+        Ok(())
+    }
+
     // Check if a user is authorized to use BNPL based on their staking
     pub fn check_bnpl_authorization(
         program_id: &Pubkey,
@@ -26,12 +110,22 @@ impl BNPLChecker {
         let user_account = next_account_info(account_info_iter)?;
         let usdc_mint = next_account_info(account_info_iter)?;
         let wallet_account = next_account_info(account_info_iter)?;
+        let score_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let staking_tier_config_account = next_account_info(account_info_iter)?;
+        let accepted_mints_account = next_account_info(account_info_iter)?;
+        let price_feed_account = next_account_info(account_info_iter)?;
+        let registry_account = next_account_info(account_info_iter)?; // The user's borrower registry, even if never created
+
+        // Load staking data
+        let staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
 
-        // Check the staking account
+        // Check the staking account for the stored position
         let seeds = [
             STAKING_SEED,
             user_account.key.as_ref(),
             usdc_mint.key.as_ref(),
+            &[staking_data.position_id],
         ];
         let (staking_pda, _) = Pubkey::find_program_address(&seeds, program_id);
 
@@ -39,9 +133,6 @@ impl BNPLChecker {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        // Load staking data
-        let staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
-
         // Verify ownership
         if staking_data.owner != *user_account.key {
             return Err(FlexfiError::Unauthorized.into());
@@ -59,35 +150,72 @@ impl BNPLChecker {
             _ => {} // Active or Locked are OK
         }
 
-        // Calculate required staking amount (1:1 ratio)
-        let required_staking = loan_amount;
-
-        // Check if staking is sufficient
-        if staking_data.amount_staked < required_staking {
-            msg!("Insufficient staking: has {}, needs {}", staking_data.amount_staked, required_staking);
-            return Err(FlexfiError::InsufficientStaking.into());
-        }
-
         // Check card type and allowed installments
         let wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
 
+        if wallet_data.owner != *user_account.key {
+            return Err(FlexfiError::Unauthorized.into());
+        }
+
         if !wallet_data.is_active {
             return Err(FlexfiError::WalletInactive.into());
         }
 
-        msg!("BNPL authorization successful: loan amount {}, staking {}", loan_amount, staking_data.amount_staked);
+        // Underwriting: the card type sets a minimum score
+        let config_data = load_config(program_id, config_account)?;
+        Self::require_min_score(program_id, user_account, wallet_data.card_type, score_account, &config_data)?;
+
+        // Calculate the loan the stake can cover with the configured collateral ratio
+        let ltv_bps = Self::get_ltv_bps(
+            program_id,
+            user_account,
+            wallet_data.card_type,
+            score_account,
+            config_account,
+        )?;
+        let staking_value = Self::get_staking_value(
+            program_id,
+            &staking_data,
+            usdc_mint,
+            accepted_mints_account,
+            price_feed_account,
+        )?;
+        let staking_tier = get_staking_tier(program_id, staking_tier_config_account, staking_value)?;
+        let max_loan = std::cmp::min(
+            Self::apply_ltv(staking_value, ltv_bps),
+            Self::apply_ltv(staking_value, staking_tier.max_bnpl_multiplier_bps),
+        );
+
+        // Check if staking is sufficient
+        if loan_amount > max_loan {
+            msg!("Insufficient staking: worth {}, covers {} at {} bps, needs {}",
+                staking_value, max_loan, ltv_bps, loan_amount);
+            return Err(FlexfiError::InsufficientStaking.into());
+        }
+
+        // Loans already running count against the same limit
+        Self::require_total_exposure(program_id, user_account, registry_account, loan_amount, max_loan)?;
+
+        msg!("BNPL authorization successful: loan amount {}, staking worth {}", loan_amount, staking_value);
         Ok(())
     }
 
     // Get the maximum BNPL amount allowed based on staking
     pub fn get_max_bnpl_amount(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         accounts: &[AccountInfo],
     ) -> Result<u64, ProgramError> {
         let account_info_iter = &mut accounts.iter();
 
         let staking_account = next_account_info(account_info_iter)?;
         let user_account = next_account_info(account_info_iter)?;
+        let wallet_account = next_account_info(account_info_iter)?;
+        let score_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let staking_tier_config_account = next_account_info(account_info_iter)?;
+        let staking_mint = next_account_info(account_info_iter)?;
+        let accepted_mints_account = next_account_info(account_info_iter)?;
+        let price_feed_account = next_account_info(account_info_iter)?;
 
         // Load staking data
         let staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
@@ -103,10 +231,34 @@ impl BNPLChecker {
             return Err(FlexfiError::StakingNotActive.into());
         }
 
-        // The maximum BNPL amount is equal to the staked amount (1:1 ratio)
-        let max_bnpl = staking_data.amount_staked;
+        let wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+        if wallet_data.owner != *user_account.key {
+            return Err(FlexfiError::Unauthorized.into());
+        }
+
+        // The maximum BNPL amount is the staked amount scaled by the collateral ratio
+        let ltv_bps = Self::get_ltv_bps(
+            program_id,
+            user_account,
+            wallet_data.card_type,
+            score_account,
+            config_account,
+        )?;
+        let staking_value = Self::get_staking_value(
+            program_id,
+            &staking_data,
+            staking_mint,
+            accepted_mints_account,
+            price_feed_account,
+        )?;
+        let staking_tier = get_staking_tier(program_id, staking_tier_config_account, staking_value)?;
+        let max_bnpl = std::cmp::min(
+            Self::apply_ltv(staking_value, ltv_bps),
+            Self::apply_ltv(staking_value, staking_tier.max_bnpl_multiplier_bps),
+        );
 
-        msg!("Maximum BNPL amount: {}", max_bnpl);
+        msg!("Maximum BNPL amount: {} ({} bps)", max_bnpl, ltv_bps);
         Ok(max_bnpl)
     }
 
@@ -0,0 +1,170 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    log::sol_log_data,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::bnpl::{MicroBNPLAccount, ForgivenessBudgetAccount};
+use crate::state::admin::AdminRole;
+use crate::constants::FORGIVENESS_BUDGET_SEED;
+use crate::core::admin::require_admin_role;
+use crate::error_context::amount_mismatch;
+
+// Emitted for every forgiveness so goodwill adjustments can be audited
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PenaltyForgiven {
+    pub contract: Pubkey,
+    pub borrower: Pubkey,
+    pub amount: u64,
+    pub authority: Pubkey,
+    pub remaining_budget: u64,
+    pub timestamp: i64,
+}
+
+// Set the forgiveness budget per period, creating the budget account the first time
+pub fn process_set_forgiveness_budget(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    monthly_cap: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let budget_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the treasurer role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Treasurer)?;
+
+    let (budget_pda, budget_bump) = Pubkey::find_program_address(&[FORGIVENESS_BUDGET_SEED], program_id);
+
+    if *budget_account.key != budget_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let mut budget_data = if budget_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let space = ForgivenessBudgetAccount::SIZE;
+        let rent_lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                &budget_pda,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[authority.clone(), budget_account.clone(), system_program.clone()],
+            &[&[FORGIVENESS_BUDGET_SEED, &[budget_bump]]],
+        )?;
+
+        ForgivenessBudgetAccount {
+            monthly_cap: 0,
+            period_start: clock.unix_timestamp,
+            forgiven_in_period: 0,
+            total_forgiven: 0,
+            updated_by: *authority.key,
+            bump: budget_bump,
+        }
+    } else {
+        ForgivenessBudgetAccount::try_from_slice(&budget_account.data.borrow())?
+    };
+
+    budget_data.roll_period(clock.unix_timestamp);
+    budget_data.monthly_cap = monthly_cap;
+    budget_data.updated_by = *authority.key;
+    budget_data.serialize(&mut *budget_account.data.borrow_mut())?;
+
+    msg!("Penalty forgiveness budget set to {} per period by {}", monthly_cap, authority.key);
+    Ok(())
+}
+
+// Support goodwill: waive part of the late fees owed on a micro loan, drawn from the period's budget
+pub fn process_forgive_penalty(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    contract: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let contract_account = next_account_info(account_info_iter)?;
+    let budget_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the support role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Support)?;
+
+    if *contract_account.key != contract {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if contract_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut contract_data = MicroBNPLAccount::try_from_slice(&contract_account.data.borrow())?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    // Fees accrued up to now can be forgiven; a loan on hold accrues none
+    if !contract_data.is_held() {
+        contract_data.accrue_late_fees(clock.unix_timestamp);
+    }
+
+    if amount == 0 || amount > contract_data.accrued_late_fees {
+        return Err(amount_mismatch(Some(contract_account), contract_data.accrued_late_fees, amount, ProgramError::InvalidArgument));
+    }
+
+    let (budget_pda, _) = Pubkey::find_program_address(&[FORGIVENESS_BUDGET_SEED], program_id);
+
+    if *budget_account.key != budget_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if budget_account.owner != program_id {
+        msg!("No forgiveness budget has been set");
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let mut budget_data = ForgivenessBudgetAccount::try_from_slice(&budget_account.data.borrow())?;
+
+    if !budget_data.spend(amount, clock.unix_timestamp) {
+        msg!("Only {} left in this period's forgiveness budget", budget_data.remaining_budget());
+        return Err(amount_mismatch(Some(budget_account), budget_data.remaining_budget(), amount, FlexfiError::AmountTooHigh));
+    }
+
+    contract_data.accrued_late_fees -= amount;
+
+    contract_data.serialize(&mut *contract_account.data.borrow_mut())?;
+    budget_data.serialize(&mut *budget_account.data.borrow_mut())?;
+
+    let event = PenaltyForgiven {
+        contract,
+        borrower: contract_data.borrower,
+        amount,
+        authority: *authority.key,
+        remaining_budget: budget_data.remaining_budget(),
+        timestamp: clock.unix_timestamp,
+    };
+
+    sol_log_data(&[b"PenaltyForgiven", &event.try_to_vec()?]);
+
+    msg!("{} of late fees forgiven on contract {} by {}", amount, contract, authority.key);
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::admin::AdminRole;
+use crate::state::bnpl::MicroBNPLAccount;
+use crate::core::admin::require_admin_role;
+
+// Check the role and load the named micro loan
+fn load_held_contract(
+    program_id: &Pubkey,
+    contract_account: &AccountInfo,
+    admin_list_account: &AccountInfo,
+    authority: &AccountInfo,
+    contract: Pubkey,
+) -> Result<MicroBNPLAccount, ProgramError> {
+    // Verify the authority holds the pauser role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Pauser)?;
+
+    if *contract_account.key != contract {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if contract_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let contract_data = MicroBNPLAccount::try_from_slice(&contract_account.data.borrow())?;
+    Ok(contract_data)
+}
+
+// Freeze a micro loan still owed pending a dispute or legal resolution: no payments, reminders or
+// penalties while held
+pub fn process_place_legal_hold(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    contract: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let contract_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    let mut contract_data = load_held_contract(program_id, contract_account, admin_list_account, authority, contract)?;
+
+    if contract_data.remaining_amount() == 0 || contract_data.is_held() {
+        return Err(FlexfiError::LoanNotActive.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    contract_data.place_hold(clock.unix_timestamp);
+    contract_data.serialize(&mut *contract_account.data.borrow_mut())?;
+
+    msg!("Legal hold placed on contract {} by {}", contract, authority.key);
+    Ok(())
+}
+
+// Lift a legal hold; the next due date moves back by the time the loan spent on hold
+pub fn process_release_legal_hold(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    contract: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let contract_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    let mut contract_data = load_held_contract(program_id, contract_account, admin_list_account, authority, contract)?;
+
+    if !contract_data.is_held() {
+        msg!("Contract {} is not on hold", contract);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    contract_data.release_hold(clock.unix_timestamp);
+    contract_data.serialize(&mut *contract_account.data.borrow_mut())?;
+
+    msg!("Legal hold released on contract {}, next payment due at {}", contract, contract_data.next_payment_due);
+    Ok(())
+}
+
+pub struct LegalHold;
+
+impl LegalHold {
+    pub fn place(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        contract: Pubkey,
+    ) -> ProgramResult {
+        process_place_legal_hold(program_id, accounts, contract)
+    }
+
+    pub fn release(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        contract: Pubkey,
+    ) -> ProgramResult {
+        process_release_legal_hold(program_id, accounts, contract)
+    }
+}
@@ -0,0 +1,161 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::bnpl::KeeperPoolAccount;
+use crate::state::admin::AdminRole;
+use crate::constants::{KEEPER_POOL_SEED, MAX_KEEPER_BOUNTY_LAMPORTS};
+use crate::core::admin::require_admin_role;
+use crate::bnpl::micro::load_open_micro_bnpl;
+
+// Set the bounty paid per repayment check, creating the keeper pool the first time
+pub fn process_set_keeper_bounty(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    bounty_lamports: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let pool_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the treasurer role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Treasurer)?;
+
+    if bounty_lamports > MAX_KEEPER_BOUNTY_LAMPORTS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (pool_pda, pool_bump) = Pubkey::find_program_address(&[KEEPER_POOL_SEED], program_id);
+
+    if *pool_account.key != pool_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut pool_data = if pool_account.owner != program_id {
+        let rent = Rent::get()?;
+        let space = KeeperPoolAccount::SIZE;
+        let rent_lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                &pool_pda,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[authority.clone(), pool_account.clone(), system_program.clone()],
+            &[&[KEEPER_POOL_SEED, &[pool_bump]]],
+        )?;
+
+        KeeperPoolAccount {
+            bounty_lamports: 0,
+            total_paid: 0,
+            updated_by: *authority.key,
+            bump: pool_bump,
+        }
+    } else {
+        KeeperPoolAccount::try_from_slice(&pool_account.data.borrow())?
+    };
+
+    pool_data.bounty_lamports = bounty_lamports;
+    pool_data.updated_by = *authority.key;
+    pool_data.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    msg!("Keeper bounty set to {} lamports by {}", bounty_lamports, authority.key);
+    Ok(())
+}
+
+// Pay the keeper's bounty out of the pool, as far as the pool holds more than its rent-exempt reserve;
+// returns the amount paid
+fn pay_keeper_bounty(
+    program_id: &Pubkey,
+    pool_account: &AccountInfo,
+    keeper_account: &AccountInfo,
+) -> Result<u64, ProgramError> {
+    let (pool_pda, _) = Pubkey::find_program_address(&[KEEPER_POOL_SEED], program_id);
+
+    if *pool_account.key != pool_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // No bounty until the pool is set up
+    if pool_account.owner != program_id {
+        return Ok(0);
+    }
+
+    let mut pool_data = KeeperPoolAccount::try_from_slice(&pool_account.data.borrow())?;
+
+    let reserve = Rent::get()?.minimum_balance(pool_account.data_len());
+    let bounty = std::cmp::min(pool_data.bounty_lamports, pool_account.lamports().saturating_sub(reserve));
+
+    if bounty == 0 {
+        return Ok(0);
+    }
+
+    **pool_account.lamports.borrow_mut() = pool_account.lamports()
+        .checked_sub(bounty)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **keeper_account.lamports.borrow_mut() = keeper_account.lamports()
+        .checked_add(bounty)
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    pool_data.total_paid = pool_data.total_paid.saturating_add(bounty);
+    pool_data.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    Ok(bounty)
+}
+
+// Permissionless crank: accrue the daily late fee of a micro loan's overdue installment, for each day
+// of the grace period not accrued yet; the fees are collected with the installment. Loans under a legal
+// hold are skipped. The keeper is paid the pool's bounty when a fee is accrued.
+pub fn process_check_repayment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let micro_bnpl_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let keeper_account = next_account_info(account_info_iter)?; // Receives the bounty
+
+    if !keeper_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut micro_bnpl_data = load_open_micro_bnpl(program_id, micro_bnpl_account)?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if !micro_bnpl_data.is_payment_due(clock.unix_timestamp) {
+        msg!("Installment not overdue, due at {}", micro_bnpl_data.next_payment_due);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let late_fee = micro_bnpl_data.accrue_late_fees(clock.unix_timestamp);
+    micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+
+    msg!("Micro BNPL {} overdue for {} days: {} late fee accrued, {} in total",
+        micro_bnpl_account.key, micro_bnpl_data.days_overdue, late_fee, micro_bnpl_data.accrued_late_fees);
+
+    // Checks that change nothing earn nothing
+    if late_fee > 0 {
+        let bounty = pay_keeper_bounty(program_id, pool_account, keeper_account)?;
+        msg!("Keeper {} paid a {} lamport bounty", keeper_account.key, bounty);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,624 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::bnpl::MicroBNPLAccount;
+use crate::state::staking::{StakingAccount, StakingStatus};
+use crate::state::wallet::WalletAccount;
+use crate::state::merchant::MerchantAccount;
+use crate::constants::{
+    MICRO_BNPL_SEED, TREASURY_SEED, MICRO_BNPL_INTERVAL_DAYS, MICRO_BNPL_ESCROW_TIMEOUT_DAYS, PREPAYMENT_NONE,
+    NFT_NONE, INSURANCE_PENALTY_SHARE_BPS, get_grace_period_days,
+};
+use crate::bnpl::checker::BNPLChecker;
+use crate::core::config::{load_config, require_region_enabled};
+use crate::core::whitelist::{load_user_limits, resolve_acting_user};
+use crate::core::mint_registry::require_payment_mint;
+use crate::merchant::registry::load_merchant;
+use crate::token::transfer_checked;
+use crate::error_context::{key_mismatch, amount_mismatch};
+use crate::math::{mul_div, Rounding};
+use crate::bnpl::registry::{load_borrower_registry, register_borrower_contract, record_registry_repayment};
+use crate::insurance::reserve::{check_reserve_token_account, get_insurance_share};
+use crate::rewards::emissions::{record_epoch_activity, Activity, ActivityAccounts};
+
+// Check a treasury token account of a mint and return the treasury bump
+pub(crate) fn check_treasury_token_account(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    treasury_token_account: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (treasury_pda, treasury_bump) = Pubkey::find_program_address(
+        &[TREASURY_SEED, mint.as_ref()],
+        program_id
+    );
+    let treasury_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &treasury_pda,
+        mint,
+        token_program,
+    );
+
+    if *treasury_token_account.key != treasury_ata {
+        return Err(key_mismatch(treasury_token_account, &treasury_ata, ProgramError::InvalidAccountData));
+    }
+
+    Ok(treasury_bump)
+}
+
+// Token account a merchant is paid at: its settlement account, or its own token account of the mint
+pub(crate) fn get_merchant_settlement_token(
+    merchant_data: &MerchantAccount,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    if merchant_data.settlement_token_account != Pubkey::default() {
+        merchant_data.settlement_token_account
+    } else {
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &merchant_data.owner,
+            mint,
+            token_program,
+        )
+    }
+}
+
+// Load a micro loan owned by the program with an amount still owed and no legal hold
+pub(crate) fn load_open_micro_bnpl(
+    program_id: &Pubkey,
+    micro_bnpl_account: &AccountInfo,
+) -> Result<MicroBNPLAccount, ProgramError> {
+    if micro_bnpl_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let micro_bnpl_data = MicroBNPLAccount::try_from_slice(&micro_bnpl_account.data.borrow())?;
+
+    if micro_bnpl_data.remaining_amount() == 0 || micro_bnpl_data.is_held() {
+        return Err(FlexfiError::LoanNotActive.into());
+    }
+
+    Ok(micro_bnpl_data)
+}
+
+// Pay a small basket from the treasury, repaid by the user in two fee-free installments.
+// In escrow mode the merchant token account is replaced by the loan's vault (the loan PDA's token
+// account of the mint, created beforehand), where the payment waits for ReleaseEscrow.
+pub fn process_create_micro_bnpl(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    escrow: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let micro_bnpl_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let user_status_account = next_account_info(account_info_iter)?; // Whitelisting checked by the processor guard
+    let staking_account = next_account_info(account_info_iter)?;
+    let wallet_account = next_account_info(account_info_iter)?;
+    let score_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let price_feed_account = next_account_info(account_info_iter)?;
+    let merchant_account = next_account_info(account_info_iter)?;
+    let merchant_token_account = next_account_info(account_info_iter)?;
+    let treasury_authority = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?; // The user's borrower registry, created on the first loan
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    // Micro loans are capped by the configured threshold
+    let config_data = load_config(program_id, config_account)?;
+
+    if amount == 0 || amount > config_data.micro_bnpl_threshold {
+        msg!("Micro-BNPL amount must be between 1 and {}", config_data.micro_bnpl_threshold);
+        return Err(amount_mismatch(None, config_data.micro_bnpl_threshold, amount, FlexfiError::AmountTooHigh));
+    }
+
+    // Only served in the regions rolled out so far
+    require_region_enabled(program_id, &config_data, user_status_account)?;
+
+    // Compliance caps on the user's whitelist status or its wallet cache (attested users have none)
+    if let Some(limits) = load_user_limits(program_id, user_status_account)? {
+        let active_contracts = load_borrower_registry(program_id, user_account.key, registry_account)?
+            .map_or(0, |registry| registry.contract_count);
+
+        if !limits.allows_bnpl(amount, active_contracts) {
+            msg!("Amount {} exceeds the user's risk limits", amount);
+            return Err(FlexfiError::RiskLimitExceeded.into());
+        }
+    }
+
+    // The user's wallet and stake back the loan at the relaxed collateral ratio
+    let wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    if wallet_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if !wallet_data.is_active {
+        return Err(FlexfiError::WalletInactive.into());
+    }
+
+    BNPLChecker::require_min_score(program_id, user_account, wallet_data.card_type, score_account, &config_data)?;
+
+    // The loan is denominated in one of the enabled stablecoins; transfers use its own decimals
+    require_payment_mint(program_id, mint.key, accepted_mints_account)?;
+
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    if staking_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let status = staking_data.get_status()?;
+    if status != StakingStatus::Active && status != StakingStatus::Locked {
+        return Err(FlexfiError::StakingNotActive.into());
+    }
+
+    let staking_value = BNPLChecker::get_staking_value(
+        program_id,
+        &staking_data,
+        mint,
+        accepted_mints_account,
+        price_feed_account,
+    )?;
+    let max_loan = BNPLChecker::apply_ltv(staking_value, config_data.micro_bnpl_ltv_bps);
+
+    if amount > max_loan {
+        msg!("Insufficient staking: worth {}, covers {} at {} bps", staking_value, max_loan, config_data.micro_bnpl_ltv_bps);
+        return Err(amount_mismatch(Some(staking_account), amount, max_loan, FlexfiError::InsufficientStaking));
+    }
+
+    // Loans already running count against the same limit
+    BNPLChecker::require_total_exposure(program_id, user_account, registry_account, amount, max_loan)?;
+
+    // The share of the stake the loan takes up can't be withdrawn until it is settled
+    let stake_hold = mul_div(staking_data.amount_staked, amount, max_loan, Rounding::Up)
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    if stake_hold > staking_data.available_amount() {
+        msg!("Loan needs {} of the stake, {} is available", stake_hold, staking_data.available_amount());
+        return Err(amount_mismatch(Some(staking_account), stake_hold, staking_data.available_amount(), FlexfiError::InsufficientStaking));
+    }
+
+    // Pay an active registered merchant at its settlement account
+    let merchant_data = load_merchant(program_id, merchant_account)?;
+
+    if !merchant_data.is_active(current_time) {
+        return Err(FlexfiError::MerchantNotActive.into());
+    }
+
+    // One open micro loan per user
+    let (micro_bnpl_pda, micro_bnpl_bump) = Pubkey::find_program_address(
+        &[MICRO_BNPL_SEED, user_account.key.as_ref()],
+        program_id
+    );
+
+    if *micro_bnpl_account.key != micro_bnpl_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let payment_destination = if escrow {
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &micro_bnpl_pda,
+            mint.key,
+            token_program.key,
+        )
+    } else {
+        get_merchant_settlement_token(&merchant_data, mint.key, token_program.key)
+    };
+
+    if *merchant_token_account.key != payment_destination {
+        return Err(key_mismatch(merchant_token_account, &payment_destination, ProgramError::InvalidAccountData));
+    }
+
+    let treasury_bump = check_treasury_token_account(program_id, mint.key, token_program.key, treasury_token_account)?;
+
+    let rent = Rent::get()?;
+    let space = MicroBNPLAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            user_account.key,
+            &micro_bnpl_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[user_account.clone(), micro_bnpl_account.clone(), system_program.clone()],
+        &[&[MICRO_BNPL_SEED, user_account.key.as_ref(), &[micro_bnpl_bump]]],
+    )?;
+
+    // The treasury fronts the purchase, no fee is charged
+    transfer_checked(
+        token_program,
+        treasury_token_account,
+        mint,
+        merchant_token_account,
+        treasury_authority,
+        hook_accounts,
+        amount,
+        &[&[TREASURY_SEED, mint.key.as_ref(), &[treasury_bump]]],
+    )?;
+
+    let micro_bnpl_data = MicroBNPLAccount {
+        borrower: *user_account.key,
+        merchant: merchant_data.owner,
+        token_mint: *mint.key,
+        amount,
+        amount_repaid: 0,
+        paid_installments: 0,
+        next_payment_due: current_time + (MICRO_BNPL_INTERVAL_DAYS * 86400),
+        escrowed_amount: if escrow { amount } else { 0 },
+        escrow_release_at: if escrow { current_time + (MICRO_BNPL_ESCROW_TIMEOUT_DAYS * 86400) } else { 0 },
+        created_at: current_time,
+        reminded_due: 0,
+        snoozed: false,
+        held_at: 0,
+        prepayment_mode: PREPAYMENT_NONE,
+        refunded_amount: 0,
+        accrued_late_fees: 0,
+        days_overdue: 0,
+        grace_period_days: get_grace_period_days(wallet_data.card_type, NFT_NONE),
+        previous_merchant: Pubkey::default(),
+        staking_account: *staking_account.key,
+        stake_hold,
+        installment_covered: 0,
+        bump: micro_bnpl_bump,
+    };
+
+    micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+
+    staking_data.loan_hold = staking_data.loan_hold.checked_add(stake_hold).ok_or(FlexfiError::MathOverflow)?;
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    register_borrower_contract(
+        program_id,
+        registry_account,
+        user_account,
+        system_program,
+        micro_bnpl_account.key,
+        amount,
+        current_time,
+    )?;
+
+    msg!("Micro-BNPL created: {} to merchant {}{}, first installment due at {}",
+        amount, merchant_data.owner, if escrow { " in escrow" } else { "" }, micro_bnpl_data.next_payment_due);
+    Ok(())
+}
+
+// Give the stake held for a micro loan back to its position once nothing is owed on the loan
+pub(crate) fn release_stake_hold(
+    program_id: &Pubkey,
+    micro_bnpl_data: &mut MicroBNPLAccount,
+    staking_account: &AccountInfo,
+) -> ProgramResult {
+    if *staking_account.key != micro_bnpl_data.staking_account {
+        return Err(key_mismatch(staking_account, &micro_bnpl_data.staking_account, ProgramError::InvalidAccountData));
+    }
+
+    if micro_bnpl_data.remaining_amount() > 0 || micro_bnpl_data.stake_hold == 0 {
+        return Ok(());
+    }
+
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+    staking_data.loan_hold = staking_data.loan_hold.saturating_sub(micro_bnpl_data.stake_hold);
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    micro_bnpl_data.stake_hold = 0;
+    Ok(())
+}
+
+// Close a micro loan account and refund its rent to the borrower
+pub(crate) fn close_micro_bnpl_account(
+    micro_bnpl_account: &AccountInfo,
+    borrower_account: &AccountInfo,
+) -> ProgramResult {
+    let micro_bnpl_lamports = micro_bnpl_account.lamports();
+    **borrower_account.lamports.borrow_mut() = borrower_account.lamports()
+        .checked_add(micro_bnpl_lamports)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **micro_bnpl_account.lamports.borrow_mut() = 0;
+
+    micro_bnpl_account.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+// Pay an escrowed micro loan out to the merchant: on delivery confirmed by the borrower or the
+// merchant, or by anyone once the timeout passed. The emptied vault is closed to the borrower.
+pub fn process_release_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let micro_bnpl_account = next_account_info(account_info_iter)?;
+    let signer_account = next_account_info(account_info_iter)?;
+    let borrower_account = next_account_info(account_info_iter)?;
+    let escrow_vault_account = next_account_info(account_info_iter)?;
+    let merchant_account = next_account_info(account_info_iter)?;
+    let merchant_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    if !signer_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if micro_bnpl_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut micro_bnpl_data = MicroBNPLAccount::try_from_slice(&micro_bnpl_account.data.borrow())?;
+
+    if micro_bnpl_data.escrowed_amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if micro_bnpl_data.borrower != *borrower_account.key || micro_bnpl_data.token_mint != *mint.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let confirmed_by_party = *signer_account.key == micro_bnpl_data.borrower || *signer_account.key == micro_bnpl_data.merchant;
+
+    if !confirmed_by_party && clock.unix_timestamp < micro_bnpl_data.escrow_release_at {
+        msg!("Escrow can only be released by the parties before {}", micro_bnpl_data.escrow_release_at);
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // The vault and the merchant's current settlement account
+    let escrow_vault = spl_associated_token_account::get_associated_token_address_with_program_id(
+        micro_bnpl_account.key,
+        mint.key,
+        token_program.key,
+    );
+
+    if *escrow_vault_account.key != escrow_vault {
+        return Err(key_mismatch(escrow_vault_account, &escrow_vault, ProgramError::InvalidAccountData));
+    }
+
+    let merchant_data = load_merchant(program_id, merchant_account)?;
+
+    if merchant_data.owner != micro_bnpl_data.merchant {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let merchant_settlement_token = get_merchant_settlement_token(&merchant_data, mint.key, token_program.key);
+
+    if *merchant_token_account.key != merchant_settlement_token {
+        return Err(key_mismatch(merchant_token_account, &merchant_settlement_token, ProgramError::InvalidAccountData));
+    }
+
+    let escrowed_amount = micro_bnpl_data.escrowed_amount;
+    let micro_bnpl_seeds: &[&[u8]] = &[MICRO_BNPL_SEED, micro_bnpl_data.borrower.as_ref(), &[micro_bnpl_data.bump]];
+
+    transfer_checked(
+        token_program,
+        escrow_vault_account,
+        mint,
+        merchant_token_account,
+        micro_bnpl_account,
+        hook_accounts,
+        escrowed_amount,
+        &[micro_bnpl_seeds],
+    )?;
+
+    invoke_signed(
+        &spl_token_2022::instruction::close_account(
+            token_program.key,
+            escrow_vault_account.key,
+            borrower_account.key,
+            micro_bnpl_account.key,
+            &[],
+        )?,
+        &[escrow_vault_account.clone(), borrower_account.clone(), micro_bnpl_account.clone(), token_program.clone()],
+        &[micro_bnpl_seeds],
+    )?;
+
+    micro_bnpl_data.escrowed_amount = 0;
+    micro_bnpl_data.escrow_release_at = 0;
+
+    // A loan repaid while in escrow is closed now
+    if micro_bnpl_data.remaining_amount() == 0 {
+        close_micro_bnpl_account(micro_bnpl_account, borrower_account)?;
+    } else {
+        micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+    }
+
+    msg!("Escrow released: {} to merchant {}", escrowed_amount, micro_bnpl_data.merchant);
+    Ok(())
+}
+
+// Pay the next installment back to the treasury, with the late fees accrued on it if it is overdue;
+// the account is closed after the last one
+pub fn process_repay_micro_bnpl(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let micro_bnpl_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?; // The borrower or one of their device keys
+    let user_status_account = next_account_info(account_info_iter)?; // Whitelisting checked by the processor guard
+    let user_token_account = next_account_info(account_info_iter)?; // Owned by the signer
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?; // The user's borrower registry
+    let insurance_token_account = next_account_info(account_info_iter)?; // Receives the insurance slice of late fees
+    let staking_account = next_account_info(account_info_iter)?; // Position backing the loan, its hold released once repaid
+    let activity_accounts = ActivityAccounts {
+        schedule_account: next_account_info(account_info_iter)?,
+        epoch_activity_account: next_account_info(account_info_iter)?,
+        user_activity_account: next_account_info(account_info_iter)?,
+        system_program: next_account_info(account_info_iter)?,
+    };
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // A device key repays for the borrower it is registered to
+    let borrower = resolve_acting_user(program_id, user_account, user_status_account)?;
+
+    if micro_bnpl_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut micro_bnpl_data = MicroBNPLAccount::try_from_slice(&micro_bnpl_account.data.borrow())?;
+
+    if micro_bnpl_data.borrower != borrower {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if micro_bnpl_data.token_mint != *mint.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // No payments while a legal hold is in place
+    if micro_bnpl_data.remaining_amount() == 0 || micro_bnpl_data.is_held() {
+        return Err(FlexfiError::LoanNotActive.into());
+    }
+
+    check_treasury_token_account(program_id, mint.key, token_program.key, treasury_token_account)?;
+    check_reserve_token_account(program_id, mint.key, token_program.key, insurance_token_account)?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    // An overdue installment is only paid together with its late fees, brought up to date first
+    micro_bnpl_data.accrue_late_fees(clock.unix_timestamp);
+
+    let installment = micro_bnpl_data.next_installment();
+    let late_fees = micro_bnpl_data.collect_late_fees();
+    let due_at = micro_bnpl_data.next_payment_due;
+    let insurance_share = get_insurance_share(late_fees, INSURANCE_PENALTY_SHARE_BPS);
+
+    transfer_checked(
+        token_program,
+        user_token_account,
+        mint,
+        treasury_token_account,
+        user_account,
+        hook_accounts,
+        installment.checked_add(late_fees - insurance_share).ok_or(FlexfiError::MathOverflow)?,
+        &[],
+    )?;
+
+    if insurance_share > 0 {
+        transfer_checked(
+            token_program,
+            user_token_account,
+            mint,
+            insurance_token_account,
+            user_account,
+            hook_accounts,
+            insurance_share,
+            &[],
+        )?;
+    }
+
+    // Settles the installment, together with whatever an insurance cover already paid on it
+    micro_bnpl_data.pay_installment(installment);
+
+    release_stake_hold(program_id, &mut micro_bnpl_data, staking_account)?;
+
+    // Installments paid on schedule earn repayment emissions: in their own period, without late fees
+    let on_schedule = clock.unix_timestamp <= due_at
+        && clock.unix_timestamp >= due_at - MICRO_BNPL_INTERVAL_DAYS * 86400;
+
+    if on_schedule && late_fees == 0 {
+        record_epoch_activity(program_id, &activity_accounts, &borrower, user_account, Activity::Repayment(installment))?;
+    }
+
+    record_registry_repayment(
+        program_id,
+        &borrower,
+        registry_account,
+        micro_bnpl_account.key,
+        installment,
+        micro_bnpl_data.remaining_amount() == 0,
+        clock.unix_timestamp,
+    )?;
+
+    // Repaid: close the account and refund the rent to the signing key, unless the vault still holds the payment
+    if micro_bnpl_data.remaining_amount() == 0 && micro_bnpl_data.escrowed_amount == 0 {
+        close_micro_bnpl_account(micro_bnpl_account, user_account)?;
+
+        msg!("Micro-BNPL repaid in full");
+        return Ok(());
+    }
+
+    if micro_bnpl_data.remaining_amount() == 0 {
+        micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+
+        msg!("Micro-BNPL repaid in full, closed once the escrow is released");
+        return Ok(());
+    }
+
+    micro_bnpl_data.next_payment_due += MICRO_BNPL_INTERVAL_DAYS * 86400;
+    micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+
+    msg!("Micro-BNPL installment paid: {} with {} late fees, remaining {}", installment, late_fees, micro_bnpl_data.remaining_amount());
+    Ok(())
+}
+
+pub struct MicroBNPL;
+
+impl MicroBNPL {
+    pub fn create(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        escrow: bool,
+    ) -> ProgramResult {
+        process_create_micro_bnpl(program_id, accounts, amount, escrow)
+    }
+
+    pub fn repay(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_repay_micro_bnpl(program_id, accounts)
+    }
+
+    pub fn release_escrow(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_release_escrow(program_id, accounts)
+    }
+}
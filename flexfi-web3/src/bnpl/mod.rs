@@ -1,3 +1,24 @@
 pub mod checker;
+pub mod reminder;
+pub mod micro;
+pub mod hold;
+pub mod calendar;
+pub mod prepay;
+pub mod refund;
+pub mod late_fee;
+pub mod registry;
+pub mod reassign;
+pub mod forgiveness;
+pub mod receipt;
 
 pub use checker::BNPLChecker;
+pub use reminder::{process_send_payment_reminder, process_snooze_payment, PaymentDueSoon};
+pub use micro::{process_create_micro_bnpl, process_repay_micro_bnpl, process_release_escrow};
+pub use hold::{process_place_legal_hold, process_release_legal_hold};
+pub use calendar::process_anchor_installment_calendar;
+pub use prepay::process_prepay_bnpl;
+pub use refund::process_refund_bnpl_contract;
+pub use late_fee::{process_check_repayment, process_set_keeper_bounty};
+pub use reassign::{process_reassign_merchant, MerchantReassigned};
+pub use forgiveness::{process_set_forgiveness_budget, process_forgive_penalty, PenaltyForgiven};
+pub use receipt::{process_issue_purchase_receipt, PurchaseReceiptIssued};
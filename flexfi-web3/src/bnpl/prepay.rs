@@ -0,0 +1,130 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::BorshSerialize;
+
+use crate::error::FlexfiError;
+use crate::bnpl::micro::{load_open_micro_bnpl, check_treasury_token_account, close_micro_bnpl_account, release_stake_hold};
+use crate::token::transfer_checked;
+use crate::error_context::amount_mismatch;
+use crate::bnpl::registry::record_registry_repayment;
+use crate::insurance::reserve::{check_reserve_token_account, get_insurance_share};
+use crate::constants::INSURANCE_PENALTY_SHARE_BPS;
+
+// Pay an arbitrary extra amount on a micro loan back to the treasury, with any late fees accrued so far;
+// the mode picks whether the schedule gets shorter or the remaining installment smaller, and is recorded on the loan.
+// Only installments repaid on schedule earn repayment emissions, prepayments don't.
+pub fn process_prepay_bnpl(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    mode: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let micro_bnpl_account = next_account_info(account_info_iter)?;
+    let borrower_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Whitelisting checked by the processor guard
+    let borrower_token_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?; // The borrower's registry
+    let insurance_token_account = next_account_info(account_info_iter)?; // Receives the insurance slice of late fees
+    let staking_account = next_account_info(account_info_iter)?; // Position backing the loan, its hold released once repaid
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Check borrower signature
+    if !borrower_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Loans on hold take no payments
+    let mut micro_bnpl_data = load_open_micro_bnpl(program_id, micro_bnpl_account)?;
+
+    if micro_bnpl_data.borrower != *borrower_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if micro_bnpl_data.token_mint != *mint.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let remaining = micro_bnpl_data.remaining_amount();
+
+    if amount == 0 || amount > remaining {
+        return Err(amount_mismatch(Some(micro_bnpl_account), remaining, amount, ProgramError::InvalidArgument));
+    }
+
+    check_treasury_token_account(program_id, mint.key, token_program.key, treasury_token_account)?;
+    check_reserve_token_account(program_id, mint.key, token_program.key, insurance_token_account)?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    // Late fees are settled first, so a payoff doesn't leave them behind
+    micro_bnpl_data.accrue_late_fees(clock.unix_timestamp);
+    let late_fees = micro_bnpl_data.collect_late_fees();
+    let insurance_share = get_insurance_share(late_fees, INSURANCE_PENALTY_SHARE_BPS);
+
+    micro_bnpl_data.apply_prepayment(amount, mode)?;
+    release_stake_hold(program_id, &mut micro_bnpl_data, staking_account)?;
+
+    transfer_checked(
+        token_program,
+        borrower_token_account,
+        mint,
+        treasury_token_account,
+        borrower_account,
+        hook_accounts,
+        amount.checked_add(late_fees - insurance_share).ok_or(FlexfiError::MathOverflow)?,
+        &[],
+    )?;
+
+    if insurance_share > 0 {
+        transfer_checked(
+            token_program,
+            borrower_token_account,
+            mint,
+            insurance_token_account,
+            borrower_account,
+            hook_accounts,
+            insurance_share,
+            &[],
+        )?;
+    }
+
+    record_registry_repayment(
+        program_id,
+        borrower_account.key,
+        registry_account,
+        micro_bnpl_account.key,
+        amount,
+        micro_bnpl_data.remaining_amount() == 0,
+        clock.unix_timestamp,
+    )?;
+
+    // Paid off: close the account as a last repayment would, unless the vault still holds the payment
+    if micro_bnpl_data.remaining_amount() == 0 && micro_bnpl_data.escrowed_amount == 0 {
+        close_micro_bnpl_account(micro_bnpl_account, borrower_account)?;
+
+        msg!("Prepaid {} with {} late fees: micro-BNPL repaid in full", amount, late_fees);
+        return Ok(());
+    }
+
+    micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+
+    msg!(
+        "Prepaid {} with {} late fees: {} left, next installment {}",
+        amount,
+        late_fees,
+        micro_bnpl_data.remaining_amount(),
+        micro_bnpl_data.next_installment()
+    );
+    Ok(())
+}
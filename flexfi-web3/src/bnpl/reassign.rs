@@ -0,0 +1,94 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    log::sol_log_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::bnpl::MicroBNPLAccount;
+use crate::core::whitelist::require_whitelist_authority;
+use crate::merchant::registry::load_merchant;
+
+// Emitted when a contract changes merchant so the settlement side can follow it
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct MerchantReassigned {
+    pub contract: Pubkey,
+    pub old_merchant: Pubkey,
+    pub new_merchant: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+// Move a micro loan still owed to another registered merchant, e.g. after the store was acquired or the
+// merchant rotated wallets; refunds, escrow releases and other merchant flows use the new merchant from then on
+pub fn process_reassign_merchant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    contract: Pubkey,
+    new_merchant: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let contract_account = next_account_info(account_info_iter)?;
+    let new_merchant_account = next_account_info(account_info_iter)?; // The new merchant's registry account
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    if *contract_account.key != contract {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if contract_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut contract_data = MicroBNPLAccount::try_from_slice(&contract_account.data.borrow())?;
+
+    if contract_data.remaining_amount() == 0 {
+        return Err(FlexfiError::LoanNotActive.into());
+    }
+
+    if contract_data.merchant == new_merchant {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The new merchant must be registered and active
+    let merchant_data = load_merchant(program_id, new_merchant_account)?;
+
+    if merchant_data.owner != new_merchant {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if !merchant_data.is_active(clock.unix_timestamp) {
+        return Err(FlexfiError::MerchantNotActive.into());
+    }
+
+    let old_merchant = contract_data.merchant;
+    contract_data.previous_merchant = old_merchant;
+    contract_data.merchant = new_merchant;
+    contract_data.serialize(&mut *contract_account.data.borrow_mut())?;
+
+    let event = MerchantReassigned {
+        contract,
+        old_merchant,
+        new_merchant,
+        authority: *authority.key,
+        timestamp: clock.unix_timestamp,
+    };
+
+    sol_log_data(&[b"MerchantReassigned", &event.try_to_vec()?]);
+
+    msg!("Contract {} moved from merchant {} to {} by {}", contract, old_merchant, new_merchant, authority.key);
+    Ok(())
+}
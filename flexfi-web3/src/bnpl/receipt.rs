@@ -0,0 +1,141 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    log::sol_log_data,
+    program::{invoke_signed, set_return_data},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::bnpl::MicroBNPLAccount;
+use crate::state::receipt::PurchaseReceiptAccount;
+use crate::constants::PURCHASE_RECEIPT_SEED;
+
+// Emitted with the receipt so the commitment can be kept off-chain alongside the purchase
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PurchaseReceiptIssued {
+    pub receipt: Pubkey,
+    pub contract: Pubkey,
+    pub borrower: Pubkey,
+    pub merchant: Pubkey,
+    pub amount: u64,
+    pub purchased_at: i64,
+    pub commitment: [u8; 32],
+}
+
+// Issue the receipt of a micro loan, with a keccak commitment of its fields (see
+// PurchaseReceiptAccount::compute_commitment) a merchant can check without an RPC call. Requested by
+// the borrower or the merchant, once per loan and before it is repaid and closed.
+pub fn process_issue_purchase_receipt(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let receipt_account = next_account_info(account_info_iter)?;
+    let contract_account = next_account_info(account_info_iter)?; // Micro loan
+    let payer_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    if !payer_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if contract_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if contract_account.data_len() != MicroBNPLAccount::SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let micro_bnpl_data = MicroBNPLAccount::try_from_slice(&contract_account.data.borrow())?;
+    let (borrower, merchant, token_mint, amount, purchased_at) = (
+        micro_bnpl_data.borrower,
+        micro_bnpl_data.merchant,
+        micro_bnpl_data.token_mint,
+        micro_bnpl_data.amount,
+        micro_bnpl_data.created_at,
+    );
+
+    if *payer_account.key != borrower && *payer_account.key != merchant {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let (receipt_pda, receipt_bump) = Pubkey::find_program_address(
+        &[PURCHASE_RECEIPT_SEED, contract_account.key.as_ref()],
+        program_id
+    );
+
+    if *receipt_account.key != receipt_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !receipt_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::get()?;
+    let space = PurchaseReceiptAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account.key,
+            &receipt_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[payer_account.clone(), receipt_account.clone(), system_program.clone()],
+        &[&[PURCHASE_RECEIPT_SEED, contract_account.key.as_ref(), &[receipt_bump]]],
+    )?;
+
+    let commitment = PurchaseReceiptAccount::compute_commitment(
+        contract_account.key,
+        &borrower,
+        &merchant,
+        &token_mint,
+        amount,
+        purchased_at,
+    );
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let receipt_data = PurchaseReceiptAccount {
+        contract: *contract_account.key,
+        borrower,
+        merchant,
+        token_mint,
+        amount,
+        purchased_at,
+        issued_at: clock.unix_timestamp,
+        commitment,
+        bump: receipt_bump,
+    };
+
+    receipt_data.serialize(&mut *receipt_account.data.borrow_mut())?;
+
+    let event = PurchaseReceiptIssued {
+        receipt: receipt_pda,
+        contract: *contract_account.key,
+        borrower,
+        merchant,
+        amount,
+        purchased_at,
+        commitment,
+    };
+
+    sol_log_data(&[b"PurchaseReceiptIssued", &event.try_to_vec()?]);
+
+    set_return_data(&commitment);
+
+    msg!("Receipt issued for contract {}: {} from {}", contract_account.key, amount, merchant);
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::bnpl::MicroBNPLAccount;
+use crate::constants::TREASURY_SEED;
+use crate::bnpl::micro::{check_treasury_token_account, close_micro_bnpl_account, release_stake_hold};
+use crate::token::{check_token_account, transfer_checked};
+use crate::error_context::amount_mismatch;
+use crate::bnpl::registry::record_registry_repayment;
+
+// The merchant returns part or all of a micro loan purchase to the treasury, which fronted it. The
+// refund comes off what the borrower still owes; whatever the borrower already paid beyond the new
+// price is sent back. A loan with nothing left to pay is closed to the borrower.
+pub fn process_refund_bnpl_contract(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let micro_bnpl_account = next_account_info(account_info_iter)?;
+    let merchant_account = next_account_info(account_info_iter)?;
+    let merchant_token_account = next_account_info(account_info_iter)?;
+    let treasury_authority = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let borrower_token_account = next_account_info(account_info_iter)?;
+    let borrower_account = next_account_info(account_info_iter)?; // Receives the rent once the loan is closed
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?; // The borrower's registry
+    let staking_account = next_account_info(account_info_iter)?; // Position backing the loan, its hold released once settled
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Check merchant signature
+    if !merchant_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if micro_bnpl_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut micro_bnpl_data = MicroBNPLAccount::try_from_slice(&micro_bnpl_account.data.borrow())?;
+
+    if micro_bnpl_data.merchant != *merchant_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if micro_bnpl_data.token_mint != *mint.key || micro_bnpl_data.borrower != *borrower_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Loans on hold can be refunded, e.g. to settle a dispute; an escrowed payment never reached the merchant
+    if micro_bnpl_data.remaining_amount() == 0 || micro_bnpl_data.escrowed_amount > 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let refundable = micro_bnpl_data.amount.saturating_sub(micro_bnpl_data.refunded_amount);
+
+    if amount == 0 || amount > refundable {
+        return Err(amount_mismatch(Some(micro_bnpl_account), refundable, amount, ProgramError::InvalidArgument));
+    }
+
+    let treasury_bump = check_treasury_token_account(program_id, mint.key, token_program.key, treasury_token_account)?;
+    check_token_account(borrower_token_account, &micro_bnpl_data.borrower, mint.key)?;
+
+    let owed_before = micro_bnpl_data.remaining_amount();
+    let borrower_refund = micro_bnpl_data.apply_refund(amount)?;
+    release_stake_hold(program_id, &mut micro_bnpl_data, staking_account)?;
+
+    transfer_checked(
+        token_program,
+        merchant_token_account,
+        mint,
+        treasury_token_account,
+        merchant_account,
+        hook_accounts,
+        amount,
+        &[],
+    )?;
+
+    if borrower_refund > 0 {
+        transfer_checked(
+            token_program,
+            treasury_token_account,
+            mint,
+            borrower_token_account,
+            treasury_authority,
+            hook_accounts,
+            borrower_refund,
+            &[&[TREASURY_SEED, mint.key.as_ref(), &[treasury_bump]]],
+        )?;
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    record_registry_repayment(
+        program_id,
+        &micro_bnpl_data.borrower,
+        registry_account,
+        micro_bnpl_account.key,
+        owed_before - micro_bnpl_data.remaining_amount(),
+        micro_bnpl_data.remaining_amount() == 0,
+        clock.unix_timestamp,
+    )?;
+
+    if micro_bnpl_data.remaining_amount() == 0 {
+        close_micro_bnpl_account(micro_bnpl_account, borrower_account)?;
+    } else {
+        micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+    }
+
+    msg!(
+        "Merchant refunded {} ({} in total): {} returned to the borrower, {} still owed",
+        amount,
+        micro_bnpl_data.refunded_amount,
+        borrower_refund,
+        micro_bnpl_data.remaining_amount()
+    );
+    Ok(())
+}
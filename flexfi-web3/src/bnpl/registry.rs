@@ -0,0 +1,127 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{rent::Rent, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::bnpl::BorrowerRegistryAccount;
+use crate::constants::BORROWER_REGISTRY_SEED;
+use crate::error_context::key_mismatch;
+
+// Check the borrower's registry PDA and return its bump
+fn check_borrower_registry(
+    program_id: &Pubkey,
+    borrower: &Pubkey,
+    registry_account: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (registry_pda, registry_bump) = Pubkey::find_program_address(
+        &[BORROWER_REGISTRY_SEED, borrower.as_ref()],
+        program_id
+    );
+
+    if *registry_account.key != registry_pda {
+        return Err(key_mismatch(registry_account, &registry_pda, ProgramError::InvalidAccountData));
+    }
+
+    Ok(registry_bump)
+}
+
+// Load a borrower's registry; None when the borrower has not taken a loan since registries exist
+pub(crate) fn load_borrower_registry(
+    program_id: &Pubkey,
+    borrower: &Pubkey,
+    registry_account: &AccountInfo,
+) -> Result<Option<BorrowerRegistryAccount>, ProgramError> {
+    check_borrower_registry(program_id, borrower, registry_account)?;
+
+    if registry_account.owner != program_id || registry_account.data_is_empty() {
+        return Ok(None);
+    }
+
+    let registry_data = BorrowerRegistryAccount::try_from_slice(&registry_account.data.borrow())?;
+    Ok(Some(registry_data))
+}
+
+// Debt still owed on the borrower's tracked loans
+pub(crate) fn get_outstanding_debt(
+    program_id: &Pubkey,
+    borrower: &Pubkey,
+    registry_account: &AccountInfo,
+) -> Result<u64, ProgramError> {
+    let registry_data = load_borrower_registry(program_id, borrower, registry_account)?;
+    Ok(registry_data.map_or(0, |registry| registry.outstanding_debt))
+}
+
+// Track a new loan, creating the registry (paid by the borrower) on the first one
+pub(crate) fn register_borrower_contract<'a>(
+    program_id: &Pubkey,
+    registry_account: &AccountInfo<'a>,
+    borrower_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    contract: &Pubkey,
+    amount: u64,
+    current_time: i64,
+) -> ProgramResult {
+    let registry_bump = check_borrower_registry(program_id, borrower_account.key, registry_account)?;
+
+    let mut registry_data = if registry_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let space = BorrowerRegistryAccount::SIZE;
+        let rent_lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                borrower_account.key,
+                registry_account.key,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[borrower_account.clone(), registry_account.clone(), system_program.clone()],
+            &[&[BORROWER_REGISTRY_SEED, borrower_account.key.as_ref(), &[registry_bump]]],
+        )?;
+
+        BorrowerRegistryAccount::new(*borrower_account.key, current_time, registry_bump)
+    } else {
+        BorrowerRegistryAccount::try_from_slice(&registry_account.data.borrow())?
+    };
+
+    if !registry_data.register(*contract, amount, current_time) {
+        msg!("Contract {} is already tracked or the registry is full", contract);
+        return Err(FlexfiError::BorrowerRegistryFull.into());
+    }
+
+    registry_data.serialize(&mut *registry_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// Take a repayment off the borrower's registry when the loan is tracked there; a settled loan is removed
+pub(crate) fn record_registry_repayment(
+    program_id: &Pubkey,
+    borrower: &Pubkey,
+    registry_account: &AccountInfo,
+    contract: &Pubkey,
+    amount: u64,
+    settled: bool,
+    current_time: i64,
+) -> ProgramResult {
+    let mut registry_data = match load_borrower_registry(program_id, borrower, registry_account)? {
+        Some(registry_data) => registry_data,
+        None => return Ok(()),
+    };
+
+    if !registry_data.contains(contract) {
+        return Ok(());
+    }
+
+    registry_data.record_repayment(contract, amount, settled, current_time);
+    registry_data.serialize(&mut *registry_account.data.borrow_mut())?;
+    Ok(())
+}
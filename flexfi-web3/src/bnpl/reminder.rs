@@ -0,0 +1,141 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    log::sol_log_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::nft::perks::{NFTPerk, NFTPerkChecker};
+use crate::constants::{PAYMENT_REMINDER_DAYS, PAYMENT_SNOOZE_SECS};
+use crate::bnpl::micro::load_open_micro_bnpl;
+
+// Emitted ahead of an installment so off-chain services can notify the borrower
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PaymentDueSoon {
+    pub contract: Pubkey,
+    pub borrower: Pubkey,
+    pub amount_due: u64,
+    pub due_at: i64,
+    pub installment: u8,
+}
+
+// Permissionless crank: emit PaymentDueSoon once per due date of a micro loan, PAYMENT_REMINDER_DAYS before it
+pub fn process_send_payment_reminder(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let micro_bnpl_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    let mut micro_bnpl_data = load_open_micro_bnpl(program_id, micro_bnpl_account)?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if !micro_bnpl_data.needs_reminder(clock.unix_timestamp, PAYMENT_REMINDER_DAYS * 86400) {
+        msg!("No reminder due for this loan");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let event = PaymentDueSoon {
+        contract: *micro_bnpl_account.key,
+        borrower: micro_bnpl_data.borrower,
+        amount_due: micro_bnpl_data.next_installment(),
+        due_at: micro_bnpl_data.next_payment_due,
+        installment: micro_bnpl_data.paid_installments + 1,
+    };
+
+    sol_log_data(&[b"PaymentDueSoon", &event.try_to_vec()?]);
+
+    micro_bnpl_data.reminded_due = micro_bnpl_data.next_payment_due;
+    micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+
+    msg!("Payment reminder sent: {} due at {}", event.amount_due, event.due_at);
+    Ok(())
+}
+
+// Push the next due date of a micro loan back by 72 hours without penalty; once per loan, for NFTs
+// with extended payment terms
+pub fn process_snooze_payment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let micro_bnpl_account = next_account_info(account_info_iter)?;
+    let borrower_account = next_account_info(account_info_iter)?;
+    let nft_metadata_account = next_account_info(account_info_iter)?;
+    let attachment_account = next_account_info(account_info_iter)?;
+    let nft_mint = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check borrower signature
+    if !borrower_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut micro_bnpl_data = load_open_micro_bnpl(program_id, micro_bnpl_account)?;
+
+    if micro_bnpl_data.borrower != *borrower_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if micro_bnpl_data.snoozed {
+        return Err(FlexfiError::PaymentAlreadySnoozed.into());
+    }
+
+    // Snoozing is a perk of the borrower's NFT
+    let has_perk = NFTPerkChecker::check_perk(
+        program_id,
+        &[
+            nft_metadata_account.clone(),
+            attachment_account.clone(),
+            nft_mint.clone(),
+            borrower_account.clone(),
+            clock_sysvar.clone(),
+        ],
+        NFTPerk::ExtendedPaymentTerms,
+    )?;
+
+    if !has_perk {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Only an upcoming installment can be snoozed
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if micro_bnpl_data.is_payment_due(clock.unix_timestamp) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    micro_bnpl_data.next_payment_due += PAYMENT_SNOOZE_SECS;
+    micro_bnpl_data.snoozed = true;
+    micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+
+    msg!("Payment snoozed, next payment due at {}", micro_bnpl_data.next_payment_due);
+    Ok(())
+}
+
+pub struct PaymentReminder;
+
+impl PaymentReminder {
+    pub fn remind(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_send_payment_reminder(program_id, accounts)
+    }
+
+    pub fn snooze(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_snooze_payment(program_id, accounts)
+    }
+}
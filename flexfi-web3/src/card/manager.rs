@@ -2,10 +2,16 @@ use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program::{invoke, invoke_signed},
+    program::invoke_signed,
     pubkey::Pubkey,
     system_instruction,
-    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    sysvar::{
+        clock::Clock,
+        Sysvar,
+        rent::Rent,
+        instructions::{self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked},
+    },
+    ed25519_program,
     msg,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -13,9 +19,13 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use crate::error::FlexfiError;
 use crate::state::wallet::WalletAccount;
 use crate::state::card::CardAccount;
-use crate::constants::{CARD_STANDARD, CARD_SILVER, CARD_GOLD, CARD_PLATINUM, CARD_SEED};
+use crate::state::staking::StakingAccount;
+use crate::core::staking_tier::get_staking_tier;
+use crate::constants::{CARD_STANDARD, CARD_SILVER, CARD_GOLD, CARD_PLATINUM, CARD_SEED, CARD_PROOF_LEN, CARD_PROOF_MAX_AGE_SECS};
+use crate::core::whitelist::parse_ed25519_message;
 use crate::card::config::get_card_annual_fee;
-use crate::core::whitelist::require_whitelisted;
+use crate::token::transfer_checked;
+use crate::math::{bps_share, Rounding};
 
 pub fn process_upgrade_card(
     program_id: &Pubkey,
@@ -27,24 +37,22 @@ pub fn process_upgrade_card(
     let wallet_account = next_account_info(account_info_iter)?;
     let card_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
     let user_token_account = next_account_info(account_info_iter)?;
     let fee_account = next_account_info(account_info_iter)?;
+    let fee_mint = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
+    let staking_account = next_account_info(account_info_iter)?;
+    let staking_tier_config_account = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
 
     // Check user signature
     if !user_account.is_signer {
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    require_whitelisted(
-        program_id,
-        user_account.key,
-        user_status_account
-    )?;
-
     // Check if the card type is valid
     if new_card_type > CARD_PLATINUM {
         return Err(FlexfiError::InvalidCardType.into());
@@ -76,7 +84,23 @@ pub fn process_upgrade_card(
     let current_fee = get_card_annual_fee(wallet_data.card_type)?;
     let new_fee = get_card_annual_fee(new_card_type)?;
 
-    let upgrade_fee = new_fee.saturating_sub(current_fee);
+    // Apply the staking tier fee discount
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    if staking_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let staking_tier = get_staking_tier(program_id, staking_tier_config_account, staking_data.amount_staked)?;
+
+    let full_upgrade_fee = new_fee.saturating_sub(current_fee);
+    let upgrade_fee = full_upgrade_fee.saturating_sub(
+        bps_share(full_upgrade_fee, staking_tier.fee_discount_bps, Rounding::Down)
+    );
 
     // Get current timestamp
     let clock = Clock::from_account_info(clock_sysvar)?;
@@ -92,8 +116,9 @@ pub fn process_upgrade_card(
             return Err(FlexfiError::Unauthorized.into());
         }
 
-        // Update card type
+        // Update card type (benefits never fall below the paid card type)
         card_data.card_type = new_card_type;
+        card_data.benefits_tier = std::cmp::max(card_data.benefits_tier, new_card_type);
 
         // Update annual fee expiration date
         card_data.annual_fee_paid_until = current_time + (365 * 86400);
@@ -147,23 +172,15 @@ pub fn process_upgrade_card(
 
     // Transfer upgrade fees if necessary
     if upgrade_fee > 0 {
-        let transfer_ix = spl_token::instruction::transfer(
-            token_program.key,
-            user_token_account.key,
-            fee_account.key,
-            user_account.key,
-            &[],
+        transfer_checked(
+            token_program,
+            user_token_account,
+            fee_mint,
+            fee_account,
+            user_account,
+            hook_accounts,
             upgrade_fee,
-        )?;
-
-        invoke(
-            &transfer_ix,
-            &[
-                user_token_account.clone(),
-                fee_account.clone(),
-                user_account.clone(),
-                token_program.clone(),
-            ],
+            &[],
         )?;
     }
 
@@ -171,6 +188,173 @@ pub fn process_upgrade_card(
     Ok(())
 }
 
+// Look for a signature of the card among the transaction's earlier instructions, signed less than
+// CARD_PROOF_MAX_AGE_SECS ago. The ed25519 program has already verified it when this runs.
+fn has_card_proof(
+    program_id: &Pubkey,
+    card: &Pubkey,
+    verification_key: &Pubkey,
+    instructions_account: &AccountInfo,
+) -> Result<bool, ProgramError> {
+    let current_index = load_current_index_checked(instructions_account)?;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_account)?;
+
+        if instruction.program_id != ed25519_program::id() {
+            continue;
+        }
+
+        let message = match parse_ed25519_message(&instruction.data, verification_key) {
+            Some(message) if message.len() == CARD_PROOF_LEN => message,
+            _ => continue,
+        };
+
+        if &message[..32] != program_id.as_ref() || &message[32..64] != card.as_ref() {
+            continue;
+        }
+
+        let signed_at = i64::from_le_bytes(message[64..72].try_into().map_err(|_| ProgramError::InvalidInstructionData)?);
+
+        if signed_at <= current_time && current_time - signed_at <= CARD_PROOF_MAX_AGE_SECS {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+// Store the card verification key, derived off-chain from the salted card data so it commits to the
+// card without revealing it
+pub fn process_set_card_commitment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    verification_key: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let card_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check the card account
+    let (card_pda, _) = Pubkey::find_program_address(
+        &[CARD_SEED, user_account.key.as_ref()],
+        program_id,
+    );
+
+    if *card_account.key != card_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Load card data
+    let mut card_data = CardAccount::try_from_slice(&card_account.data.borrow())?;
+
+    // Verify that the user is the owner
+    if card_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Update the commitment (the default key clears it)
+    card_data.verification_key = verification_key;
+    card_data.serialize(&mut *card_account.data.borrow_mut())?;
+
+    msg!("Card verification commitment updated for {}", user_account.key);
+    Ok(())
+}
+
+// Prove card linkage with a fresh ed25519 signature by the card verification key over
+// (program id || card || signed_at), verified by an earlier ed25519 program instruction of the
+// transaction; neither the card data nor its salt is ever sent
+pub fn process_verify_card_commitment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let card_account = next_account_info(account_info_iter)?;
+    let instructions_account = next_account_info(account_info_iter)?;
+
+    // Check the card account
+    if card_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let card_data = CardAccount::try_from_slice(&card_account.data.borrow())?;
+
+    let (card_pda, _) = Pubkey::find_program_address(
+        &[CARD_SEED, card_data.owner.as_ref()],
+        program_id,
+    );
+
+    if *card_account.key != card_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if *instructions_account.key != instructions_sysvar::id() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !card_data.has_commitment() || !has_card_proof(program_id, card_account.key, &card_data.verification_key, instructions_account)? {
+        msg!("Card verification failed for {}", card_data.owner);
+        return Err(FlexfiError::CardCommitmentMismatch.into());
+    }
+
+    msg!("Card verification successful for {}", card_data.owner);
+    Ok(())
+}
+
+// Yearly crank re-evaluating the benefits tier from the card's spend volume
+pub fn process_review_card_tier(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let card_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check the card account
+    if card_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut card_data = CardAccount::try_from_slice(&card_account.data.borrow())?;
+
+    let (card_pda, _) = Pubkey::find_program_address(
+        &[CARD_SEED, card_data.owner.as_ref()],
+        program_id,
+    );
+
+    if *card_account.key != card_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    if !card_data.is_tier_review_due(current_time) {
+        msg!("Tier review not due until {}", card_data.last_tier_review);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let previous_tier = card_data.benefits_tier;
+    let period_spend = card_data.current_period_spend;
+
+    card_data.review_benefits_tier(current_time);
+    card_data.serialize(&mut *card_account.data.borrow_mut())?;
+
+    msg!("Card tier reviewed: spend {}, tier {} -> {}", period_spend, previous_tier, card_data.benefits_tier);
+    Ok(())
+}
+
 pub struct CardManager;
 
 impl CardManager {
@@ -181,4 +365,26 @@ impl CardManager {
     ) -> ProgramResult {
         process_upgrade_card(program_id, accounts, new_card_type)
     }
+
+    pub fn set_commitment(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        verification_key: Pubkey,
+    ) -> ProgramResult {
+        process_set_card_commitment(program_id, accounts, verification_key)
+    }
+
+    pub fn verify_commitment(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_verify_card_commitment(program_id, accounts)
+    }
+
+    pub fn review_tier(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_review_card_tier(program_id, accounts)
+    }
 }
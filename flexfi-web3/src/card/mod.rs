@@ -2,4 +2,4 @@ pub mod config;
 pub mod manager;
 
 pub use config::{get_card_annual_fee, is_installment_allowed_for_card, get_max_installments_for_card};
-pub use manager::process_upgrade_card;
\ No newline at end of file
+pub use manager::{process_upgrade_card, process_set_card_commitment, process_verify_card_commitment, process_review_card_tier};
\ No newline at end of file
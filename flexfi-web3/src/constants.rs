@@ -7,6 +7,16 @@ pub const CARD_STANDARD: u8 = 0;
 pub const CARD_SILVER: u8 = 1;
 pub const CARD_GOLD: u8 = 2;
 pub const CARD_PLATINUM: u8 = 3;
+pub const CARD_TYPE_COUNT: usize = 4;
+
+// Loyalty tier review (annual spend needed for each benefits tier, 6 decimals)
+pub const CARD_TIER_REVIEW_PERIOD_DAYS: i64 = 365;
+pub const CARD_TIER_SPEND_THRESHOLDS: [u64; CARD_TYPE_COUNT] = [
+    0,                  // Standard
+    5_000_000_000,      // Silver: 5,000 USDC
+    20_000_000_000,     // Gold: 20,000 USDC
+    50_000_000_000,     // Platinum: 50,000 USDC
+];
 
 // NFT types
 pub const NFT_NONE: u8 = 0;
@@ -14,13 +24,49 @@ pub const NFT_BRONZE: u8 = 1;
 pub const NFT_SILVER: u8 = 2;
 pub const NFT_GOLD: u8 = 3;
 
+// Staking freeze reason codes
+pub const FREEZE_REASON_NONE: u8 = 0;
+pub const FREEZE_REASON_COMPLIANCE: u8 = 1;
+pub const FREEZE_REASON_DISPUTE: u8 = 2;
+pub const FREEZE_REASON_FRAUD: u8 = 3;
+
 // Fee-related constants
 pub const DEFAULT_FEE_PERCENTAGE: u16 = 700; // 7.00%
 pub const MINIMUM_FEE_PERCENTAGE: u16 = 300;  // 3.00%
 pub const MAXIMUM_FEE_PERCENTAGE: u16 = 700; // 7.00%
 
+// Insurance reserve shares, in basis points
+pub const INSURANCE_FEE_SHARE_BPS: u16 = 500; // 5% of BNPL fees
+pub const INSURANCE_PENALTY_SHARE_BPS: u16 = 2500; // 25% of late-payment penalties
+
+// Whitelist entries expire and must be renewed after a new KYC check
+pub const WHITELIST_VALIDITY_DAYS: i64 = 365;
+pub const MAX_AUTHORIZED_KEYS: usize = 3; // Device/session keys per whitelisted user
+pub const WHITELIST_ATTESTATION_LEN: usize = 32 + 32 + 8; // program id || user || expires_at
+pub const CARD_PROOF_LEN: usize = 32 + 32 + 8; // program id || card || signed_at
+pub const CARD_PROOF_MAX_AGE_SECS: i64 = 300;
+
+// Role-based admin list
+pub const MAX_ADMINS: usize = 10;
+
+// Wallet social recovery
+pub const MAX_WALLET_GUARDIANS: usize = 5;
+pub const WALLET_RECOVERY_TIMELOCK_SECS: i64 = 72 * 3600; // 72 hours
+
+// Charitable donations on spends
+pub const MAX_DONATION_BPS: u16 = 1000; // 10%
+pub const SECONDS_PER_YEAR: i64 = 31_556_952; // Average Gregorian year
+
+// Promo codes
+pub const MAX_PROMO_DISCOUNT_BPS: u16 = 5000; // 50%
+pub const MAX_PROMO_CODE_LEN: usize = 32;
+
+// Credit held for pending merchant charges
+pub const MAX_CREDIT_RESERVATIONS: usize = 4;
+pub const PENDING_CHARGE_TTL_SECS: i64 = 900; // 15 minutes to approve a charge
+
 // NFT minting cost
-pub const NFT_MINT_COST: u64 = 20_000_000; // 20 USDC (with 6 decimals)
+pub const NFT_MINT_COST: u64 = 20_000_000; // 20 USD (6 decimals), payable in any accepted mint
 
 // Card configurations (APR, BNPL fees, installments)
 pub struct CardConfig {
@@ -32,6 +78,7 @@ pub struct CardConfig {
     pub cashback_percentage: u16,      // Cashback in basis points
     pub cashback_limit: u64,           // Monthly cashback limit in USDC (with 6 decimals)
     pub nft_cost: u64,                 // NFT cost in USDC (with 6 decimals)
+    pub grace_period_days: u8,         // Days of late fees before an installment defaults
 }
 
 // Get the configuration of a card
@@ -46,6 +93,7 @@ pub fn get_card_config(card_type: u8) -> CardConfig {
             cashback_percentage: 0,        // No cashback
             cashback_limit: 0,             // No limit
             nft_cost: 0,                   // Standard does not include NFT
+            grace_period_days: 10,         // 10 days
         },
         CARD_SILVER => CardConfig {
             apr_percentage: 500,           // 5%
@@ -56,6 +104,7 @@ pub fn get_card_config(card_type: u8) -> CardConfig {
             cashback_percentage: 0,        // No cashback
             cashback_limit: 0,             // No limit
             nft_cost: 20_000_000,          // 20 USDC
+            grace_period_days: 15,         // 15 days
         },
         CARD_GOLD => CardConfig {
             apr_percentage: 600,           // 6%
@@ -66,6 +115,7 @@ pub fn get_card_config(card_type: u8) -> CardConfig {
             cashback_percentage: 50,       // 0.5%
             cashback_limit: 150_000_000,   // 150 USDC
             nft_cost: 15_000_000,          // 15 USDC
+            grace_period_days: 20,         // 20 days
         },
         CARD_PLATINUM => CardConfig {
             apr_percentage: 700,           // 7%
@@ -76,6 +126,7 @@ pub fn get_card_config(card_type: u8) -> CardConfig {
             cashback_percentage: 150,      // 1.5%
             cashback_limit: 300_000_000,   // 300 USDC
             nft_cost: 0,                   // NFT included
+            grace_period_days: 30,         // 30 days
         },
         _ => CardConfig {                  // Default value (Standard)
             apr_percentage: 400,
@@ -86,6 +137,7 @@ pub fn get_card_config(card_type: u8) -> CardConfig {
             cashback_percentage: 0,
             cashback_limit: 0,
             nft_cost: 0,
+            grace_period_days: 10,
         },
     }
 }
@@ -113,19 +165,93 @@ pub fn get_late_payment_penalty(card_type: u8, nft_type: u8) -> u16 {
     }
 }
 
+// Get the extra grace days for a combination with NFT
+pub fn get_nft_grace_bonus_days(nft_type: u8) -> u8 {
+    match nft_type {
+        NFT_BRONZE => 2,
+        NFT_SILVER => 5,
+        NFT_GOLD => 10,
+        _ => 0,             // No bonus
+    }
+}
+
+// Get the grace period of a card+NFT combination, fixed on a micro loan when it is created
+pub fn get_grace_period_days(card_type: u8, nft_type: u8) -> u8 {
+    get_card_config(card_type).grace_period_days + get_nft_grace_bonus_days(nft_type)
+}
+
 // BNPL-related constants
 pub const MIN_BNPL_INSTALLMENTS: u8 = 3;
 pub const MAX_BNPL_INSTALLMENTS: u8 = 36;
 pub const MIN_PAYMENT_INTERVAL_DAYS: u8 = 15;
 pub const MAX_PAYMENT_INTERVAL_DAYS: u8 = 90;
 pub const DEFAULT_PAYMENT_INTERVAL_DAYS: u8 = 30;
-pub const GRACE_PERIOD_DAYS: u8 = 15;
+pub const LATE_FEE_DAILY_BPS: u16 = 10; // 0.10% of the overdue installment per day of the grace period
+pub const PAYMENT_REMINDER_DAYS: i64 = 3; // PaymentDueSoon is emitted this long before a due date
+pub const PAYMENT_SNOOZE_SECS: i64 = 72 * 3600; // 72 hours
+pub const PREPAYMENT_NONE: u8 = 0;
+pub const PREPAYMENT_SHORTEN_SCHEDULE: u8 = 1; // Same installments, fewer of them
+pub const PREPAYMENT_REDUCE_INSTALLMENTS: u8 = 2; // Same schedule, smaller installments
 pub const MAX_BNPL_PER_YEAR: u16 = 5;
+pub const MAX_BORROWER_CONTRACTS: usize = 8; // Open loans tracked per borrower registry
+pub const FORGIVENESS_PERIOD_DAYS: i64 = 30; // The penalty forgiveness budget resets each period
+pub const MAX_KEEPER_BOUNTY_LAMPORTS: u64 = 10_000_000; // 0.01 SOL per repayment check
+
+// Fee-free micro-BNPL for small baskets
+pub const MICRO_BNPL_INSTALLMENTS: u8 = 2;
+pub const MICRO_BNPL_INTERVAL_DAYS: i64 = 15;
+pub const DEFAULT_MICRO_BNPL_THRESHOLD: u64 = 50_000_000; // 50 USDC (6 decimals)
+pub const DEFAULT_MICRO_BNPL_LTV_BPS: u16 = 15_000; // 150% of the stake
+pub const MICRO_BNPL_ESCROW_TIMEOUT_DAYS: i64 = 14; // Escrowed payments are released to the merchant after this
+pub const MAX_SPLIT_PARTICIPANTS: usize = 8;
+
+// Regional rollout
+pub const MAX_ENABLED_REGIONS: usize = 16;
+
+// Merchant onboarding
+pub const MERCHANT_BOND_LAMPORTS: u64 = 1_000_000_000; // 1 SOL, refunded on deregistration
+pub const MERCHANT_CHALLENGE_PERIOD_DAYS: i64 = 7;
+pub const MAX_MERCHANT_FEE_BPS: u16 = 1000; // 10%
+pub const MIN_MERCHANT_FEE_OVERRIDE_BPS: u16 = 50; // 0.5%, the lowest rate a merchant can negotiate
+pub const MERCHANT_FEE_OVERRIDE_NONE: u16 = u16::MAX; // The merchant pays its standard fee
+pub const MERCHANT_SETTLEMENT_EPOCHS: usize = 8; // Epochs of settlement history kept per merchant
 
 // Staking-related constants
 pub const MIN_STAKING_AMOUNT: u64 = 10_000_000; // 10 USDC (with 6 decimals)
 pub const MIN_STAKING_LOCK_DAYS: u16 = 7;
 pub const MAX_STAKING_LOCK_DAYS: u16 = 365;
+pub const MAX_ACCEPTED_MINTS: usize = 8;
+pub const MAX_LOCK_TRANCHES: usize = 8;
+pub const STAKING_REWARD_APY_BPS: u16 = 500; // 5.00% APY on staked amount, default until governance changes it
+pub const MAX_STAKING_REWARD_APY_BPS: u16 = 5_000; // 50.00%
+pub const DEFAULT_EMERGENCY_WITHDRAW_PENALTY_BPS: u16 = 750; // 7.50% penalty on early withdrawal
+pub const MAX_EMERGENCY_WITHDRAW_PENALTY_BPS: u16 = 1_000; // 10%
+
+// Collateral valuation
+pub const USD_DECIMALS: u8 = 6; // Values are expressed in USDC units
+pub const MAX_ORACLE_PRICE_AGE_SECS: u64 = 60;
+pub const MAX_COLLATERAL_HAIRCUT_BPS: u16 = 1000; // 10%
+
+// Staking tiers: (minimum staked, fee discount bps, max BNPL multiplier bps)
+pub const MAX_STAKING_TIERS: usize = 8;
+pub const MAX_STAKING_TIER_DISCOUNT_BPS: u16 = 500; // 5%
+pub const DEFAULT_STAKING_TIERS: [(u64, u16, u16); 4] = [
+    (0, 0, 10_000),                    // Base
+    (1_000_000_000, 25, 11_000),       // 1,000 USDC
+    (10_000_000_000, 50, 12_500),      // 10,000 USDC
+    (50_000_000_000, 100, 15_000),     // 50,000 USDC
+];
+
+// Get the BNPL fee discount granted for the remaining lock duration
+pub fn get_lock_fee_discount(remaining_lock_days: i64) -> u16 {
+    match remaining_lock_days {
+        d if d >= 365 => 150, // -1.5%
+        d if d >= 180 => 100, // -1%
+        d if d >= 90 => 50,   // -0.5%
+        d if d >= 30 => 25,   // -0.25%
+        _ => 0,               // No discount
+    }
+}
 
 // Scoring-related constants
 pub const INITIAL_SCORE: u16 = 50;
@@ -136,23 +262,104 @@ pub const SCORE_DECREASE_LATE_PAYMENT: i16 = -10;
 pub const SCORE_DECREASE_DEFAULT: i16 = -50;
 pub const SCORE_INCREASE_COMPLETE_CONTRACT: i16 = 20;
 
+// Governance
+pub const GOVERNANCE_VOTING_PERIOD_DAYS: i64 = 5;
+pub const GOVERNANCE_EXECUTION_DELAY_DAYS: i64 = 2;
+pub const MAX_PROPOSAL_DATA_LEN: usize = 64;
+pub const GOVERNANCE_MIN_PROPOSAL_STAKE: u64 = 1_000_000_000; // 1,000 USDC (6 decimals) locked through the vote
+
+// Data access grant scopes (bitmask)
+pub const DATA_SCOPE_SCORE: u8 = 1;
+pub const DATA_SCOPE_HISTORY: u8 = 2;
+pub const DATA_SCOPE_ALL: u8 = DATA_SCOPE_SCORE | DATA_SCOPE_HISTORY;
+
+// Collateral ratio (LTV) defaults, in basis points of the staked amount
+pub const SCORE_TIER_COUNT: usize = 3;
+pub const DEFAULT_SCORE_TIER_THRESHOLDS: [u16; SCORE_TIER_COUNT - 1] = [300, 700];
+pub const DEFAULT_MIN_BNPL_SCORE: [u16; CARD_TYPE_COUNT] = [0; CARD_TYPE_COUNT]; // No minimum until set
+pub const MAX_LTV_BPS: u16 = 20_000; // 200%
+pub const DEFAULT_LTV_BPS: [[u16; SCORE_TIER_COUNT]; CARD_TYPE_COUNT] = [
+    [10_000, 10_000, 10_000], // Standard
+    [10_000, 10_000, 11_000], // Silver
+    [10_000, 11_000, 12_500], // Gold
+    [10_000, 12_500, 15_000], // Platinum
+];
+
+// Treasury yield deployment
+pub const DEFAULT_TREASURY_DEPLOY_CAP_BPS: u16 = 2000; // 20% of the treasury
+pub const MAX_TREASURY_DEPLOY_CAP_BPS: u16 = 5000; // Governance can't raise the cap above 50%
+pub const STRATEGY_RECONCILE_TOLERANCE_BPS: u16 = 100; // Reported values further than 1% from the books pause the strategy
+
+// Performance fee withheld from harvested yield, by card type
+pub const DEFAULT_YIELD_PERFORMANCE_FEE_BPS: [u16; CARD_TYPE_COUNT] = [
+    1000, // Standard: 10%
+    1000, // Silver: 10%
+    750,  // Gold: 7.5%
+    500,  // Platinum: 5%
+];
+pub const MAX_YIELD_PERFORMANCE_FEE_BPS: u16 = 3000; // 30%
+
+// Treasury withdrawals
+pub const MAX_INSTANT_TREASURY_WITHDRAWAL: u64 = 10_000_000_000; // 10,000 USDC (6 decimals); larger withdrawals must stream
+pub const MAX_DAILY_INSTANT_TREASURY_WITHDRAWAL: u64 = 25_000_000_000; // 25,000 USDC of instant withdrawals per day
+pub const TREASURY_WITHDRAWAL_WINDOW_SECS: i64 = 86400;
+pub const MIN_TREASURY_STREAM_DAYS: u16 = 7;
+pub const MAX_TREASURY_STREAM_DAYS: u16 = 365;
+
+// Dust sweeping
+pub const DUST_UNIT_DIVISOR: u64 = 100; // Balances under 0.01 token are dust
+pub const DUST_VAULT_PROMO_CODE: u8 = 0;
+pub const DUST_VAULT_TREASURY_YIELD: u8 = 1;
+
 // PDA Seeds
 pub const WALLET_SEED: &[u8] = b"wallet";
+pub const WALLET_RECOVERY_SEED: &[u8] = b"wallet_recovery";
 pub const BACKEND_ID_SEED: &[u8] = b"backend_id";
 pub const STAKING_SEED: &[u8] = b"staking";
+pub const STAKING_SNAPSHOT_SEED: &[u8] = b"staking_snapshot";
+pub const SAVINGS_GOAL_SEED: &[u8] = b"savings_goal";
 pub const USDC_VAULT_SEED: &[u8] = b"usdc_vault";
+pub const REWARDS_VAULT_SEED: &[u8] = b"rewards_vault";
+pub const EMISSIONS_SCHEDULE_SEED: &[u8] = b"emissions_schedule";
+pub const EPOCH_ACTIVITY_SEED: &[u8] = b"epoch_activity";
+pub const USER_ACTIVITY_SEED: &[u8] = b"user_activity";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const TREASURY_YIELD_SEED: &[u8] = b"treasury_yield";
+pub const TREASURY_STREAM_SEED: &[u8] = b"treasury_stream";
+pub const TREASURY_STATE_SEED: &[u8] = b"treasury_state";
+pub const INSURANCE_RESERVE_SEED: &[u8] = b"insurance_reserve";
 pub const BNPL_CONTRACT_SEED: &[u8] = b"bnpl_contract";
+pub const MICRO_BNPL_SEED: &[u8] = b"micro_bnpl";
+pub const BORROWER_REGISTRY_SEED: &[u8] = b"borrower_registry";
+pub const FORGIVENESS_BUDGET_SEED: &[u8] = b"forgiveness_budget";
+pub const KEEPER_POOL_SEED: &[u8] = b"keeper_pool";
+pub const PURCHASE_RECEIPT_SEED: &[u8] = b"purchase_receipt";
+pub const INSTALLMENT_CALENDAR_SEED: &[u8] = b"installment_calendar";
 pub const SCORE_SEED: &[u8] = b"score";
+pub const DATA_ACCESS_SEED: &[u8] = b"data_access";
 pub const YIELD_CONFIG_SEED: &[u8] = b"yield_config";
 pub const YIELD_VAULT_SEED: &[u8] = b"yield_vault";
 pub const YIELD_TRACKER_SEED: &[u8] = b"yield_tracker";
 pub const NFT_METADATA_SEED: &[u8] = b"nft_metadata";
 pub const NFT_ATTACHMENT_SEED: &[u8] = b"nft_attachment";
 pub const CARD_SEED: &[u8] = b"card";
+pub const MERCHANT_SEED: &[u8] = b"merchant";
+pub const MERCHANT_SETTLEMENT_SEED: &[u8] = b"merchant_settlement";
+pub const CHARITY_SEED: &[u8] = b"charity";
+pub const DONATION_SEED: &[u8] = b"donation";
 
 pub const WHITELIST_SEED: &[u8] = b"whitelist";
+pub const BLOCKLIST_SEED: &[u8] = b"blocklist";
 pub const ADMIN_LIST_SEED: &[u8] = b"admin_list";
+pub const ACCEPTED_MINTS_SEED: &[u8] = b"accepted_mints";
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const STAKING_TIER_CONFIG_SEED: &[u8] = b"staking_tier_config";
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const VOTE_RECORD_SEED: &[u8] = b"vote_record";
 
 pub const AUTHORIZATION_SEED: &[u8] = b"authorization";
+pub const DEPOSIT_ESCROW_SEED: &[u8] = b"deposit_escrow";
+pub const PROMO_CODE_SEED: &[u8] = b"promo_code";
 
 pub const FLEXFI_AUTHORITY_SEED: &[u8] = b"flexfi_authority";
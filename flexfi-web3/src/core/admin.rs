@@ -0,0 +1,168 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::admin::{AdminListAccount, AdminRole};
+use crate::constants::{ADMIN_LIST_SEED, MAX_ADMINS};
+use crate::core::whitelist::require_whitelist_authority;
+use crate::error_context::{account_error, key_mismatch};
+
+// Helper function that generates an error if the signer doesn't hold the role on the admin list
+pub fn require_admin_role(
+    program_id: &Pubkey,
+    admin_list_account: &AccountInfo,
+    authority: &AccountInfo,
+    role: AdminRole,
+) -> ProgramResult {
+    if !authority.is_signer {
+        return Err(account_error(authority, FlexfiError::Unauthorized));
+    }
+
+    let (admin_list_pda, _) = Pubkey::find_program_address(&[ADMIN_LIST_SEED], program_id);
+
+    if admin_list_account.key != &admin_list_pda {
+        return Err(key_mismatch(admin_list_account, &admin_list_pda, ProgramError::InvalidAccountData));
+    }
+
+    let admin_list = AdminListAccount::try_from_slice(&admin_list_account.data.borrow())?;
+
+    if !admin_list.has_role(authority.key, role) {
+        msg!("{} does not hold the {:?} role", authority.key, role);
+        return Err(account_error(authority, FlexfiError::Unauthorized));
+    }
+
+    Ok(())
+}
+
+// Initialize the admin list (called once by the whitelist authority, who starts with every role)
+pub fn process_initialize_admin_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    // Create the PDA for the admin list
+    let (admin_list_pda, bump) = Pubkey::find_program_address(
+        &[ADMIN_LIST_SEED],
+        program_id
+    );
+
+    if admin_list_account.key != &admin_list_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the account
+    let rent = Rent::get()?;
+    let space = AdminListAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &admin_list_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[authority.clone(), admin_list_account.clone(), system_program.clone()],
+        &[&[ADMIN_LIST_SEED, &[bump]]],
+    )?;
+
+    // Initialize the data
+    let mut admin_list = AdminListAccount {
+        authority: *authority.key,
+        admin_count: 0,
+        admins: [Pubkey::default(); MAX_ADMINS],
+        roles: [0; MAX_ADMINS],
+        bump,
+    };
+    admin_list.set_roles(*authority.key, AdminRole::ALL);
+
+    admin_list.serialize(&mut *admin_list_account.data.borrow_mut())?;
+
+    msg!("Admin list initialized with authority: {}", authority.key);
+    Ok(())
+}
+
+// Grant or replace an admin's roles; an empty mask removes the admin
+pub fn process_set_admin_roles(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: Pubkey,
+    roles: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if roles & !AdminRole::ALL != 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (admin_list_pda, _) = Pubkey::find_program_address(&[ADMIN_LIST_SEED], program_id);
+
+    if admin_list_account.key != &admin_list_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Load the admin list
+    let mut admin_list = AdminListAccount::try_from_slice(&admin_list_account.data.borrow())?;
+
+    // Verify that the authority is correct
+    if admin_list.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if !admin_list.set_roles(admin, roles) {
+        msg!("Admin {} is not on the list or the list is full", admin);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    admin_list.serialize(&mut *admin_list_account.data.borrow_mut())?;
+
+    msg!("Admin {} roles set to {:#06b}", admin, roles);
+    Ok(())
+}
+
+pub struct AdminManager;
+
+impl AdminManager {
+    pub fn initialize(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_initialize_admin_list(program_id, accounts)
+    }
+
+    pub fn set_roles(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        admin: Pubkey,
+        roles: u8,
+    ) -> ProgramResult {
+        process_set_admin_roles(program_id, accounts, admin, roles)
+    }
+}
@@ -0,0 +1,184 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::whitelist::{BlocklistStatus, BlocklistReason, WhitelistAccount};
+use crate::state::admin::AdminRole;
+use crate::constants::{BLOCKLIST_SEED, WHITELIST_SEED};
+use crate::core::admin::require_admin_role;
+use crate::core::whitelist::{emit_whitelist_change, WhitelistChange};
+use crate::error_context::{account_error, log_error_context, ContextValue};
+
+// Helper function that generates an error if the user is blocklisted; the user's
+// blocklist PDA must be among the accounts, even when it was never created
+pub fn require_not_blocklisted(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let (blocklist_pda, _) = Pubkey::find_program_address(
+        &[BLOCKLIST_SEED, user_pubkey.as_ref()],
+        program_id
+    );
+
+    let blocklist_account = accounts.iter()
+        .find(|account| account.key == &blocklist_pda)
+        .ok_or_else(|| {
+            let error = ProgramError::NotEnoughAccountKeys;
+            log_error_context(&error, None, ContextValue::Key(blocklist_pda), ContextValue::None);
+            error
+        })?;
+
+    // No entry means the user was never blocklisted
+    if blocklist_account.owner != program_id || blocklist_account.data_is_empty() {
+        return Ok(());
+    }
+
+    let blocklist_status = BlocklistStatus::try_from_slice(&blocklist_account.data.borrow())?;
+
+    if blocklist_status.is_blocked {
+        msg!("User {} is blocklisted ({:?})", user_pubkey, blocklist_status.get_reason()?);
+        return Err(account_error(blocklist_account, FlexfiError::UserBlocklisted));
+    }
+
+    Ok(())
+}
+
+// Blocklist a user with a reason; overrides whitelist membership
+pub fn process_add_to_blocklist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    user_pubkey: Pubkey,
+    reason: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let blocklist_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the whitelist manager role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::WhitelistManager)?;
+
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if whitelist_account.key != &whitelist_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let reason = BlocklistReason::from_u8(reason)?;
+
+    // Verify the blocklist PDA
+    let (blocklist_pda, blocklist_bump) = Pubkey::find_program_address(
+        &[BLOCKLIST_SEED, user_pubkey.as_ref()],
+        program_id
+    );
+
+    if blocklist_account.key != &blocklist_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    // Create the entry on the first block; a previously removed entry is reused
+    if blocklist_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let space = BlocklistStatus::SIZE;
+        let rent_lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                &blocklist_pda,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[authority.clone(), blocklist_account.clone(), system_program.clone()],
+            &[&[BLOCKLIST_SEED, user_pubkey.as_ref(), &[blocklist_bump]]],
+        )?;
+    } else if BlocklistStatus::try_from_slice(&blocklist_account.data.borrow())?.is_blocked {
+        msg!("User {} is already blocklisted", user_pubkey);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let blocklist_status = BlocklistStatus {
+        user_pubkey,
+        is_blocked: true,
+        reason: reason.to_u8(),
+        blocked_at: clock.unix_timestamp,
+        blocked_by: *authority.key,
+        unblocked_at: 0,
+        unblocked_by: Pubkey::default(),
+        bump: blocklist_bump,
+    };
+
+    blocklist_status.serialize(&mut *blocklist_account.data.borrow_mut())?;
+
+    // Wallet caches don't see blocklist entries: invalidate them
+    let mut whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
+    whitelist_data.version = whitelist_data.version.wrapping_add(1);
+    whitelist_data.serialize(&mut *whitelist_account.data.borrow_mut())?;
+
+    emit_whitelist_change(WhitelistChange::Blocklisted, user_pubkey, *authority.key, 0)?;
+
+    msg!("User {} blocklisted by {}: {:?}", user_pubkey, authority.key, reason);
+    Ok(())
+}
+
+// Lift a block; the entry and its reason are kept for the audit trail
+pub fn process_remove_from_blocklist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    user_pubkey: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let blocklist_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the whitelist manager role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::WhitelistManager)?;
+
+    // Verify the blocklist PDA
+    let (blocklist_pda, _) = Pubkey::find_program_address(
+        &[BLOCKLIST_SEED, user_pubkey.as_ref()],
+        program_id
+    );
+
+    if blocklist_account.key != &blocklist_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut blocklist_status = BlocklistStatus::try_from_slice(&blocklist_account.data.borrow())?;
+
+    if !blocklist_status.is_blocked {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    blocklist_status.is_blocked = false;
+    blocklist_status.unblocked_at = clock.unix_timestamp;
+    blocklist_status.unblocked_by = *authority.key;
+    blocklist_status.serialize(&mut *blocklist_account.data.borrow_mut())?;
+
+    emit_whitelist_change(WhitelistChange::Unblocklisted, user_pubkey, *authority.key, 0)?;
+
+    msg!("User {} removed from the blocklist by {}", user_pubkey, authority.key);
+    Ok(())
+}
@@ -0,0 +1,483 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::config::ConfigAccount;
+use crate::constants::{
+    CONFIG_SEED, CARD_TYPE_COUNT, SCORE_TIER_COUNT, DEFAULT_SCORE_TIER_THRESHOLDS,
+    DEFAULT_LTV_BPS, MAX_LTV_BPS, DEFAULT_TREASURY_DEPLOY_CAP_BPS, MAX_TREASURY_DEPLOY_CAP_BPS,
+    DEFAULT_MICRO_BNPL_THRESHOLD, DEFAULT_MICRO_BNPL_LTV_BPS, MAX_ENABLED_REGIONS,
+    DEFAULT_MIN_BNPL_SCORE, MAX_SCORE, STAKING_REWARD_APY_BPS, MAX_STAKING_REWARD_APY_BPS,
+    DEFAULT_YIELD_PERFORMANCE_FEE_BPS, MAX_YIELD_PERFORMANCE_FEE_BPS,
+    DEFAULT_EMERGENCY_WITHDRAW_PENALTY_BPS, MAX_EMERGENCY_WITHDRAW_PENALTY_BPS,
+};
+use crate::error_context::{account_error, key_mismatch};
+use crate::core::whitelist::{require_whitelist_authority, load_user_limits};
+
+// Load the protocol config after checking its PDA
+pub fn load_config(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+) -> Result<ConfigAccount, ProgramError> {
+    let (config_pda, _) = Pubkey::find_program_address(&[CONFIG_SEED], program_id);
+
+    if *config_account.key != config_pda {
+        return Err(key_mismatch(config_account, &config_pda, ProgramError::InvalidAccountData));
+    }
+
+    ConfigAccount::try_from_slice(&config_account.data.borrow())
+        .map_err(|_| ProgramError::UninitializedAccount)
+}
+
+// Helper function that generates an error if the user's region is not enabled yet.
+// The cached mode reads the region copied onto the wallet. Users whitelisted by attestation
+// have no status PDA, hence no region, and are held back while gating is on.
+pub fn require_region_enabled(
+    program_id: &Pubkey,
+    config_data: &ConfigAccount,
+    user_status_account: &AccountInfo,
+) -> ProgramResult {
+    if !config_data.region_gating {
+        return Ok(());
+    }
+
+    let region = load_user_limits(program_id, user_status_account)?
+        .map_or(0, |limits| limits.region);
+
+    if !config_data.is_region_enabled(region) {
+        msg!("Region {} is not enabled", region);
+        return Err(account_error(user_status_account, FlexfiError::RegionNotEnabled));
+    }
+
+    Ok(())
+}
+
+// Initialize the protocol config (called once by the whitelist authority)
+pub fn process_initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Only the protocol admin can create the config, or anyone could take its authority first
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    // Create the PDA for the config
+    let (config_pda, bump) = Pubkey::find_program_address(&[CONFIG_SEED], program_id);
+
+    if config_account.key != &config_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the account
+    let rent = Rent::get()?;
+    let space = ConfigAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &config_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[authority.clone(), config_account.clone(), system_program.clone()],
+        &[&[CONFIG_SEED, &[bump]]],
+    )?;
+
+    // Initialize with the default values
+    let config_data = ConfigAccount {
+        authority: *authority.key,
+        score_tier_thresholds: DEFAULT_SCORE_TIER_THRESHOLDS,
+        ltv_bps: DEFAULT_LTV_BPS,
+        treasury_deploy_cap_bps: DEFAULT_TREASURY_DEPLOY_CAP_BPS,
+        micro_bnpl_threshold: DEFAULT_MICRO_BNPL_THRESHOLD,
+        micro_bnpl_ltv_bps: DEFAULT_MICRO_BNPL_LTV_BPS,
+        min_bnpl_score: DEFAULT_MIN_BNPL_SCORE,
+        region_gating: false,
+        enabled_regions: [0; MAX_ENABLED_REGIONS],
+        staking_reward_apy_bps: STAKING_REWARD_APY_BPS,
+        reward_index: 0,
+        reward_index_updated_at: Clock::get()?.unix_timestamp,
+        yield_performance_fee_bps: DEFAULT_YIELD_PERFORMANCE_FEE_BPS,
+        emergency_withdraw_penalty_bps: DEFAULT_EMERGENCY_WITHDRAW_PENALTY_BPS,
+        bump,
+    };
+
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Config initialized with authority: {}", authority.key);
+    Ok(())
+}
+
+// Update the collateral ratio for a card type and score tier
+pub fn process_set_collateral_ratio(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    card_type: u8,
+    score_tier: u8,
+    ltv_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check the arguments
+    if card_type as usize >= CARD_TYPE_COUNT {
+        return Err(FlexfiError::InvalidCardType.into());
+    }
+
+    if score_tier as usize >= SCORE_TIER_COUNT || ltv_bps == 0 || ltv_bps > MAX_LTV_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.ltv_bps[card_type as usize][score_tier as usize] = ltv_bps;
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Collateral ratio for card type {} tier {} set to {} bps", card_type, score_tier, ltv_bps);
+    Ok(())
+}
+
+// Update the share of the treasury that may be deployed into yield strategies
+pub fn process_set_treasury_deploy_cap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cap_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if cap_bps > MAX_TREASURY_DEPLOY_CAP_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.treasury_deploy_cap_bps = cap_bps;
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Treasury deploy cap set to {} bps", cap_bps);
+    Ok(())
+}
+
+// Update the micro-BNPL threshold and its collateral ratio
+pub fn process_set_micro_bnpl_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    threshold: u64,
+    ltv_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if ltv_bps == 0 || ltv_bps > MAX_LTV_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.micro_bnpl_threshold = threshold;
+    config_data.micro_bnpl_ltv_bps = ltv_bps;
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Micro-BNPL config set: threshold {}, {} bps", threshold, ltv_bps);
+    Ok(())
+}
+
+// Update the minimum score a card type needs to take a BNPL loan
+pub fn process_set_min_bnpl_score(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    card_type: u8,
+    min_score: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check the arguments
+    if card_type as usize >= CARD_TYPE_COUNT {
+        return Err(FlexfiError::InvalidCardType.into());
+    }
+
+    if min_score > MAX_SCORE {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.min_bnpl_score[card_type as usize] = min_score;
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Minimum BNPL score for card type {} set to {}", card_type, min_score);
+    Ok(())
+}
+
+// Update the performance fee a card type pays on harvested yield
+pub fn process_set_yield_performance_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    card_type: u8,
+    fee_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check the arguments
+    if card_type as usize >= CARD_TYPE_COUNT {
+        return Err(FlexfiError::InvalidCardType.into());
+    }
+
+    if fee_bps > MAX_YIELD_PERFORMANCE_FEE_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.yield_performance_fee_bps[card_type as usize] = fee_bps;
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Yield performance fee for card type {} set to {} bps", card_type, fee_bps);
+    Ok(())
+}
+
+// Update the staking reward APY; time before the change keeps accruing at the old rate
+pub fn process_set_staking_reward_apy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    apy_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if apy_bps > MAX_STAKING_REWARD_APY_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let current_time = Clock::get()?.unix_timestamp;
+    config_data.set_staking_reward_apy(apy_bps, current_time);
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Staking reward APY set to {} bps (index {})", apy_bps, config_data.reward_index);
+    Ok(())
+}
+
+// Update the penalty charged on the locked part of an emergency staking withdrawal
+pub fn process_set_emergency_withdraw_penalty(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    penalty_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if penalty_bps > MAX_EMERGENCY_WITHDRAW_PENALTY_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.emergency_withdraw_penalty_bps = penalty_bps;
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Emergency withdrawal penalty set to {} bps", penalty_bps);
+    Ok(())
+}
+
+// Turn region gating on or off; while off, every region is served
+pub fn process_set_region_gating(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    config_data.region_gating = enabled;
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Region gating {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+// Enable or disable a region in the rollout table
+pub fn process_set_region_enabled(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    region: u16,
+    enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if region == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let slot = config_data.enabled_regions.iter().position(|code| *code == region);
+
+    match (slot, enabled) {
+        (Some(_), true) | (None, false) => {},
+        (Some(index), false) => config_data.enabled_regions[index] = 0,
+        (None, true) => {
+            let index = config_data.enabled_regions.iter()
+                .position(|code| *code == 0)
+                .ok_or(FlexfiError::RegionLimitReached)?;
+            config_data.enabled_regions[index] = region;
+        },
+    }
+
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Region {} {}", region, if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+// Hand the config over to a new authority, e.g. the governance PDA
+pub fn process_set_config_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_authority: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut config_data = load_config(program_id, config_account)?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    config_data.authority = new_authority;
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Config authority set to {}", new_authority);
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    log::sol_log_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::state::admin::AdminRole;
+use crate::state::promo::PromoCodeAccount;
+use crate::state::yield_::TreasuryYieldAccount;
+use crate::constants::{
+    TREASURY_SEED, PROMO_CODE_SEED, TREASURY_YIELD_SEED,
+    DUST_VAULT_PROMO_CODE, DUST_VAULT_TREASURY_YIELD, DUST_UNIT_DIVISOR,
+};
+use crate::core::admin::require_admin_role;
+use crate::error_context::amount_mismatch;
+use crate::token::{transfer_checked, get_mint_decimals, get_token_account_amount};
+
+// Audit record of a sweep, logged as event data
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct DustSwept {
+    pub vault: Pubkey,
+    pub vault_kind: u8,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub swept_by: Pubkey,
+    pub swept_at: i64,
+}
+
+// Move a residual balance below the dust threshold from a program vault to the treasury (treasurer only)
+pub fn process_sweep_dust(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vault_kind: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let vault_owner_account = next_account_info(account_info_iter)?;
+    let vault_token_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Verify the authority holds the treasurer role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Treasurer)?;
+
+    if vault_owner_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    // Only vaults whose owner has nothing left to pay out can be swept. The emissions vault is never
+    // swept: epochs still owe their emissions to users until distributed, which can't be checked here.
+    let (seed, key_seed, bump): (&[u8], Vec<u8>, u8) = match vault_kind {
+        DUST_VAULT_PROMO_CODE => {
+            let promo_data = PromoCodeAccount::try_from_slice(&vault_owner_account.data.borrow())?;
+
+            if promo_data.is_redeemable(clock.unix_timestamp) {
+                msg!("Promo code is still redeemable");
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            (PROMO_CODE_SEED, promo_data.code_hash.to_vec(), promo_data.bump)
+        },
+        DUST_VAULT_TREASURY_YIELD => {
+            let treasury_yield_data = TreasuryYieldAccount::try_from_slice(&vault_owner_account.data.borrow())?;
+
+            if treasury_yield_data.principal_deployed > 0 {
+                msg!("Treasury principal is still deployed");
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            (TREASURY_YIELD_SEED, treasury_yield_data.mint.to_bytes().to_vec(), treasury_yield_data.bump)
+        },
+        _ => return Err(ProgramError::InvalidArgument),
+    };
+
+    // Verify the vault owner PDA and its token account
+    let vault_owner_pda = Pubkey::create_program_address(&[seed, &key_seed, &[bump]], program_id)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if *vault_owner_account.key != vault_owner_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        vault_owner_account.key,
+        mint.key,
+        token_program.key,
+    );
+
+    if *vault_token_account.key != vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (treasury_pda, _) = Pubkey::find_program_address(
+        &[TREASURY_SEED, mint.key.as_ref()],
+        program_id
+    );
+    let treasury_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &treasury_pda,
+        mint.key,
+        token_program.key,
+    );
+
+    if *treasury_token_account.key != treasury_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Dust is anything below a hundredth of a whole token
+    let dust_threshold = 10u64.pow(get_mint_decimals(mint)? as u32) / DUST_UNIT_DIVISOR;
+    let amount = get_token_account_amount(vault_token_account)?;
+
+    if amount == 0 || amount >= dust_threshold {
+        msg!("Vault balance {} is not dust (threshold {})", amount, dust_threshold);
+        return Err(amount_mismatch(Some(vault_token_account), dust_threshold, amount, ProgramError::InvalidArgument));
+    }
+
+    transfer_checked(
+        token_program,
+        vault_token_account,
+        mint,
+        treasury_token_account,
+        vault_owner_account,
+        hook_accounts,
+        amount,
+        &[&[seed, &key_seed, &[bump]]],
+    )?;
+
+    // Residual strategy balance is earned yield
+    if vault_kind == DUST_VAULT_TREASURY_YIELD {
+        let mut treasury_yield_data = TreasuryYieldAccount::try_from_slice(&vault_owner_account.data.borrow())?;
+        treasury_yield_data.yield_realized = treasury_yield_data.yield_realized.saturating_add(amount);
+        treasury_yield_data.serialize(&mut *vault_owner_account.data.borrow_mut())?;
+    }
+
+    let event = DustSwept {
+        vault: *vault_token_account.key,
+        vault_kind,
+        mint: *mint.key,
+        amount,
+        swept_by: *authority.key,
+        swept_at: clock.unix_timestamp,
+    };
+
+    sol_log_data(&[b"DustSwept", &event.try_to_vec()?]);
+
+    msg!("Swept {} dust from vault {} to the treasury", amount, vault_token_account.key);
+    Ok(())
+}
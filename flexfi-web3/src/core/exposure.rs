@@ -0,0 +1,108 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::set_return_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::authorization::AuthorizationAccount;
+use crate::state::bnpl::{BNPLContractAccount, MicroBNPLAccount};
+use crate::constants::{AUTHORIZATION_SEED, DATA_SCOPE_HISTORY};
+use crate::score::access::require_data_access;
+
+// What a user owes across products, returned to the risk engine and the app via return data
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct UserExposure {
+    pub bnpl_outstanding: u64,
+    pub authorization_used: u64,
+    pub subscription_commitments: u64,
+    pub total_owed: u64,
+}
+
+pub fn process_get_user_exposure(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let user_account = next_account_info(account_info_iter)?;
+    let viewer_account = next_account_info(account_info_iter)?;
+
+    // The owner or a viewer holding a grant may read the data
+    require_data_access(program_id, user_account.key, viewer_account, account_info_iter, DATA_SCOPE_HISTORY)?;
+
+    let authorization_account = next_account_info(account_info_iter)?;
+    let contract_accounts = account_info_iter.as_slice(); // The user's BNPL contracts and micro loans
+
+    let mut exposure = UserExposure::default();
+
+    // Credit drawn through the FlexFi authorization, if the user has one
+    let (authorization_pda, _) = Pubkey::find_program_address(
+        &[AUTHORIZATION_SEED, user_account.key.as_ref()],
+        program_id
+    );
+
+    if *authorization_account.key != authorization_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if authorization_account.owner == program_id && !authorization_account.data_is_empty() {
+        let authorization = AuthorizationAccount::try_from_slice(&authorization_account.data.borrow())?;
+        exposure.authorization_used = authorization.used_amount;
+    }
+
+    // Remaining installments of every active contract and open micro loan
+    for (index, contract_account) in contract_accounts.iter().enumerate() {
+        if contract_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        if contract_accounts[..index].iter().any(|other| other.key == contract_account.key) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (borrower, outstanding) = match contract_account.data_len() {
+            MicroBNPLAccount::SIZE => {
+                let micro_bnpl_data = MicroBNPLAccount::try_from_slice(&contract_account.data.borrow())?;
+                (micro_bnpl_data.borrower, micro_bnpl_data.remaining_amount())
+            },
+            _ => {
+                let contract_data = BNPLContractAccount::try_from_slice(&contract_account.data.borrow())?;
+                let outstanding = if contract_data.is_open()? {
+                    contract_data.remaining_amount()
+                } else {
+                    0
+                };
+                (contract_data.borrower, outstanding)
+            },
+        };
+
+        if borrower != *user_account.key {
+            return Err(FlexfiError::Unauthorized.into());
+        }
+
+        exposure.bnpl_outstanding = exposure.bnpl_outstanding
+            .checked_add(outstanding)
+            .ok_or(FlexfiError::MathOverflow)?;
+    }
+
+    exposure.total_owed = exposure.bnpl_outstanding
+        .checked_add(exposure.authorization_used)
+        .and_then(|total| total.checked_add(exposure.subscription_commitments))
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    set_return_data(&exposure.try_to_vec()?);
+
+    msg!(
+        "User exposure: BNPL {}, authorization {}, subscriptions {}, total {}",
+        exposure.bnpl_outstanding,
+        exposure.authorization_used,
+        exposure.subscription_commitments,
+        exposure.total_owed
+    );
+    Ok(())
+}
@@ -0,0 +1,289 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::mint_registry::AcceptedMintsAccount;
+use crate::constants::{ACCEPTED_MINTS_SEED, MAX_ACCEPTED_MINTS};
+use crate::core::whitelist::require_whitelist_authority;
+
+// Helper function that generates an error if the mint can't be used as collateral
+pub fn require_accepted_mint(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    accepted_mints_account: &AccountInfo,
+) -> ProgramResult {
+    let (accepted_mints_pda, _) = Pubkey::find_program_address(
+        &[ACCEPTED_MINTS_SEED],
+        program_id
+    );
+
+    if *accepted_mints_account.key != accepted_mints_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if accepted_mints_account.data_is_empty() {
+        msg!("Accepted mints registry is not initialized");
+        return Err(FlexfiError::MintNotAccepted.into());
+    }
+
+    let registry = AcceptedMintsAccount::try_from_slice(&accepted_mints_account.data.borrow())?;
+
+    if !registry.is_accepted(mint) {
+        msg!("Mint {} is not accepted as collateral", mint);
+        return Err(FlexfiError::MintNotAccepted.into());
+    }
+
+    Ok(())
+}
+
+// Helper function that generates an error if BNPL loans can't be denominated in the mint
+pub fn require_payment_mint(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    accepted_mints_account: &AccountInfo,
+) -> ProgramResult {
+    require_accepted_mint(program_id, mint, accepted_mints_account)?;
+
+    let registry = AcceptedMintsAccount::try_from_slice(&accepted_mints_account.data.borrow())?;
+
+    if !registry.is_payment_mint(mint) {
+        msg!("Mint {} is not enabled for BNPL payments", mint);
+        return Err(FlexfiError::MintNotAccepted.into());
+    }
+
+    Ok(())
+}
+
+// Price feed registered for an accepted mint (default for stablecoins)
+pub fn get_mint_price_feed(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    accepted_mints_account: &AccountInfo,
+) -> Result<Pubkey, ProgramError> {
+    require_accepted_mint(program_id, mint, accepted_mints_account)?;
+
+    let registry = AcceptedMintsAccount::try_from_slice(&accepted_mints_account.data.borrow())?;
+
+    registry.get_price_feed(mint).ok_or(FlexfiError::MintNotAccepted.into())
+}
+
+// Initialize the accepted mints registry (called once by the whitelist authority)
+pub fn process_initialize_accepted_mints(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Only the protocol admin can create the registry, or anyone could take its authority first
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    // Create the PDA for the registry
+    let (accepted_mints_pda, bump) = Pubkey::find_program_address(
+        &[ACCEPTED_MINTS_SEED],
+        program_id
+    );
+
+    if accepted_mints_account.key != &accepted_mints_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the account
+    let rent = Rent::get()?;
+    let space = AcceptedMintsAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &accepted_mints_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[authority.clone(), accepted_mints_account.clone(), system_program.clone()],
+        &[&[ACCEPTED_MINTS_SEED, &[bump]]],
+    )?;
+
+    // Initialize the data
+    let registry = AcceptedMintsAccount {
+        authority: *authority.key,
+        mint_count: 0,
+        mints: [Pubkey::default(); MAX_ACCEPTED_MINTS],
+        price_feeds: [Pubkey::default(); MAX_ACCEPTED_MINTS],
+        payment_mints: [false; MAX_ACCEPTED_MINTS],
+        bump,
+    };
+
+    registry.serialize(&mut *accepted_mints_account.data.borrow_mut())?;
+
+    msg!("Accepted mints registry initialized with authority: {}", authority.key);
+    Ok(())
+}
+
+pub fn process_add_accepted_mint(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Load the registry
+    let mut registry = AcceptedMintsAccount::try_from_slice(&accepted_mints_account.data.borrow())?;
+
+    // Verify that the authority is correct
+    if registry.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if !registry.add_mint(mint) {
+        msg!("Mint {} is already accepted or the registry is full", mint);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    registry.serialize(&mut *accepted_mints_account.data.borrow_mut())?;
+
+    msg!("Mint {} added to accepted mints", mint);
+    Ok(())
+}
+
+pub fn process_remove_accepted_mint(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Load the registry
+    let mut registry = AcceptedMintsAccount::try_from_slice(&accepted_mints_account.data.borrow())?;
+
+    // Verify that the authority is correct
+    if registry.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if !registry.remove_mint(&mint) {
+        return Err(FlexfiError::MintNotAccepted.into());
+    }
+
+    registry.serialize(&mut *accepted_mints_account.data.borrow_mut())?;
+
+    msg!("Mint {} removed from accepted mints", mint);
+    Ok(())
+}
+
+pub fn process_set_mint_price_feed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+    price_feed: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let (accepted_mints_pda, _) = Pubkey::find_program_address(
+        &[ACCEPTED_MINTS_SEED],
+        program_id
+    );
+
+    if accepted_mints_account.key != &accepted_mints_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Load the registry
+    let mut registry = AcceptedMintsAccount::try_from_slice(&accepted_mints_account.data.borrow())?;
+
+    // Verify that the authority is correct
+    if registry.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if !registry.set_price_feed(&mint, price_feed) {
+        return Err(FlexfiError::MintNotAccepted.into());
+    }
+
+    registry.serialize(&mut *accepted_mints_account.data.borrow_mut())?;
+
+    msg!("Price feed for mint {} set to {}", mint, price_feed);
+    Ok(())
+}
+
+// Enable or disable an accepted stablecoin (USDC, USDT, PYUSD, EURC...) as a BNPL payment mint
+pub fn process_set_payment_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+    enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let (accepted_mints_pda, _) = Pubkey::find_program_address(
+        &[ACCEPTED_MINTS_SEED],
+        program_id
+    );
+
+    if accepted_mints_account.key != &accepted_mints_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Load the registry
+    let mut registry = AcceptedMintsAccount::try_from_slice(&accepted_mints_account.data.borrow())?;
+
+    // Verify that the authority is correct
+    if registry.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if !registry.set_payment_mint(&mint, enabled) {
+        return Err(FlexfiError::MintNotAccepted.into());
+    }
+
+    registry.serialize(&mut *accepted_mints_account.data.borrow_mut())?;
+
+    msg!("Mint {} enabled for BNPL payments: {}", mint, enabled);
+    Ok(())
+}
@@ -1,11 +1,90 @@
 pub mod staking;
 pub mod whitelist;
+pub mod blocklist;
+pub mod status;
+pub mod mint_registry;
+pub mod config;
+pub mod snapshot;
+pub mod staking_tier;
+pub mod savings;
+pub mod wallet;
+pub mod admin;
+pub mod exposure;
+pub mod dust;
+pub mod treasury_stream;
 
-pub use staking::{process_deposit_staking, process_withdraw_staking};
+pub use staking::{
+    process_deposit_staking,
+    process_withdraw_staking,
+    process_emergency_withdraw_staking,
+    process_extend_staking_lock,
+    process_set_staking_delegate,
+    process_close_staking_account,
+    process_check_unlock_status,
+    process_freeze_staking,
+    process_unfreeze_staking,
+    process_claim_staking_rewards,
+    process_set_auto_compound,
+    process_compound_staking_rewards,
+    process_migrate_recovered_staking
+};
 pub use whitelist::{
     process_initialize_whitelist, 
     process_add_to_whitelist,
     process_remove_from_whitelist,
+    process_renew_whitelist,
+    process_set_whitelist_active,
+    process_add_authorized_key,
+    process_remove_authorized_key,
+    process_set_attestation_signer,
+    process_set_user_risk_limits,
+    WhitelistChange,
+    WhitelistChanged,
+    check_whitelist_attestation,
     check_user_whitelisted, 
-    require_whitelisted
-};
\ No newline at end of file
+    require_whitelisted,
+    require_whitelist_authority
+};
+pub use blocklist::{process_add_to_blocklist, process_remove_from_blocklist, require_not_blocklisted};
+pub use admin::{process_initialize_admin_list, process_set_admin_roles, require_admin_role};
+pub use status::{process_get_protocol_status, process_get_active_parameters, ProtocolStatus, ActiveParameters, CardParameters};
+pub use exposure::{process_get_user_exposure, UserExposure};
+pub use dust::{process_sweep_dust, DustSwept};
+pub use treasury_stream::{process_withdraw_treasury, process_claim_treasury_stream, process_cancel_treasury_stream};
+pub use mint_registry::{
+    process_initialize_accepted_mints,
+    process_add_accepted_mint,
+    process_remove_accepted_mint,
+    process_set_mint_price_feed,
+    process_set_payment_mint,
+    require_accepted_mint,
+    require_payment_mint,
+    get_mint_price_feed
+};
+pub use config::{
+    process_initialize_config,
+    process_set_collateral_ratio,
+    process_set_treasury_deploy_cap,
+    process_set_micro_bnpl_config,
+    process_set_yield_performance_fee,
+    process_set_emergency_withdraw_penalty,
+    process_set_config_authority,
+    load_config
+};
+pub use snapshot::process_take_staking_snapshot;
+pub use staking_tier::{process_initialize_staking_tiers, process_set_staking_tier, get_staking_tier};
+pub use savings::{process_create_savings_goal, process_deposit_savings_goal, process_withdraw_savings_goal};
+pub use wallet::{
+    process_create_wallet,
+    process_deactivate_wallet,
+    process_reactivate_wallet,
+    process_set_wallet_guardians,
+    process_approve_wallet_recovery,
+    process_cancel_wallet_recovery,
+    process_recover_wallet,
+    process_migrate_recovered_profile,
+    process_link_backend_id,
+    process_close_wallet,
+    process_request_data_erasure,
+    process_acknowledge_data_erasure
+};
@@ -0,0 +1,302 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::savings::SavingsGoalAccount;
+use crate::constants::SAVINGS_GOAL_SEED;
+use crate::core::mint_registry::require_accepted_mint;
+use crate::token::transfer_checked;
+
+// Load a savings goal after checking its PDA and owner
+fn load_savings_goal(
+    program_id: &Pubkey,
+    goal_account: &AccountInfo,
+    user_account: &AccountInfo,
+) -> Result<SavingsGoalAccount, ProgramError> {
+    let goal_data = SavingsGoalAccount::try_from_slice(&goal_account.data.borrow())?;
+
+    let (goal_pda, _) = Pubkey::find_program_address(
+        &[SAVINGS_GOAL_SEED, user_account.key.as_ref(), &goal_data.name_hash],
+        program_id,
+    );
+
+    if *goal_account.key != goal_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if goal_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    Ok(goal_data)
+}
+
+// Check the goal vault is the goal's own token account
+fn check_goal_vault(
+    goal_account: &AccountInfo,
+    goal_vault_token_account: &AccountInfo,
+    mint: &AccountInfo,
+    token_program: &AccountInfo,
+) -> ProgramResult {
+    let goal_vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        goal_account.key,
+        mint.key,
+        token_program.key,
+    );
+
+    if *goal_vault_token_account.key != goal_vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+pub fn process_create_savings_goal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name_hash: [u8; 32],
+    target_amount: u64,
+    target_date: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let goal_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+    let goal_vault_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let associated_token_program = next_account_info(account_info_iter)?;
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    require_accepted_mint(program_id, mint.key, accepted_mints_account)?;
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    if target_amount == 0 || target_date <= current_time {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Create the PDA for the goal
+    let (goal_pda, goal_bump) = Pubkey::find_program_address(
+        &[SAVINGS_GOAL_SEED, user_account.key.as_ref(), &name_hash],
+        program_id,
+    );
+
+    if *goal_account.key != goal_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent = Rent::get()?;
+    let space = SavingsGoalAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            user_account.key,
+            &goal_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[user_account.clone(), goal_account.clone(), system_program.clone()],
+        &[&[SAVINGS_GOAL_SEED, user_account.key.as_ref(), &name_hash, &[goal_bump]]],
+    )?;
+
+    // Create the goal vault, owned by the goal PDA only
+    check_goal_vault(goal_account, goal_vault_token_account, mint, token_program)?;
+
+    if goal_vault_token_account.data_is_empty() {
+        invoke_signed(
+            &spl_associated_token_account::instruction::create_associated_token_account(
+                user_account.key,
+                goal_account.key,
+                mint.key,
+                token_program.key,
+            ),
+            &[
+                user_account.clone(),
+                goal_vault_token_account.clone(),
+                goal_account.clone(),
+                mint.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+            &[],
+        )?;
+    }
+
+    let goal_data = SavingsGoalAccount {
+        owner: *user_account.key,
+        mint: *mint.key,
+        name_hash,
+        target_amount,
+        target_date,
+        amount_saved: 0,
+        created_at: current_time,
+        bump: goal_bump,
+    };
+
+    goal_data.serialize(&mut *goal_account.data.borrow_mut())?;
+
+    msg!("Savings goal created: target {} by {}", target_amount, target_date);
+    Ok(())
+}
+
+pub fn process_deposit_savings_goal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let goal_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let user_token_account = next_account_info(account_info_iter)?;
+    let goal_vault_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut goal_data = load_savings_goal(program_id, goal_account, user_account)?;
+
+    if *mint.key != goal_data.mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    check_goal_vault(goal_account, goal_vault_token_account, mint, token_program)?;
+
+    goal_data.amount_saved = goal_data.amount_saved.saturating_add(amount);
+    goal_data.serialize(&mut *goal_account.data.borrow_mut())?;
+
+    transfer_checked(
+        token_program,
+        user_token_account,
+        mint,
+        goal_vault_token_account,
+        user_account,
+        hook_accounts,
+        amount,
+        &[],
+    )?;
+
+    msg!("Savings goal deposit: {} ({} of {})", amount, goal_data.amount_saved, goal_data.target_amount);
+    Ok(())
+}
+
+pub fn process_withdraw_savings_goal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let goal_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let user_token_account = next_account_info(account_info_iter)?;
+    let goal_vault_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut goal_data = load_savings_goal(program_id, goal_account, user_account)?;
+
+    if *mint.key != goal_data.mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    check_goal_vault(goal_account, goal_vault_token_account, mint, token_program)?;
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if !goal_data.is_unlocked(clock.unix_timestamp) {
+        msg!("Savings goal locked until {} or {} saved", goal_data.target_date, goal_data.target_amount);
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    if amount == 0 || amount > goal_data.amount_saved {
+        return Err(FlexfiError::InsufficientStaking.into());
+    }
+
+    goal_data.amount_saved = goal_data.amount_saved.saturating_sub(amount);
+    goal_data.serialize(&mut *goal_account.data.borrow_mut())?;
+
+    transfer_checked(
+        token_program,
+        goal_vault_token_account,
+        mint,
+        user_token_account,
+        goal_account, // The goal PDA owns its vault
+        hook_accounts,
+        amount,
+        &[&[SAVINGS_GOAL_SEED, user_account.key.as_ref(), &goal_data.name_hash, &[goal_data.bump]]],
+    )?;
+
+    msg!("Savings goal withdrawal: {}", amount);
+    Ok(())
+}
+
+pub struct SavingsManager;
+
+impl SavingsManager {
+    pub fn create_goal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        name_hash: [u8; 32],
+        target_amount: u64,
+        target_date: i64,
+    ) -> ProgramResult {
+        process_create_savings_goal(program_id, accounts, name_hash, target_amount, target_date)
+    }
+
+    pub fn deposit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        process_deposit_savings_goal(program_id, accounts, amount)
+    }
+
+    pub fn withdraw(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        process_withdraw_savings_goal(program_id, accounts, amount)
+    }
+}
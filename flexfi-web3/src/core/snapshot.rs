@@ -0,0 +1,122 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::staking::{StakingAccount, StakingStatus};
+use crate::state::snapshot::StakingSnapshotAccount;
+use crate::constants::{STAKING_SEED, STAKING_SNAPSHOT_SEED};
+
+// Record the user's staked amount for the current epoch (one snapshot per epoch)
+pub fn process_take_staking_snapshot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let snapshot_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let staking_accounts = account_info_iter.as_slice(); // Staking positions to include
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if staking_accounts.is_empty() || staking_accounts.len() > u8::MAX as usize {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    // Get the current epoch
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let epoch_bytes = clock.epoch.to_le_bytes();
+
+    // Check the snapshot PDA
+    let (snapshot_pda, snapshot_bump) = Pubkey::find_program_address(
+        &[STAKING_SNAPSHOT_SEED, user_account.key.as_ref(), &epoch_bytes],
+        program_id,
+    );
+
+    if *snapshot_account.key != snapshot_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !snapshot_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Sum the positions, each counted once
+    let mut amount_staked: u64 = 0;
+    for (index, staking_account) in staking_accounts.iter().enumerate() {
+        if staking_accounts[..index].iter().any(|other| other.key == staking_account.key) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if staking_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+        let (staking_pda, _) = Pubkey::find_program_address(
+            &[
+                STAKING_SEED,
+                user_account.key.as_ref(),
+                staking_data.usdc_mint.as_ref(),
+                &[staking_data.position_id],
+            ],
+            program_id,
+        );
+
+        if *staking_account.key != staking_pda || staking_data.owner != *user_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Only live positions carry voting power
+        let status = staking_data.get_status()?;
+        if status == StakingStatus::Active || status == StakingStatus::Locked {
+            amount_staked = amount_staked.saturating_add(staking_data.amount_staked);
+        }
+    }
+
+    // Create the snapshot account
+    let rent = Rent::get()?;
+    let space = StakingSnapshotAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            user_account.key,
+            &snapshot_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[user_account.clone(), snapshot_account.clone(), system_program.clone()],
+        &[&[STAKING_SNAPSHOT_SEED, user_account.key.as_ref(), &epoch_bytes, &[snapshot_bump]]],
+    )?;
+
+    let snapshot_data = StakingSnapshotAccount {
+        owner: *user_account.key,
+        epoch: clock.epoch,
+        amount_staked,
+        position_count: staking_accounts.len() as u8,
+        taken_at: clock.unix_timestamp,
+        bump: snapshot_bump,
+    };
+
+    snapshot_data.serialize(&mut *snapshot_account.data.borrow_mut())?;
+
+    msg!("Staking snapshot for epoch {}: {} staked", clock.epoch, amount_staked);
+    Ok(())
+}
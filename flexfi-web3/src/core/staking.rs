@@ -2,7 +2,7 @@ use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program::{invoke, invoke_signed},
+    program::{invoke_signed, set_return_data},
     pubkey::Pubkey,
     system_instruction,
     sysvar::{clock::Clock, Sysvar, rent::Rent},
@@ -12,20 +12,64 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use spl_associated_token_account;
 use crate::error::FlexfiError;
 use crate::state::{staking::{StakingAccount, StakingStatus}};
-use crate::constants::{STAKING_SEED, USDC_VAULT_SEED, MIN_STAKING_AMOUNT, MIN_STAKING_LOCK_DAYS, MAX_STAKING_LOCK_DAYS};
-use crate::core::whitelist::require_whitelisted;
+use crate::state::yield_::YieldAccount;
+use crate::state::authorization::AuthorizationAccount;
+use crate::constants::{
+    STAKING_SEED, USDC_VAULT_SEED, REWARDS_VAULT_SEED, TREASURY_SEED, MIN_STAKING_AMOUNT,
+    MIN_STAKING_LOCK_DAYS, MAX_STAKING_LOCK_DAYS,
+    FREEZE_REASON_NONE, FREEZE_REASON_COMPLIANCE, FREEZE_REASON_FRAUD,
+    YIELD_CONFIG_SEED, AUTHORIZATION_SEED, INSURANCE_PENALTY_SHARE_BPS,
+};
+use crate::core::whitelist::require_whitelist_authority;
+use crate::core::mint_registry::get_mint_price_feed;
+use crate::oracle::get_usd_value;
+use crate::math::penalty_amount;
+use crate::token::{transfer_checked, get_token_account_amount};
+use crate::core::config::load_config;
+use crate::core::wallet::{update_staking_positions, load_recovered_wallet};
+use crate::insurance::reserve::{check_reserve_token_account, get_insurance_share};
+use crate::rewards::emissions::{record_epoch_activity, Activity, ActivityAccounts};
+
+// Current staking reward index from the protocol config
+pub(crate) fn get_staking_reward_index(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    current_time: i64,
+) -> Result<u128, ProgramError> {
+    Ok(load_config(program_id, config_account)?.get_reward_index(current_time))
+}
+
+// Check that the vault authority is the position's vault PDA and return its bump for signing
+pub(crate) fn get_vault_bump(
+    program_id: &Pubkey,
+    staking_account: &Pubkey,
+    vault_authority: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (vault_pda, vault_bump) = Pubkey::find_program_address(
+        &[USDC_VAULT_SEED, staking_account.as_ref()],
+        program_id
+    );
+
+    if *vault_authority != vault_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(vault_bump)
+}
 
 pub fn process_deposit_staking(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
     lock_days: u16,
+    position_id: u8,
+    vesting: bool,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let staking_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?; // Whitelist account
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
     let user_token_account = next_account_info(account_info_iter)?;
     let vault_token_account = next_account_info(account_info_iter)?;
     let usdc_mint = next_account_info(account_info_iter)?;
@@ -34,34 +78,54 @@ pub fn process_deposit_staking(
     let associated_token_program = next_account_info(account_info_iter)?;
     let _rent_sysvar = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?; // Holds the reward index
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let price_feed_account = next_account_info(account_info_iter)?; // Ignored for stablecoins
+    let wallet_account = next_account_info(account_info_iter)?; // Counts the position when it opens
+    let activity_accounts = ActivityAccounts {
+        schedule_account: next_account_info(account_info_iter)?,
+        epoch_activity_account: next_account_info(account_info_iter)?,
+        user_activity_account: next_account_info(account_info_iter)?,
+        system_program,
+    };
+    // A delegate signs in place of the owner for managed accounts
+    let delegate_account = if user_account.is_signer {
+        None
+    } else {
+        Some(next_account_info(account_info_iter)?)
+    };
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
 
-    // Check user signature
-    if !user_account.is_signer {
+    // Check owner or delegate signature
+    if !user_account.is_signer && !delegate_account.is_some_and(|d| d.is_signer) {
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    // Check if the user is whitelisted
-    require_whitelisted(
-        program_id,
-        user_account.key,
-        user_status_account
-    )?;
+    // Check that the mint is accepted as collateral
+    let price_feed = get_mint_price_feed(program_id, usdc_mint.key, accepted_mints_account)?;
+
+    // Get current time
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+    let reward_index = get_staking_reward_index(program_id, config_account, current_time)?;
 
-    // Check minimum amount
-    if amount < MIN_STAKING_AMOUNT {
+    // Check minimum amount, valued in USD for non-stablecoin collateral
+    let deposit_value = get_usd_value(usdc_mint, &price_feed, price_feed_account, amount, current_time)?;
+    if deposit_value < MIN_STAKING_AMOUNT {
         return Err(FlexfiError::InsufficientStaking.into());
     }
 
     // Check lock period
-    if lock_days < MIN_STAKING_LOCK_DAYS || lock_days > MAX_STAKING_LOCK_DAYS {
+    if !(MIN_STAKING_LOCK_DAYS..=MAX_STAKING_LOCK_DAYS).contains(&lock_days) {
         return Err(ProgramError::InvalidArgument);
     }
 
-    // Find the PDA of the staking account
+    // Find the PDA of the staking account (one per position)
     let seeds = [
         STAKING_SEED,
         user_account.key.as_ref(),
         usdc_mint.key.as_ref(),
+        &[position_id],
     ];
     let (staking_pda, staking_bump) = Pubkey::find_program_address(&seeds, program_id);
 
@@ -84,23 +148,30 @@ pub fn process_deposit_staking(
     msg!("Received vault account: {}", vault_token_account.key);
     msg!("Vault bump: {}", vault_bump);
 
-    // Get current time
-    let clock = Clock::from_account_info(clock_sysvar)?;
-    let current_time = clock.unix_timestamp;
-
     // Initialize or update the staking account
     let mut staking_data = if !staking_account.data_is_empty() {
         // Existing account, load data
         let mut data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
 
+        // Verify the delegate if the owner did not sign
+        if let Some(delegate) = delegate_account {
+            if !data.is_delegate(delegate.key) {
+                return Err(FlexfiError::Unauthorized.into());
+            }
+        }
+
         // Check that staking is active or locked
         let status = data.get_status()?;
         if status != StakingStatus::Active && status != StakingStatus::Locked {
             return Err(FlexfiError::StakingFrozen.into());
         }
 
-        // Update amounts and lock period
-        data.amount_staked = data.amount_staked.saturating_add(amount);
+        // Accrue rewards on the previous balance before it changes
+        data.accrue_rewards(reward_index, current_time);
+
+        // Update amounts and lock period (the vesting mode is set when the position opens);
+        // the top-up gets its own lock instead of relocking the whole balance
+        data.add_locked_deposit(amount, current_time + (lock_days as i64 * 86400), current_time);
 
         if status == StakingStatus::Locked {
             let new_lock_end = current_time + (lock_days as i64 * 86400);
@@ -115,6 +186,12 @@ pub fn process_deposit_staking(
         data.last_update = current_time;
         data
     } else {
+        // Only the owner can open a new position
+        if delegate_account.is_some() {
+            msg!("Only the owner can open a staking position");
+            return Err(FlexfiError::Unauthorized.into());
+        }
+
         // New staking account to create
         let rent = Rent::get()?;
         let space = StakingAccount::SIZE;
@@ -131,7 +208,7 @@ pub fn process_deposit_staking(
                 program_id,
             ),
             &[user_account.clone(), staking_account.clone(), system_program.clone()],
-            &[&[STAKING_SEED, user_account.key.as_ref(), usdc_mint.key.as_ref(), &[staking_bump]]],
+            &[&[STAKING_SEED, user_account.key.as_ref(), usdc_mint.key.as_ref(), &[position_id], &[staking_bump]]],
         )?;
 
         // Create the vault ATA if necessary
@@ -141,7 +218,7 @@ pub fn process_deposit_staking(
                     user_account.key,
                     &vault_pda,
                     usdc_mint.key,
-                    &spl_token::id(),
+                    token_program.key,
                 ),
                 &[
                     user_account.clone(),
@@ -155,42 +232,49 @@ pub fn process_deposit_staking(
             )?;
         }
 
+        update_staking_positions(program_id, wallet_account, user_account.key, true)?;
+
         // Initialize staking data
-        StakingAccount::new(
+        let mut data = StakingAccount::new(
             *user_account.key,
             *usdc_mint.key,
-            amount,
-            StakingStatus::Locked,
-            current_time + (lock_days as i64 * 86400),
+            position_id,
+            vesting,
             current_time,
+            reward_index,
             staking_bump,
-        )
+        );
+        data.add_locked_deposit(amount, current_time + (lock_days as i64 * 86400), current_time);
+        data
     };
 
+    // Stake-days held before this deposit earn staking emissions at their USD value
+    let stake_days = staking_data.take_stake_days();
+
     // Save staking data
     staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
 
-    // Transfer USDC to the vault
-    let transfer_ix = spl_token::instruction::transfer(
-        token_program.key,
-        user_token_account.key,
-        vault_token_account.key,
-        user_account.key,
-        &[],
+    // Transfer the collateral to the vault (from the delegate when it signs)
+    let transfer_authority = delegate_account.unwrap_or(user_account);
+
+    transfer_checked(
+        token_program,
+        user_token_account,
+        usdc_mint,
+        vault_token_account,
+        transfer_authority,
+        hook_accounts,
         amount,
+        &[],
     )?;
 
-    invoke(
-        &transfer_ix,
-        &[
-            user_token_account.clone(),
-            vault_token_account.clone(),
-            user_account.clone(),
-            token_program.clone(),
-        ],
-    )?;
+    if stake_days > 0 {
+        let stake_days_value = get_usd_value(usdc_mint, &price_feed, price_feed_account, stake_days, current_time)?;
+        record_epoch_activity(program_id, &activity_accounts, user_account.key, transfer_authority, Activity::Staking(stake_days_value))?;
+    }
 
-    msg!("Staking deposit successful: {} units, locked for {} days", amount, lock_days);
+    msg!("Staking deposit successful: {} units, locked for {} days (position {}, vesting {})",
+        amount, lock_days, position_id, staking_data.vesting);
     Ok(())
 }
 
@@ -203,24 +287,21 @@ pub fn process_withdraw_staking(
 
     let staking_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?; // Whitelist account
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
     let user_token_account = next_account_info(account_info_iter)?;
     let vault_token_account = next_account_info(account_info_iter)?;
+    let vault_authority = next_account_info(account_info_iter)?;
+    let staking_mint = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?; // Holds the reward index
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
 
     // Check user signature
     if !user_account.is_signer {
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    // CHECK IF THE USER IS WHITELISTED
-    require_whitelisted(
-        program_id,
-        user_account.key,
-        user_status_account
-    )?;
-
     // Load staking data
     let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
 
@@ -229,26 +310,43 @@ pub fn process_withdraw_staking(
         return Err(FlexfiError::Unauthorized.into());
     }
 
+    // Verify the staking mint
+    if *staking_mint.key != staking_data.usdc_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
     // Check staking status
     let status = staking_data.get_status()?;
     if status == StakingStatus::Frozen || status == StakingStatus::Closed {
         return Err(FlexfiError::StakingFrozen.into());
     }
 
+    // Verify the vault authority
+    let vault_bump = get_vault_bump(program_id, staking_account.key, vault_authority.key)?;
+
     // Get current timestamp
     let clock = Clock::from_account_info(clock_sysvar)?;
     let current_time = clock.unix_timestamp;
-
-    // If locked, check if the lock period has ended
-    if status == StakingStatus::Locked && current_time < staking_data.lock_period_end {
-        return Err(FlexfiError::StakingFrozen.into());
-    }
+    let reward_index = get_staking_reward_index(program_id, config_account, current_time)?;
 
     // Check if the requested amount is available
-    if amount > staking_data.amount_staked {
+    if amount > staking_data.available_amount() {
         return Err(FlexfiError::InsufficientStaking.into());
     }
 
+    // If locked, only the vested portion or matured tranches can leave
+    let withdrawable = staking_data.unlocked_amount(current_time);
+    if amount > withdrawable {
+        msg!("Only {} units vested or in matured tranches", withdrawable);
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    staking_data.prune_matured_tranches(current_time);
+    let still_locked = staking_data.tranche_count > 0;
+
+    // Accrue rewards on the previous balance before it changes
+    staking_data.accrue_rewards(reward_index, current_time);
+
     // Update the staked amount
     staking_data.amount_staked = staking_data.amount_staked.saturating_sub(amount);
     staking_data.last_update = current_time;
@@ -256,6 +354,9 @@ pub fn process_withdraw_staking(
     // If the remaining amount is less than the minimum, close the account
     if staking_data.amount_staked < MIN_STAKING_AMOUNT {
         staking_data.set_status(StakingStatus::Closed);
+    } else if still_locked {
+        // A partial vested withdrawal keeps the rest locked
+        staking_data.set_status(StakingStatus::Locked);
     } else {
         // Otherwise, set to active status
         staking_data.set_status(StakingStatus::Active);
@@ -265,60 +366,50 @@ pub fn process_withdraw_staking(
     staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
 
     // Prepare seeds to sign with the vault PDA
-    let vault_seeds = [
-        USDC_VAULT_SEED,
-        staking_account.key.as_ref(),
-        &[staking_data.bump],
-    ];
+    let vault_seeds: &[&[u8]] = &[USDC_VAULT_SEED, staking_account.key.as_ref(), &[vault_bump]];
 
     // Transfer tokens from the vault to the user
-    let transfer_ix = spl_token::instruction::transfer(
-        token_program.key,
-        vault_token_account.key,
-        user_token_account.key,
-        &staking_account.key, // The staking account is the vault's authority
-        &[],
+    transfer_checked(
+        token_program,
+        vault_token_account,
+        staking_mint,
+        user_token_account,
+        vault_authority,
+        hook_accounts,
         amount,
-    )?;
-
-    invoke_signed(
-        &transfer_ix,
-        &[
-            vault_token_account.clone(),
-            user_token_account.clone(),
-            staking_account.clone(),
-            token_program.clone(),
-        ],
-        &[&vault_seeds],
+        &[vault_seeds],
     )?;
 
     msg!("Staking withdrawal successful: {} units", amount);
     Ok(())
 }
 
-pub fn process_check_unlock_status(
+pub fn process_emergency_withdraw_staking(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    amount: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let staking_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?; // Whitelist account
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+    let user_token_account = next_account_info(account_info_iter)?;
+    let vault_token_account = next_account_info(account_info_iter)?;
+    let vault_authority = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let staking_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?; // Holds the reward index
+    let insurance_token_account = next_account_info(account_info_iter)?; // Receives the insurance slice of the penalty
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
 
     // Check user signature
     if !user_account.is_signer {
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    // CHECK IF THE USER IS WHITELISTED
-    require_whitelisted(
-        program_id,
-        user_account.key,
-        user_status_account
-    )?;
-
     // Load staking data
     let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
 
@@ -327,6 +418,162 @@ pub fn process_check_unlock_status(
         return Err(FlexfiError::Unauthorized.into());
     }
 
+    // Frozen or closed positions can't be withdrawn, even in an emergency
+    let status = staking_data.get_status()?;
+    if status == StakingStatus::Frozen || status == StakingStatus::Closed {
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    // Verify the staking mint
+    if *staking_mint.key != staking_data.usdc_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check if the requested amount is available
+    if amount == 0 || amount > staking_data.available_amount() {
+        return Err(FlexfiError::InsufficientStaking.into());
+    }
+
+    // Verify the vault authority
+    let vault_bump = get_vault_bump(program_id, staking_account.key, vault_authority.key)?;
+
+    // The penalty must go to the treasury of the staking mint
+    let (treasury_pda, _) = Pubkey::find_program_address(
+        &[TREASURY_SEED, staking_data.usdc_mint.as_ref()],
+        program_id
+    );
+    let treasury_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &treasury_pda,
+        &staking_data.usdc_mint,
+        token_program.key,
+    );
+
+    if *treasury_token_account.key != treasury_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    check_reserve_token_account(program_id, &staking_data.usdc_mint, token_program.key, insurance_token_account)?;
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+    let config_data = load_config(program_id, config_account)?;
+    let reward_index = config_data.get_reward_index(current_time);
+
+    // Penalty only applies to the part still under lock
+    let is_early = status == StakingStatus::Locked && current_time < staking_data.lock_period_end;
+    let locked_part = if is_early {
+        amount.saturating_sub(staking_data.unlocked_amount(current_time))
+    } else {
+        0
+    };
+
+    // Stake that weighs in a running vote stays locked until the vote ends
+    if locked_part > 0 && staking_data.is_vote_locked(current_time) {
+        msg!("Stake is voting until {}", staking_data.vote_locked_until);
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    let penalty = penalty_amount(locked_part, config_data.emergency_withdraw_penalty_bps);
+    let payout = amount.saturating_sub(penalty);
+    let insurance_share = get_insurance_share(penalty, INSURANCE_PENALTY_SHARE_BPS);
+
+    // Accrue rewards on the previous balance before it changes
+    staking_data.accrue_rewards(reward_index, current_time);
+
+    // Broken locks leave their tranches
+    staking_data.release_locked(locked_part, current_time);
+
+    // Update the staked amount
+    staking_data.amount_staked = staking_data.amount_staked.saturating_sub(amount);
+    staking_data.last_update = current_time;
+
+    // Close the position if it falls below the minimum
+    if staking_data.amount_staked < MIN_STAKING_AMOUNT {
+        staking_data.set_status(StakingStatus::Closed);
+    }
+
+    // Save changes
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    let vault_seeds: &[&[u8]] = &[USDC_VAULT_SEED, staking_account.key.as_ref(), &[vault_bump]];
+
+    // Transfer the payout to the user
+    transfer_checked(
+        token_program,
+        vault_token_account,
+        staking_mint,
+        user_token_account,
+        vault_authority,
+        hook_accounts,
+        payout,
+        &[vault_seeds],
+    )?;
+
+    // Route the penalty to the treasury, less the insurance reserve's slice
+    if penalty > insurance_share {
+        transfer_checked(
+            token_program,
+            vault_token_account,
+            staking_mint,
+            treasury_token_account,
+            vault_authority,
+            hook_accounts,
+            penalty - insurance_share,
+            &[vault_seeds],
+        )?;
+    }
+
+    if insurance_share > 0 {
+        transfer_checked(
+            token_program,
+            vault_token_account,
+            staking_mint,
+            insurance_token_account,
+            vault_authority,
+            hook_accounts,
+            insurance_share,
+            &[vault_seeds],
+        )?;
+    }
+
+    msg!("Emergency staking withdrawal: {} units paid out, {} units penalty", payout, penalty);
+    Ok(())
+}
+
+// Permissionless crank: anyone can flip an expired lock from Locked to Active
+pub fn process_check_unlock_status(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // No signer is required, so make sure this is one of our staking accounts
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Verify the staking PDA
+    let (staking_pda, _) = Pubkey::find_program_address(
+        &[
+            STAKING_SEED,
+            staking_data.owner.as_ref(),
+            staking_data.usdc_mint.as_ref(),
+            &[staking_data.position_id],
+        ],
+        program_id
+    );
+
+    if *staking_account.key != staking_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
     // Check if staking is locked
     let status = staking_data.get_status()?;
     if status != StakingStatus::Locked {
@@ -358,31 +605,1093 @@ pub fn process_check_unlock_status(
     Ok(())
 }
 
-// Manager for staking functions
-pub struct StakingManager;
+pub fn process_extend_staking_lock(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    additional_days: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
 
-impl StakingManager {
-    pub fn deposit(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        amount: u64,
-        lock_days: u16,
-    ) -> ProgramResult {
-        process_deposit_staking(program_id, accounts, amount, lock_days)
+    let staking_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    // A delegate signs in place of the owner for managed accounts
+    let delegate_account = if user_account.is_signer {
+        None
+    } else {
+        Some(next_account_info(account_info_iter)?)
+    };
+
+    // Check owner or delegate signature
+    if !user_account.is_signer && !delegate_account.is_some_and(|d| d.is_signer) {
+        return Err(FlexfiError::Unauthorized.into());
     }
 
-    pub fn withdraw(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        amount: u64,
-    ) -> ProgramResult {
-        process_withdraw_staking(program_id, accounts, amount)
+    if additional_days == 0 {
+        return Err(ProgramError::InvalidArgument);
     }
 
-    pub fn check_unlock(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-    ) -> ProgramResult {
-        process_check_unlock_status(program_id, accounts)
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Verify that the user is the owner
+    if staking_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Verify the delegate if the owner did not sign
+    if let Some(delegate) = delegate_account {
+        if !staking_data.is_delegate(delegate.key) {
+            return Err(FlexfiError::Unauthorized.into());
+        }
+    }
+
+    // Only active or locked positions can be extended
+    let status = staking_data.get_status()?;
+    if status != StakingStatus::Active && status != StakingStatus::Locked {
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    // Extend from the current lock end, or from now if it already expired
+    let lock_start = std::cmp::max(staking_data.lock_period_end, current_time);
+    let new_lock_end = lock_start + (additional_days as i64 * 86400);
+
+    // The total remaining lock can't exceed the maximum lock period
+    if new_lock_end - current_time > MAX_STAKING_LOCK_DAYS as i64 * 86400 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Extending applies to the whole balance, which vests again from now in a vesting position
+    staking_data.lock_period_end = new_lock_end;
+    staking_data.relock_all(new_lock_end, current_time);
+    staking_data.set_status(StakingStatus::Locked);
+    staking_data.last_update = current_time;
+
+    // Save changes
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    msg!("Staking lock extended by {} days, BNPL fee discount: {} bps",
+         additional_days, staking_data.lock_fee_discount(current_time));
+    Ok(())
+}
+
+// Set or clear (with the default pubkey) the delegate of a staking position
+pub fn process_set_staking_delegate(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    delegate: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+
+    // Only the owner can change the delegate
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Verify that the user is the owner
+    if staking_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if delegate == *user_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    staking_data.delegate = delegate;
+
+    // Save changes
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    if delegate == Pubkey::default() {
+        msg!("Staking delegate cleared");
+    } else {
+        msg!("Staking delegate set to {}", delegate);
+    }
+    Ok(())
+}
+
+// Close an emptied staking position and refund its rent to the owner
+pub fn process_close_staking_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let vault_token_account = next_account_info(account_info_iter)?;
+    let wallet_account = next_account_info(account_info_iter)?;
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // The staking account must belong to the program
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Load staking data
+    let staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Verify that the user is the owner
+    if staking_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Only closed and fully withdrawn positions can be closed
+    if staking_data.get_status()? != StakingStatus::Closed || staking_data.amount_staked > 0 {
+        return Err(FlexfiError::StakingNotActive.into());
+    }
+
+    if staking_data.accrued_rewards > 0 {
+        msg!("Claim staking rewards before closing the position");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the vault belongs to this position and is empty
+    let (vault_pda, _) = Pubkey::find_program_address(
+        &[USDC_VAULT_SEED, staking_account.key.as_ref()],
+        program_id
+    );
+    let vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &vault_pda,
+        &staking_data.usdc_mint,
+        vault_token_account.owner,
+    );
+
+    if *vault_token_account.key != vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !vault_token_account.data_is_empty() && get_token_account_amount(vault_token_account)? > 0 {
+        msg!("Staking vault is not empty");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    update_staking_positions(program_id, wallet_account, user_account.key, false)?;
+
+    // Refund the rent to the owner
+    let staking_lamports = staking_account.lamports();
+    **user_account.lamports.borrow_mut() = user_account.lamports()
+        .checked_add(staking_lamports)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **staking_account.lamports.borrow_mut() = 0;
+
+    // Zero the account data
+    staking_account.data.borrow_mut().fill(0);
+
+    msg!("Staking account closed, {} lamports refunded", staking_lamports);
+    Ok(())
+}
+
+// Move a staking position of a recovered wallet's previous owner, and the collateral in its vault, to
+// the new owner's PDAs. Positions backing a deposit escrow or frozen move once settled or unfrozen.
+pub fn process_migrate_recovered_staking(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?; // The new owner's recovered wallet
+    let new_owner_account = next_account_info(account_info_iter)?;
+    let staking_account = next_account_info(account_info_iter)?;
+    let new_staking_account = next_account_info(account_info_iter)?;
+    let vault_authority = next_account_info(account_info_iter)?;
+    let vault_token_account = next_account_info(account_info_iter)?;
+    let new_vault_authority = next_account_info(account_info_iter)?;
+    let new_vault_token_account = next_account_info(account_info_iter)?;
+    let staking_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let associated_token_program = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    let wallet_data = load_recovered_wallet(program_id, wallet_account, new_owner_account)?;
+
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    if staking_data.owner != wallet_data.recovered_from || staking_data.usdc_mint != *staking_mint.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if staking_data.get_status()? == StakingStatus::Frozen {
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    if staking_data.escrowed_amount > 0 {
+        msg!("Settle the deposit escrows of the position before migrating it");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if staking_data.loan_hold > 0 {
+        msg!("Repay the micro loan the position backs before migrating it");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // The new owner could vote again with a position still weighing in a vote
+    if staking_data.is_vote_locked(Clock::get()?.unix_timestamp) {
+        msg!("Position is voting until {}", staking_data.vote_locked_until);
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    // Check the previous position and its vault
+    let (staking_pda, _) = Pubkey::find_program_address(
+        &[STAKING_SEED, staking_data.owner.as_ref(), staking_mint.key.as_ref(), &[staking_data.position_id]],
+        program_id
+    );
+
+    if *staking_account.key != staking_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (vault_pda, vault_bump) = Pubkey::find_program_address(
+        &[USDC_VAULT_SEED, staking_account.key.as_ref()],
+        program_id
+    );
+    let vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &vault_pda,
+        staking_mint.key,
+        token_program.key,
+    );
+
+    if *vault_authority.key != vault_pda || *vault_token_account.key != vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the new owner's position and its vault
+    let (new_staking_pda, new_staking_bump) = Pubkey::find_program_address(
+        &[STAKING_SEED, new_owner_account.key.as_ref(), staking_mint.key.as_ref(), &[staking_data.position_id]],
+        program_id
+    );
+
+    if *new_staking_account.key != new_staking_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !new_staking_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let (new_vault_pda, _) = Pubkey::find_program_address(
+        &[USDC_VAULT_SEED, new_staking_account.key.as_ref()],
+        program_id
+    );
+
+    if *new_vault_authority.key != new_vault_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent = Rent::get()?;
+    let space = StakingAccount::SIZE;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            new_owner_account.key,
+            &new_staking_pda,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[new_owner_account.clone(), new_staking_account.clone(), system_program.clone()],
+        &[&[STAKING_SEED, new_owner_account.key.as_ref(), staking_mint.key.as_ref(), &[staking_data.position_id], &[new_staking_bump]]],
+    )?;
+
+    if new_vault_token_account.data_is_empty() {
+        invoke_signed(
+            &spl_associated_token_account::instruction::create_associated_token_account(
+                new_owner_account.key,
+                &new_vault_pda,
+                staking_mint.key,
+                token_program.key,
+            ),
+            &[
+                new_owner_account.clone(),
+                new_vault_token_account.clone(),
+                new_vault_authority.clone(),
+                staking_mint.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+            &[],
+        )?;
+    }
+
+    // Move the whole vault balance
+    let vault_balance = get_token_account_amount(vault_token_account)?;
+
+    if vault_balance > 0 {
+        transfer_checked(
+            token_program,
+            vault_token_account,
+            staking_mint,
+            new_vault_token_account,
+            vault_authority,
+            hook_accounts,
+            vault_balance,
+            &[&[USDC_VAULT_SEED, staking_account.key.as_ref(), &[vault_bump]]],
+        )?;
+    }
+
+    // The delegate was appointed by the previous owner, the new owner appoints their own
+    staking_data.owner = *new_owner_account.key;
+    staking_data.delegate = Pubkey::default();
+    staking_data.bump = new_staking_bump;
+    staking_data.serialize(&mut *new_staking_account.data.borrow_mut())?;
+
+    // Refund the previous position's rent to the new owner; the wallet's position count is unchanged
+    let staking_lamports = staking_account.lamports();
+    **new_owner_account.lamports.borrow_mut() = new_owner_account.lamports()
+        .checked_add(staking_lamports)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **staking_account.lamports.borrow_mut() = 0;
+    staking_account.data.borrow_mut().fill(0);
+
+    msg!("Staking position {} moved to {} with {} in its vault",
+        staking_data.position_id, new_staking_account.key, vault_balance);
+    Ok(())
+}
+
+// Freeze a staking position (compliance or dispute handling, admin only)
+pub fn process_freeze_staking(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reason: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    // Check the reason code
+    if !(FREEZE_REASON_COMPLIANCE..=FREEZE_REASON_FRAUD).contains(&reason) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Closed positions can't be frozen, frozen ones only get a new reason
+    let status = staking_data.get_status()?;
+    if status == StakingStatus::Closed {
+        return Err(FlexfiError::StakingNotActive.into());
+    }
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    staking_data.set_status(StakingStatus::Frozen);
+    staking_data.freeze_reason = reason;
+    staking_data.last_update = current_time;
+
+    // Save changes
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    msg!("Staking {} frozen by {}, reason code {}", staking_account.key, authority.key, reason);
+    Ok(())
+}
+
+// Unfreeze a staking position (admin only)
+pub fn process_unfreeze_staking(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    if staking_data.get_status()? != StakingStatus::Frozen {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    // Restore the lock if it is still running
+    if current_time < staking_data.lock_period_end {
+        staking_data.set_status(StakingStatus::Locked);
+    } else {
+        staking_data.set_status(StakingStatus::Active);
+    }
+    staking_data.freeze_reason = FREEZE_REASON_NONE;
+    staking_data.last_update = current_time;
+
+    // Save changes
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    msg!("Staking {} unfrozen by {}", staking_account.key, authority.key);
+    Ok(())
+}
+
+pub fn process_claim_staking_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+    let user_token_account = next_account_info(account_info_iter)?;
+    let rewards_vault_token_account = next_account_info(account_info_iter)?;
+    let rewards_vault_authority = next_account_info(account_info_iter)?;
+    let staking_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?; // Holds the reward index
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let price_feed_account = next_account_info(account_info_iter)?; // Ignored for stablecoins
+    let activity_accounts = ActivityAccounts {
+        schedule_account: next_account_info(account_info_iter)?,
+        epoch_activity_account: next_account_info(account_info_iter)?,
+        user_activity_account: next_account_info(account_info_iter)?,
+        system_program: next_account_info(account_info_iter)?,
+    };
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Verify that the user is the owner
+    if staking_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Verify the staking mint
+    if *staking_mint.key != staking_data.usdc_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the rewards vault authority (one per staking mint)
+    let (rewards_vault_pda, rewards_vault_bump) = Pubkey::find_program_address(
+        &[REWARDS_VAULT_SEED, staking_data.usdc_mint.as_ref()],
+        program_id
+    );
+
+    if *rewards_vault_authority.key != rewards_vault_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+    let reward_index = get_staking_reward_index(program_id, config_account, current_time)?;
+
+    // Bring rewards and stake-days up to date
+    staking_data.accrue_rewards(reward_index, current_time);
+
+    let rewards = staking_data.accrued_rewards;
+    let stake_days = staking_data.take_stake_days();
+    if rewards == 0 && stake_days == 0 {
+        return Err(FlexfiError::NoStakingRewards.into());
+    }
+
+    // Reset accrued rewards before transferring
+    staking_data.accrued_rewards = 0;
+    staking_data.last_update = current_time;
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    // Transfer rewards from the rewards vault to the user
+    if rewards > 0 {
+        transfer_checked(
+            token_program,
+            rewards_vault_token_account,
+            staking_mint,
+            user_token_account,
+            rewards_vault_authority,
+            hook_accounts,
+            rewards,
+            &[&[REWARDS_VAULT_SEED, staking_data.usdc_mint.as_ref(), &[rewards_vault_bump]]],
+        )?;
+    }
+
+    // The stake held since the last record earns staking emissions at its USD value
+    if stake_days > 0 {
+        let price_feed = get_mint_price_feed(program_id, staking_mint.key, accepted_mints_account)?;
+        let stake_days_value = get_usd_value(staking_mint, &price_feed, price_feed_account, stake_days, current_time)?;
+        record_epoch_activity(program_id, &activity_accounts, user_account.key, user_account, Activity::Staking(stake_days_value))?;
+    }
+
+    msg!("Staking rewards claimed: {} units, {} stake-days recorded", rewards, stake_days);
+    Ok(())
+}
+
+// Opt in or out of keeper-driven reward compounding
+pub fn process_set_auto_compound(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+
+    // Only the owner can change the flag
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Verify that the user is the owner
+    if staking_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    staking_data.auto_compound = enabled;
+
+    // Save changes
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    msg!("Staking auto-compound set to {}", enabled);
+    Ok(())
+}
+
+// Keeper crank folding accrued rewards back into the stake of opted-in positions
+pub fn process_compound_staking_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let vault_token_account = next_account_info(account_info_iter)?;
+    let rewards_vault_token_account = next_account_info(account_info_iter)?;
+    let rewards_vault_authority = next_account_info(account_info_iter)?;
+    let staking_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?; // Holds the reward index
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // The staking account must belong to the program
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Check the staking account from its stored seeds
+    let (staking_pda, _) = Pubkey::find_program_address(
+        &[
+            STAKING_SEED,
+            staking_data.owner.as_ref(),
+            staking_data.usdc_mint.as_ref(),
+            &[staking_data.position_id],
+        ],
+        program_id
+    );
+
+    if *staking_account.key != staking_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !staking_data.auto_compound {
+        msg!("Auto-compound is not enabled for this position");
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check staking status
+    let status = staking_data.get_status()?;
+    if status != StakingStatus::Active && status != StakingStatus::Locked {
+        return Err(FlexfiError::StakingNotActive.into());
+    }
+
+    // Verify the staking mint
+    if *staking_mint.key != staking_data.usdc_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the staking vault
+    let (vault_pda, _) = Pubkey::find_program_address(
+        &[USDC_VAULT_SEED, staking_account.key.as_ref()],
+        program_id
+    );
+    let vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &vault_pda,
+        staking_mint.key,
+        token_program.key,
+    );
+
+    if *vault_token_account.key != vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the rewards vault authority
+    let (rewards_vault_pda, rewards_vault_bump) = Pubkey::find_program_address(
+        &[REWARDS_VAULT_SEED, staking_data.usdc_mint.as_ref()],
+        program_id
+    );
+
+    if *rewards_vault_authority.key != rewards_vault_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+    let reward_index = get_staking_reward_index(program_id, config_account, current_time)?;
+
+    // Bring rewards up to date
+    staking_data.accrue_rewards(reward_index, current_time);
+
+    let rewards = staking_data.accrued_rewards;
+    if rewards == 0 {
+        return Err(FlexfiError::NoStakingRewards.into());
+    }
+
+    // Move the rewards into the stake
+    staking_data.accrued_rewards = 0;
+    staking_data.amount_staked = staking_data.amount_staked.saturating_add(rewards);
+    staking_data.last_update = current_time;
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    // Transfer rewards from the rewards vault to the staking vault
+    transfer_checked(
+        token_program,
+        rewards_vault_token_account,
+        staking_mint,
+        vault_token_account,
+        rewards_vault_authority,
+        hook_accounts,
+        rewards,
+        &[&[REWARDS_VAULT_SEED, staking_data.usdc_mint.as_ref(), &[rewards_vault_bump]]],
+    )?;
+
+    msg!("Staking rewards compounded: {} units, new staked amount: {}", rewards, staking_data.amount_staked);
+    Ok(())
+}
+
+// View: rewards a position has earned so far (accrued plus pending since the last accrual), returned as u64 data
+pub fn process_get_accrued_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // The staking account must belong to the program
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let reward_index = get_staking_reward_index(program_id, config_account, clock.unix_timestamp)?;
+
+    let rewards = staking_data.accrued_rewards.saturating_add(staking_data.pending_rewards(reward_index));
+
+    set_return_data(&rewards.try_to_vec()?);
+
+    msg!("Accrued staking rewards: {} units", rewards);
+    Ok(())
+}
+
+// Opt in or out of moving the stake into the owner's yield strategy once it unlocks
+pub fn process_set_convert_on_unlock(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+
+    // Only the owner can change the flag
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Verify that the user is the owner
+    if staking_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    staking_data.convert_on_unlock = enabled;
+
+    // Save changes
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    msg!("Staking convert-on-unlock set to {}", enabled);
+    Ok(())
+}
+
+// Keeper crank moving an opted-in position into the owner's yield strategy once its lock is over
+// and nothing on it is reserved for BNPL spending, so the capital doesn't sit idle
+pub fn process_convert_unlocked_staking(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let staking_account = next_account_info(account_info_iter)?;
+    let yield_account = next_account_info(account_info_iter)?;
+    let authorization_account = next_account_info(account_info_iter)?; // The owner's FlexFi authorization, even if never created
+    let vault_authority = next_account_info(account_info_iter)?;
+    let vault_token_account = next_account_info(account_info_iter)?;
+    let yield_token_account = next_account_info(account_info_iter)?;
+    let staking_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?; // Holds the reward index
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // The staking account must belong to the program
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Check the staking account from its stored seeds
+    let (staking_pda, _) = Pubkey::find_program_address(
+        &[
+            STAKING_SEED,
+            staking_data.owner.as_ref(),
+            staking_data.usdc_mint.as_ref(),
+            &[staking_data.position_id],
+        ],
+        program_id
+    );
+
+    if *staking_account.key != staking_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !staking_data.convert_on_unlock {
+        msg!("Convert-on-unlock is not enabled for this position");
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check staking status
+    let status = staking_data.get_status()?;
+    if status == StakingStatus::Frozen || status == StakingStatus::Closed {
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    // Verify the staking mint
+    if *staking_mint.key != staking_data.usdc_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    if !staking_data.is_fully_unlocked(current_time) {
+        msg!("Position is locked until {}", staking_data.lock_period_end);
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    // Stake backing escrows or a live FlexFi authorization stays put
+    if staking_data.escrowed_amount > 0 {
+        msg!("{} units of the stake back deposit escrows", staking_data.escrowed_amount);
+        return Err(FlexfiError::InsufficientStaking.into());
+    }
+
+    if staking_data.loan_hold > 0 {
+        msg!("{} units of the stake are held for a micro loan", staking_data.loan_hold);
+        return Err(FlexfiError::InsufficientStaking.into());
+    }
+
+    let (authorization_pda, _) = Pubkey::find_program_address(
+        &[AUTHORIZATION_SEED, staking_data.owner.as_ref()],
+        program_id
+    );
+
+    if *authorization_account.key != authorization_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if authorization_account.owner == program_id {
+        let authorization = AuthorizationAccount::try_from_slice(&authorization_account.data.borrow())?;
+
+        if authorization.is_valid(current_time) {
+            msg!("Stake is reserved by an active FlexFi authorization");
+            return Err(FlexfiError::InsufficientStaking.into());
+        }
+    }
+
+    // Verify the owner's yield account; the strategy must have been chosen beforehand
+    let (yield_pda, _) = Pubkey::find_program_address(
+        &[YIELD_CONFIG_SEED, staking_data.owner.as_ref()],
+        program_id
+    );
+
+    if *yield_account.key != yield_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if yield_account.owner != program_id {
+        msg!("No yield strategy chosen");
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let mut yield_data = YieldAccount::try_from_slice(&yield_account.data.borrow())?;
+
+    let yield_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &yield_pda,
+        staking_mint.key,
+        token_program.key,
+    );
+
+    if *yield_token_account.key != yield_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the vault authority
+    let (vault_pda, vault_bump) = Pubkey::find_program_address(
+        &[USDC_VAULT_SEED, staking_account.key.as_ref()],
+        program_id
+    );
+
+    if *vault_authority.key != vault_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let amount = staking_data.available_amount();
+    if amount == 0 {
+        return Err(FlexfiError::InsufficientStaking.into());
+    }
+
+    let reward_index = get_staking_reward_index(program_id, config_account, current_time)?;
+
+    // Accrue rewards on the balance before it leaves; they stay claimable on the position
+    staking_data.accrue_rewards(reward_index, current_time);
+
+    staking_data.amount_staked = 0;
+    staking_data.prune_matured_tranches(current_time);
+    staking_data.last_update = current_time;
+    staking_data.set_status(StakingStatus::Closed);
+
+    yield_data.principal = yield_data.principal.saturating_add(amount);
+
+    // Save changes
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+    yield_data.serialize(&mut *yield_account.data.borrow_mut())?;
+
+    // Transfer the stake from the vault to the yield account
+    transfer_checked(
+        token_program,
+        vault_token_account,
+        staking_mint,
+        yield_token_account,
+        vault_authority,
+        hook_accounts,
+        amount,
+        &[&[USDC_VAULT_SEED, staking_account.key.as_ref(), &[vault_bump]]],
+    )?;
+
+    msg!(
+        "Unlocked stake of {} converted to {:?}: {} units",
+        staking_data.owner,
+        yield_data.get_strategy()?,
+        amount
+    );
+    Ok(())
+}
+
+// Manager for staking functions
+pub struct StakingManager;
+
+impl StakingManager {
+    pub fn deposit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        lock_days: u16,
+        position_id: u8,
+        vesting: bool,
+    ) -> ProgramResult {
+        process_deposit_staking(program_id, accounts, amount, lock_days, position_id, vesting)
+    }
+
+    pub fn withdraw(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        process_withdraw_staking(program_id, accounts, amount)
+    }
+
+    pub fn emergency_withdraw(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        process_emergency_withdraw_staking(program_id, accounts, amount)
+    }
+
+    pub fn check_unlock(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_check_unlock_status(program_id, accounts)
+    }
+
+    pub fn extend_lock(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        additional_days: u16,
+    ) -> ProgramResult {
+        process_extend_staking_lock(program_id, accounts, additional_days)
+    }
+
+    pub fn set_delegate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        delegate: Pubkey,
+    ) -> ProgramResult {
+        process_set_staking_delegate(program_id, accounts, delegate)
+    }
+
+    pub fn close(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_close_staking_account(program_id, accounts)
+    }
+
+    pub fn freeze(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reason: u8,
+    ) -> ProgramResult {
+        process_freeze_staking(program_id, accounts, reason)
+    }
+
+    pub fn unfreeze(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_unfreeze_staking(program_id, accounts)
+    }
+
+    pub fn claim_rewards(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_claim_staking_rewards(program_id, accounts)
+    }
+
+    pub fn set_auto_compound(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        enabled: bool,
+    ) -> ProgramResult {
+        process_set_auto_compound(program_id, accounts, enabled)
+    }
+
+    pub fn compound_rewards(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_compound_staking_rewards(program_id, accounts)
+    }
+
+    pub fn get_accrued_rewards(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_get_accrued_rewards(program_id, accounts)
+    }
+
+    pub fn set_convert_on_unlock(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        enabled: bool,
+    ) -> ProgramResult {
+        process_set_convert_on_unlock(program_id, accounts, enabled)
+    }
+
+    pub fn convert_unlocked(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_convert_unlocked_staking(program_id, accounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vault_bump_signs_for_the_vault_pda() {
+        let program_id = Pubkey::new_unique();
+        let staking_account = Pubkey::new_unique();
+        let (vault_pda, _) = Pubkey::find_program_address(
+            &[USDC_VAULT_SEED, staking_account.as_ref()],
+            &program_id
+        );
+
+        let vault_bump = get_vault_bump(&program_id, &staking_account, &vault_pda).unwrap();
+        let signer = Pubkey::create_program_address(
+            &[USDC_VAULT_SEED, staking_account.as_ref(), &[vault_bump]],
+            &program_id
+        ).unwrap();
+        assert_eq!(signer, vault_pda);
+    }
+
+    #[test]
+    fn vault_bump_rejects_other_authorities() {
+        let program_id = Pubkey::new_unique();
+        let staking_account = Pubkey::new_unique();
+
+        // The staking account itself is not the vault's authority
+        assert_eq!(
+            get_vault_bump(&program_id, &staking_account, &staking_account),
+            Err(ProgramError::InvalidAccountData)
+        );
     }
 }
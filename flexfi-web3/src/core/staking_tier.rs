@@ -0,0 +1,161 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::staking_tier::{StakingTierConfig, StakingTier};
+use crate::constants::{
+    STAKING_TIER_CONFIG_SEED, MAX_STAKING_TIERS, DEFAULT_STAKING_TIERS, MAX_LTV_BPS,
+    MAX_STAKING_TIER_DISCOUNT_BPS,
+};
+
+// Helper returning the benefits of the tier reached by a staked amount
+pub fn get_staking_tier(
+    program_id: &Pubkey,
+    staking_tier_config_account: &AccountInfo,
+    amount_staked: u64,
+) -> Result<StakingTier, ProgramError> {
+    let (config_pda, _) = Pubkey::find_program_address(&[STAKING_TIER_CONFIG_SEED], program_id);
+
+    if *staking_tier_config_account.key != config_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let config_data = StakingTierConfig::try_from_slice(&staking_tier_config_account.data.borrow())
+        .map_err(|_| ProgramError::UninitializedAccount)?;
+
+    Ok(config_data.get_tier(amount_staked))
+}
+
+// Initialize the staking tier configuration (called once by an admin)
+pub fn process_initialize_staking_tiers(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify that the authority is the signer
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Create the PDA for the tier configuration
+    let (config_pda, bump) = Pubkey::find_program_address(&[STAKING_TIER_CONFIG_SEED], program_id);
+
+    if config_account.key != &config_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the account
+    let rent = Rent::get()?;
+    let space = StakingTierConfig::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &config_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[authority.clone(), config_account.clone(), system_program.clone()],
+        &[&[STAKING_TIER_CONFIG_SEED, &[bump]]],
+    )?;
+
+    // Initialize with the default tiers
+    let mut tiers = [StakingTier::default(); MAX_STAKING_TIERS];
+    for (tier, (min_staked, fee_discount_bps, max_bnpl_multiplier_bps)) in
+        tiers.iter_mut().zip(DEFAULT_STAKING_TIERS.iter())
+    {
+        *tier = StakingTier {
+            min_staked: *min_staked,
+            fee_discount_bps: *fee_discount_bps,
+            max_bnpl_multiplier_bps: *max_bnpl_multiplier_bps,
+        };
+    }
+
+    let config_data = StakingTierConfig {
+        authority: *authority.key,
+        tier_count: DEFAULT_STAKING_TIERS.len() as u8,
+        tiers,
+        bump,
+    };
+
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Staking tiers initialized with authority: {}", authority.key);
+    Ok(())
+}
+
+// Set or append a staking tier
+pub fn process_set_staking_tier(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u8,
+    tier: StakingTier,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority
+    if !authority.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let (config_pda, _) = Pubkey::find_program_address(&[STAKING_TIER_CONFIG_SEED], program_id);
+
+    if config_account.key != &config_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut config_data = StakingTierConfig::try_from_slice(&config_account.data.borrow())?;
+
+    if config_data.authority != *authority.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // An existing tier can be replaced, or a new one appended
+    let index = index as usize;
+    if index > config_data.tier_count as usize || index >= MAX_STAKING_TIERS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if tier.fee_discount_bps > MAX_STAKING_TIER_DISCOUNT_BPS || tier.max_bnpl_multiplier_bps > MAX_LTV_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Keep the tiers sorted by threshold
+    if index > 0 && tier.min_staked <= config_data.tiers[index - 1].min_staked {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if index + 1 < config_data.tier_count as usize && tier.min_staked >= config_data.tiers[index + 1].min_staked {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.tiers[index] = tier;
+    if index == config_data.tier_count as usize {
+        config_data.tier_count += 1;
+    }
+
+    config_data.serialize(&mut *config_account.data.borrow_mut())?;
+
+    msg!("Staking tier {} set: min {}, discount {} bps, BNPL multiplier {} bps",
+        index, tier.min_staked, tier.fee_discount_bps, tier.max_bnpl_multiplier_bps);
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::set_return_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::state::whitelist::WhitelistAccount;
+use crate::constants::{
+    FLEXFI_VERSION, WHITELIST_SEED, MIN_STAKING_AMOUNT, MIN_STAKING_LOCK_DAYS,
+    MAX_STAKING_LOCK_DAYS, STAKING_REWARD_APY_BPS, INITIAL_SCORE, MAX_SCORE,
+    NFT_MINT_COST, CARD_STANDARD, CARD_TYPE_COUNT, SCORE_TIER_COUNT,
+    MINIMUM_FEE_PERCENTAGE, MAXIMUM_FEE_PERCENTAGE, MAX_MERCHANT_FEE_BPS,
+    MIN_BNPL_INSTALLMENTS, MAX_BNPL_INSTALLMENTS,
+    MIN_PAYMENT_INTERVAL_DAYS, MAX_PAYMENT_INTERVAL_DAYS, MAX_BNPL_PER_YEAR,
+    PAYMENT_REMINDER_DAYS, PAYMENT_SNOOZE_SECS, MICRO_BNPL_INSTALLMENTS,
+    MICRO_BNPL_INTERVAL_DAYS, MAX_DONATION_BPS, MAX_PROMO_DISCOUNT_BPS,
+    get_card_config,
+};
+use crate::core::config::load_config;
+
+// Snapshot of the protocol returned to integrators via return data
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProtocolStatus {
+    pub version: String,
+    pub whitelist_active: bool,
+    pub whitelist_total_users: u64,
+    pub min_staking_amount: u64,
+    pub min_staking_lock_days: u16,
+    pub max_staking_lock_days: u16,
+    pub staking_reward_apy_bps: u16,
+    pub initial_score: u16,
+    pub max_score: u16,
+    pub nft_mint_cost: u64,
+    pub grace_period_days: u8, // Of the Standard card, see the active parameters for the other cards
+}
+
+pub fn process_get_protocol_status(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let whitelist_account = next_account_info(account_info_iter)?;
+
+    // Verify the whitelist PDA
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if *whitelist_account.key != whitelist_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // An uninitialized whitelist reports as inactive
+    let (whitelist_active, whitelist_total_users) = if whitelist_account.data_is_empty() {
+        (false, 0)
+    } else {
+        let whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
+        (whitelist_data.is_active, whitelist_data.total_users)
+    };
+
+    let status = ProtocolStatus {
+        version: FLEXFI_VERSION.to_string(),
+        whitelist_active,
+        whitelist_total_users,
+        min_staking_amount: MIN_STAKING_AMOUNT,
+        min_staking_lock_days: MIN_STAKING_LOCK_DAYS,
+        max_staking_lock_days: MAX_STAKING_LOCK_DAYS,
+        staking_reward_apy_bps: STAKING_REWARD_APY_BPS,
+        initial_score: INITIAL_SCORE,
+        max_score: MAX_SCORE,
+        nft_mint_cost: NFT_MINT_COST,
+        grace_period_days: get_card_config(CARD_STANDARD).grace_period_days,
+    };
+
+    set_return_data(&status.try_to_vec()?);
+
+    msg!("Protocol status: version {}, whitelist active: {}", status.version, status.whitelist_active);
+    Ok(())
+}
+
+// Terms of one card type as enforced by the program
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CardParameters {
+    pub card_type: u8,
+    pub apr_bps: u16,
+    pub bnpl_fee_bps: u16,
+    pub bnpl_fee_12months_bps: u16,
+    pub max_installments: u8,
+    pub available_installments: [u8; 4],
+    pub cashback_bps: u16,
+    pub cashback_limit: u64,
+    pub nft_cost: u64,
+    pub grace_period_days: u8,
+    pub yield_performance_fee_bps: u16,
+}
+
+// Effective parameters (constants and the live config) returned to wallets via return data
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ActiveParameters {
+    // Fee schedule
+    pub min_bnpl_fee_bps: u16,
+    pub max_bnpl_fee_bps: u16,
+    pub max_merchant_fee_bps: u16,
+    pub emergency_withdraw_penalty_bps: u16,
+    pub cards: Vec<CardParameters>,
+    // Limits
+    pub min_installments: u8,
+    pub max_installments: u8,
+    pub min_payment_interval_days: u8,
+    pub max_payment_interval_days: u8,
+    pub max_bnpl_per_year: u16,
+    pub max_donation_bps: u16,
+    pub max_promo_discount_bps: u16,
+    pub min_staking_amount: u64,
+    // Reminders
+    pub payment_reminder_days: i64,
+    pub payment_snooze_secs: i64,
+    // Governable config
+    pub score_tier_thresholds: [u16; SCORE_TIER_COUNT - 1],
+    pub ltv_bps: [[u16; SCORE_TIER_COUNT]; CARD_TYPE_COUNT],
+    pub treasury_deploy_cap_bps: u16,
+    pub micro_bnpl_threshold: u64,
+    pub micro_bnpl_ltv_bps: u16,
+    pub micro_bnpl_installments: u8,
+    pub micro_bnpl_interval_days: i64,
+    pub staking_reward_apy_bps: u16,
+}
+
+pub fn process_get_active_parameters(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let config_account = next_account_info(account_info_iter)?;
+
+    let config_data = load_config(program_id, config_account)?;
+
+    let cards = (0..CARD_TYPE_COUNT as u8)
+        .map(|card_type| {
+            let card_config = get_card_config(card_type);
+            CardParameters {
+                card_type,
+                apr_bps: card_config.apr_percentage,
+                bnpl_fee_bps: card_config.bnpl_fee_percentage,
+                bnpl_fee_12months_bps: card_config.bnpl_fee_12months,
+                max_installments: card_config.max_installments,
+                available_installments: card_config.available_installments,
+                cashback_bps: card_config.cashback_percentage,
+                cashback_limit: card_config.cashback_limit,
+                nft_cost: card_config.nft_cost,
+                grace_period_days: card_config.grace_period_days,
+                yield_performance_fee_bps: config_data.get_yield_performance_fee_bps(card_type),
+            }
+        })
+        .collect();
+
+    let parameters = ActiveParameters {
+        min_bnpl_fee_bps: MINIMUM_FEE_PERCENTAGE,
+        max_bnpl_fee_bps: MAXIMUM_FEE_PERCENTAGE,
+        max_merchant_fee_bps: MAX_MERCHANT_FEE_BPS,
+        emergency_withdraw_penalty_bps: config_data.emergency_withdraw_penalty_bps,
+        cards,
+        min_installments: MIN_BNPL_INSTALLMENTS,
+        max_installments: MAX_BNPL_INSTALLMENTS,
+        min_payment_interval_days: MIN_PAYMENT_INTERVAL_DAYS,
+        max_payment_interval_days: MAX_PAYMENT_INTERVAL_DAYS,
+        max_bnpl_per_year: MAX_BNPL_PER_YEAR,
+        max_donation_bps: MAX_DONATION_BPS,
+        max_promo_discount_bps: MAX_PROMO_DISCOUNT_BPS,
+        min_staking_amount: MIN_STAKING_AMOUNT,
+        payment_reminder_days: PAYMENT_REMINDER_DAYS,
+        payment_snooze_secs: PAYMENT_SNOOZE_SECS,
+        score_tier_thresholds: config_data.score_tier_thresholds,
+        ltv_bps: config_data.ltv_bps,
+        treasury_deploy_cap_bps: config_data.treasury_deploy_cap_bps,
+        micro_bnpl_threshold: config_data.micro_bnpl_threshold,
+        micro_bnpl_ltv_bps: config_data.micro_bnpl_ltv_bps,
+        micro_bnpl_installments: MICRO_BNPL_INSTALLMENTS,
+        micro_bnpl_interval_days: MICRO_BNPL_INTERVAL_DAYS,
+        staking_reward_apy_bps: config_data.staking_reward_apy_bps,
+    };
+
+    set_return_data(&parameters.try_to_vec()?);
+
+    msg!("Active parameters returned for {} card types", parameters.cards.len());
+    Ok(())
+}
@@ -0,0 +1,398 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::treasury::{TreasuryStreamAccount, TreasuryStateAccount};
+use crate::state::admin::AdminRole;
+use crate::constants::{
+    TREASURY_SEED, TREASURY_STREAM_SEED, TREASURY_STATE_SEED, MAX_INSTANT_TREASURY_WITHDRAWAL,
+    MIN_TREASURY_STREAM_DAYS, MAX_TREASURY_STREAM_DAYS,
+};
+use crate::core::admin::require_admin_role;
+use crate::bnpl::micro::check_treasury_token_account;
+use crate::token::{transfer_checked, check_token_account, get_token_account_amount};
+use crate::error_context::{amount_mismatch, key_mismatch};
+
+// Load a treasury stream owned by the program
+fn load_treasury_stream(
+    program_id: &Pubkey,
+    stream_account: &AccountInfo,
+) -> Result<TreasuryStreamAccount, ProgramError> {
+    if stream_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    TreasuryStreamAccount::try_from_slice(&stream_account.data.borrow())
+        .map_err(|_| ProgramError::UninitializedAccount)
+}
+
+// Load the treasury state of a mint
+fn load_treasury_state(
+    program_id: &Pubkey,
+    state_account: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<TreasuryStateAccount, ProgramError> {
+    let (state_pda, _) = Pubkey::find_program_address(&[TREASURY_STATE_SEED, mint.as_ref()], program_id);
+
+    if *state_account.key != state_pda {
+        return Err(key_mismatch(state_account, &state_pda, ProgramError::InvalidAccountData));
+    }
+
+    if state_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    TreasuryStateAccount::try_from_slice(&state_account.data.borrow())
+        .map_err(|_| ProgramError::UninitializedAccount)
+}
+
+// Load the treasury state of a mint, creating it on the first withdrawal
+fn load_or_create_treasury_state<'a>(
+    program_id: &Pubkey,
+    state_account: &AccountInfo<'a>,
+    mint: &Pubkey,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    current_time: i64,
+) -> Result<TreasuryStateAccount, ProgramError> {
+    if !state_account.data_is_empty() {
+        return load_treasury_state(program_id, state_account, mint);
+    }
+
+    let (state_pda, state_bump) = Pubkey::find_program_address(&[TREASURY_STATE_SEED, mint.as_ref()], program_id);
+
+    if *state_account.key != state_pda {
+        return Err(key_mismatch(state_account, &state_pda, ProgramError::InvalidAccountData));
+    }
+
+    let rent = Rent::get()?;
+    let space = TreasuryStateAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            &state_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[payer.clone(), state_account.clone(), system_program.clone()],
+        &[&[TREASURY_STATE_SEED, mint.as_ref(), &[state_bump]]],
+    )?;
+
+    Ok(TreasuryStateAccount {
+        mint: *mint,
+        window_start: current_time,
+        withdrawn_in_window: 0,
+        reserved_for_streams: 0,
+        bump: state_bump,
+    })
+}
+
+// Withdraw from the treasury (treasurer only): paid at once up to MAX_INSTANT_TREASURY_WITHDRAWAL,
+// within a daily budget, otherwise streamed linearly to the recipient over stream_days so a compromised
+// treasurer can't drain the treasury before the stream is cancelled. Streamed amounts are reserved
+// until claimed, and neither kind of withdrawal can touch them.
+pub fn process_withdraw_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    recipient: Pubkey,
+    amount: u64,
+    stream_id: u64,
+    stream_days: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let treasury_authority = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let recipient_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?; // Unused for an instant withdrawal
+    let treasury_state_account = next_account_info(account_info_iter)?; // Created on the first withdrawal
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Verify the authority holds the treasurer role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Treasurer)?;
+
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let treasury_bump = check_treasury_token_account(program_id, mint.key, token_program.key, treasury_token_account)?;
+    check_token_account(recipient_token_account, &recipient, mint.key)?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let mut state_data = load_or_create_treasury_state(
+        program_id,
+        treasury_state_account,
+        mint.key,
+        authority,
+        system_program,
+        clock.unix_timestamp,
+    )?;
+
+    // Funds owed to open streams can't be promised twice
+    let unreserved = state_data.unreserved_balance(get_token_account_amount(treasury_token_account)?);
+
+    if amount > unreserved {
+        return Err(amount_mismatch(Some(treasury_token_account), unreserved, amount, ProgramError::InsufficientFunds));
+    }
+
+    // Instant withdrawal
+    if stream_days == 0 {
+        if amount > MAX_INSTANT_TREASURY_WITHDRAWAL {
+            return Err(amount_mismatch(None, MAX_INSTANT_TREASURY_WITHDRAWAL, amount, FlexfiError::TreasuryStreamRequired));
+        }
+
+        state_data.roll_window(clock.unix_timestamp);
+        let remaining_budget = state_data.remaining_instant_budget();
+
+        if !state_data.spend_instant(amount, clock.unix_timestamp) {
+            msg!("Only {} left in today's instant withdrawal budget", remaining_budget);
+            return Err(amount_mismatch(Some(treasury_state_account), remaining_budget, amount, FlexfiError::TreasuryStreamRequired));
+        }
+
+        state_data.serialize(&mut *treasury_state_account.data.borrow_mut())?;
+
+        transfer_checked(
+            token_program,
+            treasury_token_account,
+            mint,
+            recipient_token_account,
+            treasury_authority,
+            hook_accounts,
+            amount,
+            &[&[TREASURY_SEED, mint.key.as_ref(), &[treasury_bump]]],
+        )?;
+
+        msg!("Treasury withdrawal of {} paid to {} by {}", amount, recipient, authority.key);
+        return Ok(());
+    }
+
+    if !(MIN_TREASURY_STREAM_DAYS..=MAX_TREASURY_STREAM_DAYS).contains(&stream_days) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify the stream PDA
+    let (stream_pda, stream_bump) = Pubkey::find_program_address(
+        &[TREASURY_STREAM_SEED, mint.key.as_ref(), recipient.as_ref(), &stream_id.to_le_bytes()],
+        program_id
+    );
+
+    if *stream_account.key != stream_pda {
+        return Err(key_mismatch(stream_account, &stream_pda, ProgramError::InvalidAccountData));
+    }
+
+    if !stream_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create the stream account; the funds stay in the treasury until claimed
+    let rent = Rent::get()?;
+    let space = TreasuryStreamAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &stream_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[authority.clone(), stream_account.clone(), system_program.clone()],
+        &[&[TREASURY_STREAM_SEED, mint.key.as_ref(), recipient.as_ref(), &stream_id.to_le_bytes(), &[stream_bump]]],
+    )?;
+
+    let stream_data = TreasuryStreamAccount {
+        mint: *mint.key,
+        recipient,
+        stream_id,
+        total_amount: amount,
+        claimed_amount: 0,
+        start_time: clock.unix_timestamp,
+        end_time: clock.unix_timestamp + stream_days as i64 * 86400,
+        created_by: *authority.key,
+        bump: stream_bump,
+    };
+
+    stream_data.serialize(&mut *stream_account.data.borrow_mut())?;
+
+    state_data.reserved_for_streams = state_data.reserved_for_streams
+        .checked_add(amount)
+        .ok_or(FlexfiError::MathOverflow)?;
+    state_data.serialize(&mut *treasury_state_account.data.borrow_mut())?;
+
+    msg!("Treasury stream {} opened: {} to {} over {} days", stream_id, amount, recipient, stream_days);
+    Ok(())
+}
+
+// Claim the vested part of a treasury stream (recipient only); the finished stream is closed
+// and its rent returned to the treasurer who opened it
+pub fn process_claim_treasury_stream(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let stream_account = next_account_info(account_info_iter)?;
+    let recipient_account = next_account_info(account_info_iter)?;
+    let recipient_token_account = next_account_info(account_info_iter)?;
+    let treasury_authority = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let treasury_state_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let rent_receiver = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Check recipient signature
+    if !recipient_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut stream_data = load_treasury_stream(program_id, stream_account)?;
+
+    if stream_data.recipient != *recipient_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if stream_data.mint != *mint.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if stream_data.created_by != *rent_receiver.key {
+        return Err(key_mismatch(rent_receiver, &stream_data.created_by, ProgramError::InvalidAccountData));
+    }
+
+    let treasury_bump = check_treasury_token_account(program_id, mint.key, token_program.key, treasury_token_account)?;
+    check_token_account(recipient_token_account, recipient_account.key, mint.key)?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let claimable = stream_data.claimable_amount(clock.unix_timestamp);
+
+    if claimable == 0 {
+        msg!("Nothing vested yet");
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    stream_data.claimed_amount = stream_data.claimed_amount.saturating_add(claimable);
+
+    // What is paid out is no longer reserved
+    let mut state_data = load_treasury_state(program_id, treasury_state_account, mint.key)?;
+    state_data.release_stream(claimable);
+    state_data.serialize(&mut *treasury_state_account.data.borrow_mut())?;
+
+    transfer_checked(
+        token_program,
+        treasury_token_account,
+        mint,
+        recipient_token_account,
+        treasury_authority,
+        hook_accounts,
+        claimable,
+        &[&[TREASURY_SEED, mint.key.as_ref(), &[treasury_bump]]],
+    )?;
+
+    if stream_data.is_finished() {
+        let stream_lamports = stream_account.lamports();
+        **rent_receiver.lamports.borrow_mut() = rent_receiver.lamports()
+            .checked_add(stream_lamports)
+            .ok_or(FlexfiError::MathOverflow)?;
+        **stream_account.lamports.borrow_mut() = 0;
+
+        stream_account.data.borrow_mut().fill(0);
+
+        msg!("Treasury stream {} claimed in full and closed", stream_data.stream_id);
+    } else {
+        stream_data.serialize(&mut *stream_account.data.borrow_mut())?;
+    }
+
+    msg!("Treasury stream claim: {} ({} of {})", claimable, stream_data.claimed_amount, stream_data.total_amount);
+    Ok(())
+}
+
+// Stop a treasury stream (pauser only, e.g. when the treasurer key is compromised); the part
+// vested so far stays claimable, the rest is no longer reserved
+pub fn process_cancel_treasury_stream(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let stream_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let treasury_state_account = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the pauser role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Pauser)?;
+
+    let mut stream_data = load_treasury_stream(program_id, stream_account)?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if clock.unix_timestamp >= stream_data.end_time {
+        msg!("Treasury stream already fully vested");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let total_before = stream_data.total_amount;
+    stream_data.cancel(clock.unix_timestamp);
+    stream_data.serialize(&mut *stream_account.data.borrow_mut())?;
+
+    let mut state_data = load_treasury_state(program_id, treasury_state_account, &stream_data.mint)?;
+    state_data.release_stream(total_before - stream_data.total_amount);
+    state_data.serialize(&mut *treasury_state_account.data.borrow_mut())?;
+
+    msg!("Treasury stream {} cancelled by {}: {} vested", stream_data.stream_id, authority.key, stream_data.total_amount);
+    Ok(())
+}
+
+pub struct TreasuryManager;
+
+impl TreasuryManager {
+    pub fn withdraw(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        recipient: Pubkey,
+        amount: u64,
+        stream_id: u64,
+        stream_days: u16,
+    ) -> ProgramResult {
+        process_withdraw_treasury(program_id, accounts, recipient, amount, stream_id, stream_days)
+    }
+
+    pub fn claim_stream(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_claim_treasury_stream(program_id, accounts)
+    }
+
+    pub fn cancel_stream(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_cancel_treasury_stream(program_id, accounts)
+    }
+}
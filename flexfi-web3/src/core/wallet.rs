@@ -0,0 +1,1104 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::wallet::{WalletAccount, BackendIdAccount};
+use crate::state::authorization::AuthorizationAccount;
+use crate::state::recovery::WalletRecoveryAccount;
+use crate::state::card::CardAccount;
+use crate::state::score::ScoreAccount;
+use crate::state::savings::SavingsGoalAccount;
+use crate::state::whitelist::{WhitelistAccount, UserWhitelistStatus, UserLimits};
+use crate::constants::{WALLET_SEED, CARD_STANDARD, AUTHORIZATION_SEED, WALLET_RECOVERY_SEED, BACKEND_ID_SEED, MAX_WALLET_GUARDIANS, WALLET_RECOVERY_TIMELOCK_SECS, WHITELIST_SEED, SCORE_SEED, CARD_SEED};
+use crate::core::whitelist::{require_whitelist_authority, check_user_whitelisted};
+use crate::core::blocklist::require_not_blocklisted;
+use crate::bnpl::registry::load_borrower_registry;
+
+// Load a wallet and check that the signer owns it
+fn load_owned_wallet(
+    program_id: &Pubkey,
+    wallet_account: &AccountInfo,
+    owner_account: &AccountInfo,
+) -> Result<WalletAccount, ProgramError> {
+    if !owner_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if wallet_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    if wallet_data.owner != *owner_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    Ok(wallet_data)
+}
+
+// Create the user's FlexFi wallet with a standard card
+pub fn process_create_wallet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check the wallet PDA
+    let (wallet_pda, wallet_bump) = Pubkey::find_program_address(
+        &[WALLET_SEED, user_account.key.as_ref()],
+        program_id,
+    );
+
+    if *wallet_account.key != wallet_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the wallet account
+    let rent = Rent::get()?;
+    let space = WalletAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            user_account.key,
+            &wallet_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[user_account.clone(), wallet_account.clone(), system_program.clone()],
+        &[&[WALLET_SEED, user_account.key.as_ref(), &[wallet_bump]]],
+    )?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let wallet_data = WalletAccount {
+        owner: *user_account.key,
+        is_active: true,
+        card_type: CARD_STANDARD,
+        created_at: clock.unix_timestamp,
+        backend_id_hash: [0u8; 32],
+        whitelist_cached: false,
+        whitelist_version: 0,
+        whitelist_expires_at: 0,
+        whitelist_limits: UserLimits::default(),
+        erasure_requested_at: 0,
+        erased: false,
+        staking_positions: 0,
+        recovered_from: Pubkey::default(),
+        bump: wallet_bump,
+    };
+
+    wallet_data.serialize(&mut *wallet_account.data.borrow_mut())?;
+
+    msg!("Wallet created for {}", user_account.key);
+    Ok(())
+}
+
+// Deactivate the wallet; BNPL checks reject inactive wallets
+pub fn process_deactivate_wallet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let owner_account = next_account_info(account_info_iter)?;
+
+    let mut wallet_data = load_owned_wallet(program_id, wallet_account, owner_account)?;
+
+    if !wallet_data.is_active {
+        return Err(FlexfiError::WalletInactive.into());
+    }
+
+    wallet_data.is_active = false;
+    wallet_data.serialize(&mut *wallet_account.data.borrow_mut())?;
+
+    msg!("Wallet deactivated");
+    Ok(())
+}
+
+// Reactivate a deactivated wallet
+pub fn process_reactivate_wallet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let owner_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+
+    let mut wallet_data = load_owned_wallet(program_id, wallet_account, owner_account)?;
+
+    if wallet_data.is_active {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    wallet_data.is_active = true;
+    wallet_data.serialize(&mut *wallet_account.data.borrow_mut())?;
+
+    msg!("Wallet reactivated");
+    Ok(())
+}
+
+// Load the recovery settings of a wallet after checking their PDA
+fn load_recovery(
+    program_id: &Pubkey,
+    wallet_account: &AccountInfo,
+    recovery_account: &AccountInfo,
+) -> Result<WalletRecoveryAccount, ProgramError> {
+    let (recovery_pda, _) = Pubkey::find_program_address(
+        &[WALLET_RECOVERY_SEED, wallet_account.key.as_ref()],
+        program_id,
+    );
+
+    if *recovery_account.key != recovery_pda || recovery_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    WalletRecoveryAccount::try_from_slice(&recovery_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)
+}
+
+// Register the guardian keys that can recover the wallet; cancels any recovery in progress
+pub fn process_set_wallet_guardians(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let recovery_account = next_account_info(account_info_iter)?;
+    let owner_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Check owner signature
+    if !owner_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if wallet_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    if wallet_data.owner != *owner_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Validate the guardian set
+    if guardians.len() > MAX_WALLET_GUARDIANS
+        || threshold as usize > guardians.len()
+        || (threshold == 0 && !guardians.is_empty())
+    {
+        return Err(FlexfiError::InvalidGuardians.into());
+    }
+
+    for (i, guardian) in guardians.iter().enumerate() {
+        if *guardian == wallet_data.owner || guardians[..i].contains(guardian) {
+            return Err(FlexfiError::InvalidGuardians.into());
+        }
+    }
+
+    // Check the recovery PDA
+    let (recovery_pda, recovery_bump) = Pubkey::find_program_address(
+        &[WALLET_RECOVERY_SEED, wallet_account.key.as_ref()],
+        program_id,
+    );
+
+    if *recovery_account.key != recovery_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the recovery account on first use
+    if recovery_account.owner != program_id {
+        let rent = Rent::get()?;
+        let space = WalletRecoveryAccount::SIZE;
+        let rent_lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                owner_account.key,
+                &recovery_pda,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[owner_account.clone(), recovery_account.clone(), system_program.clone()],
+            &[&[WALLET_RECOVERY_SEED, wallet_account.key.as_ref(), &[recovery_bump]]],
+        )?;
+    }
+
+    let mut guardian_list = [Pubkey::default(); MAX_WALLET_GUARDIANS];
+    guardian_list[..guardians.len()].copy_from_slice(&guardians);
+
+    let recovery_data = WalletRecoveryAccount {
+        wallet: *wallet_account.key,
+        guardian_count: guardians.len() as u8,
+        guardians: guardian_list,
+        threshold,
+        pending_owner: Pubkey::default(),
+        approvals: 0,
+        recovery_unlocks_at: 0,
+        bump: recovery_bump,
+    };
+
+    recovery_data.serialize(&mut *recovery_account.data.borrow_mut())?;
+
+    msg!("Wallet guardians set: {} of {} required", threshold, guardians.len());
+    Ok(())
+}
+
+// Guardian approval of a new owner; the timelock starts once the threshold is reached
+pub fn process_approve_wallet_recovery(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_owner: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let recovery_account = next_account_info(account_info_iter)?;
+    let guardian_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check guardian signature
+    if !guardian_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if new_owner == Pubkey::default() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut recovery_data = load_recovery(program_id, wallet_account, recovery_account)?;
+
+    let guardian_index = recovery_data.guardian_index(guardian_account.key)
+        .ok_or(FlexfiError::Unauthorized)?;
+
+    // Approving a different owner restarts the recovery
+    if recovery_data.pending_owner != new_owner {
+        recovery_data.clear_recovery();
+        recovery_data.pending_owner = new_owner;
+    }
+
+    recovery_data.approvals |= 1 << guardian_index;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if recovery_data.recovery_unlocks_at == 0
+        && recovery_data.approval_count() >= recovery_data.threshold
+    {
+        recovery_data.recovery_unlocks_at = clock.unix_timestamp + WALLET_RECOVERY_TIMELOCK_SECS;
+        msg!("Wallet recovery to {} unlocks at {}", new_owner, recovery_data.recovery_unlocks_at);
+    }
+
+    recovery_data.serialize(&mut *recovery_account.data.borrow_mut())?;
+
+    msg!("Wallet recovery approved by guardian {} ({}/{})",
+        guardian_account.key, recovery_data.approval_count(), recovery_data.threshold);
+    Ok(())
+}
+
+// Let the current owner cancel a recovery during the timelock
+pub fn process_cancel_wallet_recovery(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let recovery_account = next_account_info(account_info_iter)?;
+    let owner_account = next_account_info(account_info_iter)?;
+
+    // Check owner signature
+    if !owner_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if wallet_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    if wallet_data.owner != *owner_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut recovery_data = load_recovery(program_id, wallet_account, recovery_account)?;
+
+    recovery_data.clear_recovery();
+    recovery_data.serialize(&mut *recovery_account.data.borrow_mut())?;
+
+    msg!("Wallet recovery cancelled by owner");
+    Ok(())
+}
+
+// Refund a program account's rent to the recipient and zero its data
+fn close_program_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+    **recipient.lamports.borrow_mut() = recipient.lamports()
+        .checked_add(lamports)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **account.lamports.borrow_mut() = 0;
+
+    account.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+// Create the PDA an account of the previous owner moves to, paid by the new owner; returns its bump
+fn create_migrated_account<'a>(
+    program_id: &Pubkey,
+    new_account: &AccountInfo<'a>,
+    new_owner_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+    space: usize,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
+
+    if *new_account.key != pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !new_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::get()?;
+    let bump_seed = [bump];
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(&bump_seed);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            new_owner_account.key,
+            &pda,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[new_owner_account.clone(), new_account.clone(), system_program.clone()],
+        &[&signer_seeds],
+    )?;
+
+    Ok(bump)
+}
+
+// Load the wallet the signer recovered, whose previous owner's accounts can be migrated
+pub(crate) fn load_recovered_wallet(
+    program_id: &Pubkey,
+    wallet_account: &AccountInfo,
+    new_owner_account: &AccountInfo,
+) -> Result<WalletAccount, ProgramError> {
+    let wallet_data = load_owned_wallet(program_id, wallet_account, new_owner_account)?;
+
+    let (wallet_pda, _) = Pubkey::find_program_address(
+        &[WALLET_SEED, new_owner_account.key.as_ref()],
+        program_id,
+    );
+
+    if *wallet_account.key != wallet_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !wallet_data.is_recovered() {
+        msg!("Wallet was not recovered");
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    Ok(wallet_data)
+}
+
+// Move the wallet to the new owner once the recovery timelock has passed. Every PDA of the protocol is
+// derived from the owner's key, so the wallet and its guardians move to the new owner's PDAs; the
+// staking positions, score and card follow with MigrateRecoveredStaking and MigrateRecoveredProfile.
+// Loans stay with the key that borrowed them, so recovery waits until the previous owner's micro loans
+// are repaid (a registered device key can still repay them). The whitelist status isn't moved either: the new key goes through KYC again before it
+// can use guarded instructions.
+pub fn process_recover_wallet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let recovery_account = next_account_info(account_info_iter)?;
+    let new_wallet_account = next_account_info(account_info_iter)?;
+    let new_recovery_account = next_account_info(account_info_iter)?;
+    let new_owner_account = next_account_info(account_info_iter)?; // Pays for the new accounts
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?; // The previous owner's borrower registry, even if never created
+
+    if wallet_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let recovery_data = load_recovery(program_id, wallet_account, recovery_account)?;
+
+    if !recovery_data.has_pending_recovery() || recovery_data.recovery_unlocks_at == 0 {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if !new_owner_account.is_signer || *new_owner_account.key != recovery_data.pending_owner {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if clock.unix_timestamp < recovery_data.recovery_unlocks_at {
+        msg!("Wallet recovery unlocks at {}", recovery_data.recovery_unlocks_at);
+        return Err(FlexfiError::RecoveryTimelockActive.into());
+    }
+
+    let mut wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+    let previous_owner = wallet_data.owner;
+
+    if let Some(registry_data) = load_borrower_registry(program_id, &previous_owner, registry_account)? {
+        if registry_data.contract_count > 0 || registry_data.outstanding_debt > 0 {
+            msg!("{} loans are still open, {} owed", registry_data.contract_count, registry_data.outstanding_debt);
+            return Err(FlexfiError::WalletHasOpenPositions.into());
+        }
+    }
+
+    // Move the wallet to the new owner's PDA
+    let wallet_bump = create_migrated_account(
+        program_id,
+        new_wallet_account,
+        new_owner_account,
+        system_program,
+        &[WALLET_SEED, new_owner_account.key.as_ref()],
+        WalletAccount::SIZE,
+    )?;
+
+    wallet_data.owner = *new_owner_account.key;
+    wallet_data.recovered_from = previous_owner;
+    wallet_data.bump = wallet_bump;
+    wallet_data.clear_whitelist_cache();
+    wallet_data.serialize(&mut *new_wallet_account.data.borrow_mut())?;
+
+    // Keep the guardians, less the new owner, for the new wallet
+    let recovery_bump = create_migrated_account(
+        program_id,
+        new_recovery_account,
+        new_owner_account,
+        system_program,
+        &[WALLET_RECOVERY_SEED, new_wallet_account.key.as_ref()],
+        WalletRecoveryAccount::SIZE,
+    )?;
+
+    let mut guardians = [Pubkey::default(); MAX_WALLET_GUARDIANS];
+    let mut guardian_count = 0;
+
+    for guardian in recovery_data.guardians[..recovery_data.guardian_count as usize].iter() {
+        if guardian != new_owner_account.key {
+            guardians[guardian_count] = *guardian;
+            guardian_count += 1;
+        }
+    }
+
+    let new_recovery_data = WalletRecoveryAccount {
+        wallet: *new_wallet_account.key,
+        guardian_count: guardian_count as u8,
+        guardians,
+        threshold: std::cmp::min(recovery_data.threshold, guardian_count as u8),
+        pending_owner: Pubkey::default(),
+        approvals: 0,
+        recovery_unlocks_at: 0,
+        bump: recovery_bump,
+    };
+
+    new_recovery_data.serialize(&mut *new_recovery_account.data.borrow_mut())?;
+
+    // The previous wallet and its recovery settings are closed, their rent going to the new owner
+    close_program_account(wallet_account, new_owner_account)?;
+    close_program_account(recovery_account, new_owner_account)?;
+
+    msg!("Wallet recovered from {} to {}", previous_owner, new_owner_account.key);
+    Ok(())
+}
+
+// Move the score and card of the recovered wallet's previous owner to the new owner, and close the
+// previous owner's FlexFi authorization: it draws on the previous owner's token delegation, so the new
+// owner initializes their own. Accounts the previous owner never created are skipped.
+pub fn process_migrate_recovered_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let new_owner_account = next_account_info(account_info_iter)?;
+    let score_account = next_account_info(account_info_iter)?;
+    let new_score_account = next_account_info(account_info_iter)?;
+    let card_account = next_account_info(account_info_iter)?;
+    let new_card_account = next_account_info(account_info_iter)?;
+    let authorization_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let wallet_data = load_recovered_wallet(program_id, wallet_account, new_owner_account)?;
+    let previous_owner = wallet_data.recovered_from;
+
+    // Score
+    let (score_pda, _) = Pubkey::find_program_address(&[SCORE_SEED, previous_owner.as_ref()], program_id);
+
+    if *score_account.key != score_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if score_account.owner == program_id {
+        let mut score_data = ScoreAccount::try_from_slice(&score_account.data.borrow())?;
+
+        score_data.bump = create_migrated_account(
+            program_id,
+            new_score_account,
+            new_owner_account,
+            system_program,
+            &[SCORE_SEED, new_owner_account.key.as_ref()],
+            ScoreAccount::SIZE,
+        )?;
+        score_data.owner = *new_owner_account.key;
+        score_data.serialize(&mut *new_score_account.data.borrow_mut())?;
+
+        close_program_account(score_account, new_owner_account)?;
+        msg!("Score {} moved to {}", score_data.score, new_score_account.key);
+    }
+
+    // Card
+    let (card_pda, _) = Pubkey::find_program_address(&[CARD_SEED, previous_owner.as_ref()], program_id);
+
+    if *card_account.key != card_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if card_account.owner == program_id {
+        let mut card_data = CardAccount::try_from_slice(&card_account.data.borrow())?;
+
+        card_data.bump = create_migrated_account(
+            program_id,
+            new_card_account,
+            new_owner_account,
+            system_program,
+            &[CARD_SEED, new_owner_account.key.as_ref()],
+            CardAccount::SIZE,
+        )?;
+        card_data.owner = *new_owner_account.key;
+        card_data.serialize(&mut *new_card_account.data.borrow_mut())?;
+
+        close_program_account(card_account, new_owner_account)?;
+        msg!("Card moved to {}", new_card_account.key);
+    }
+
+    // FlexFi authorization
+    let (authorization_pda, _) = Pubkey::find_program_address(
+        &[AUTHORIZATION_SEED, previous_owner.as_ref()],
+        program_id,
+    );
+
+    if *authorization_account.key != authorization_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if authorization_account.owner == program_id {
+        close_program_account(authorization_account, new_owner_account)?;
+        msg!("Authorization of {} closed", previous_owner);
+    }
+
+    Ok(())
+}
+
+// Link the wallet to a hashed off-chain customer id (signed by the user and the backend)
+pub fn process_link_backend_id(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    backend_id_hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let backend_id_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let backend_authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Verify the backend authority
+    require_whitelist_authority(program_id, whitelist_account, backend_authority)?;
+
+    if backend_id_hash == [0u8; 32] {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if wallet_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    if wallet_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if wallet_data.has_backend_id() {
+        return Err(FlexfiError::BackendIdAlreadyLinked.into());
+    }
+
+    // Check the backend id PDA; creating it fails if the id is linked elsewhere
+    let (backend_id_pda, backend_id_bump) = Pubkey::find_program_address(
+        &[BACKEND_ID_SEED, &backend_id_hash],
+        program_id,
+    );
+
+    if *backend_id_account.key != backend_id_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if backend_id_account.owner == program_id {
+        return Err(FlexfiError::BackendIdAlreadyLinked.into());
+    }
+
+    let rent = Rent::get()?;
+    let space = BackendIdAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            backend_authority.key,
+            &backend_id_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[backend_authority.clone(), backend_id_account.clone(), system_program.clone()],
+        &[&[BACKEND_ID_SEED, &backend_id_hash, &[backend_id_bump]]],
+    )?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let backend_id_data = BackendIdAccount {
+        wallet: *wallet_account.key,
+        owner: *user_account.key,
+        linked_at: clock.unix_timestamp,
+        bump: backend_id_bump,
+    };
+
+    backend_id_data.serialize(&mut *backend_id_account.data.borrow_mut())?;
+
+    wallet_data.backend_id_hash = backend_id_hash;
+    wallet_data.serialize(&mut *wallet_account.data.borrow_mut())?;
+
+    msg!("Backend id linked to wallet {}", wallet_account.key);
+    Ok(())
+}
+
+// Count a staking account opened or closed by the owner on their wallet, which must exist
+pub(crate) fn update_staking_positions(
+    program_id: &Pubkey,
+    wallet_account: &AccountInfo,
+    owner: &Pubkey,
+    opened: bool,
+) -> ProgramResult {
+    let (wallet_pda, _) = Pubkey::find_program_address(&[WALLET_SEED, owner.as_ref()], program_id);
+
+    if *wallet_account.key != wallet_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if wallet_account.owner != program_id {
+        msg!("Create a wallet before opening a staking position");
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let mut wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    wallet_data.staking_positions = if opened {
+        wallet_data.staking_positions.checked_add(1).ok_or(FlexfiError::MathOverflow)?
+    } else {
+        wallet_data.staking_positions.saturating_sub(1)
+    };
+
+    wallet_data.serialize(&mut *wallet_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// Close a wallet with nothing left open and refund its rent to the owner
+pub fn process_close_wallet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let owner_account = next_account_info(account_info_iter)?;
+    let authorization_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let registry_account = next_account_info(account_info_iter)?; // The owner's borrower registry, even if never created
+
+    // Check owner signature
+    if !owner_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if wallet_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    if wallet_data.owner != *owner_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    // The FlexFi authorization must be revoked or expired
+    let (authorization_pda, _) = Pubkey::find_program_address(
+        &[AUTHORIZATION_SEED, owner_account.key.as_ref()],
+        program_id,
+    );
+
+    if *authorization_account.key != authorization_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if authorization_account.owner == program_id {
+        let authorization = AuthorizationAccount::try_from_slice(&authorization_account.data.borrow())?;
+
+        if authorization.is_valid(clock.unix_timestamp) {
+            msg!("Revoke the FlexFi authorization before closing the wallet");
+            return Err(FlexfiError::WalletHasOpenPositions.into());
+        }
+    }
+
+    // Every staking account must be closed and every loan repaid
+    if wallet_data.staking_positions > 0 {
+        msg!("{} staking positions are still open", wallet_data.staking_positions);
+        return Err(FlexfiError::WalletHasOpenPositions.into());
+    }
+
+    if let Some(registry_data) = load_borrower_registry(program_id, owner_account.key, registry_account)? {
+        if registry_data.contract_count > 0 || registry_data.outstanding_debt > 0 {
+            msg!("{} loans are still open, {} owed", registry_data.contract_count, registry_data.outstanding_debt);
+            return Err(FlexfiError::WalletHasOpenPositions.into());
+        }
+    }
+
+    // Refund the rent to the owner
+    let wallet_lamports = wallet_account.lamports();
+    **owner_account.lamports.borrow_mut() = owner_account.lamports()
+        .checked_add(wallet_lamports)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **wallet_account.lamports.borrow_mut() = 0;
+
+    // Zero the account data
+    wallet_account.data.borrow_mut().fill(0);
+
+    msg!("Wallet closed, {} lamports refunded", wallet_lamports);
+    Ok(())
+}
+
+// Copy the owner's whitelist approval onto the wallet at the current whitelist version.
+// Anyone may refresh a wallet: the cache only ever reflects the user status and the blocklist.
+pub fn process_refresh_whitelist_cache(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let user_status_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let _blocklist_account = next_account_info(account_info_iter)?; // Found by require_not_blocklisted
+
+    if wallet_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    let (wallet_pda, _) = Pubkey::find_program_address(
+        &[WALLET_SEED, wallet_data.owner.as_ref()],
+        program_id
+    );
+
+    if *wallet_account.key != wallet_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if *whitelist_account.key != whitelist_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
+
+    // The status must be the owner's own, not one the owner is a device key of
+    let is_whitelisted = check_user_whitelisted(program_id, &wallet_data.owner, std::slice::from_ref(user_status_account))?
+        && UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())?.user_pubkey == wallet_data.owner;
+
+    let is_blocked = match require_not_blocklisted(program_id, &wallet_data.owner, accounts) {
+        Ok(()) => false,
+        Err(error) if error == FlexfiError::UserBlocklisted.into() => true,
+        Err(error) => return Err(error),
+    };
+
+    wallet_data.whitelist_cached = is_whitelisted && !is_blocked;
+    wallet_data.whitelist_version = whitelist_data.version;
+    if wallet_data.whitelist_cached {
+        let user_status = UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())?;
+        wallet_data.whitelist_expires_at = user_status.expires_at;
+        wallet_data.whitelist_limits = user_status.limits();
+    } else {
+        wallet_data.whitelist_expires_at = 0;
+        wallet_data.whitelist_limits = UserLimits::default();
+    }
+    wallet_data.serialize(&mut *wallet_account.data.borrow_mut())?;
+
+    msg!("Whitelist cache of {} refreshed at version {}: {}", wallet_data.owner, wallet_data.whitelist_version, wallet_data.whitelist_cached);
+    Ok(())
+}
+
+// Ask for the erasure of the wallet's off-chain identifiers (GDPR); loans and balances are untouched
+pub fn process_request_data_erasure(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let owner_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    let mut wallet_data = load_owned_wallet(program_id, wallet_account, owner_account)?;
+
+    if wallet_data.erasure_requested_at != 0 {
+        msg!("Data erasure already requested at {}", wallet_data.erasure_requested_at);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    wallet_data.erasure_requested_at = clock.unix_timestamp;
+    wallet_data.serialize(&mut *wallet_account.data.borrow_mut())?;
+
+    msg!("Data erasure requested for wallet {}", wallet_account.key);
+    Ok(())
+}
+
+// The backend authority acknowledges an erasure request once its own records are deleted, and the
+// on-chain traces go with them: the backend id binding, the card verification commitment and the
+// savings goal names. Obligations (stakes, loans, scores) are kept.
+pub fn process_acknowledge_data_erasure(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet_account = next_account_info(account_info_iter)?;
+    let backend_id_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let backend_authority = next_account_info(account_info_iter)?;
+    let auxiliary_accounts = account_info_iter.as_slice(); // The owner's card and savings goals
+
+    // Verify the backend authority
+    require_whitelist_authority(program_id, whitelist_account, backend_authority)?;
+
+    if wallet_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    if wallet_data.erasure_requested_at == 0 || wallet_data.erased {
+        msg!("No pending data erasure for wallet {}", wallet_account.key);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Unlink the backend id and close its reverse lookup, refunding the backend that paid for it
+    if wallet_data.has_backend_id() {
+        let (backend_id_pda, _) = Pubkey::find_program_address(
+            &[BACKEND_ID_SEED, &wallet_data.backend_id_hash],
+            program_id,
+        );
+
+        if *backend_id_account.key != backend_id_pda {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if backend_id_account.owner == program_id {
+            let backend_id_lamports = backend_id_account.lamports();
+            **backend_authority.lamports.borrow_mut() = backend_authority.lamports()
+                .checked_add(backend_id_lamports)
+                .ok_or(FlexfiError::MathOverflow)?;
+            **backend_id_account.lamports.borrow_mut() = 0;
+
+            backend_id_account.data.borrow_mut().fill(0);
+        }
+
+        wallet_data.backend_id_hash = [0u8; 32];
+    }
+
+    // Tombstone the commitments kept in the owner's other accounts
+    for auxiliary_account in auxiliary_accounts {
+        if auxiliary_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        match auxiliary_account.data_len() {
+            CardAccount::SIZE => {
+                let mut card_data = CardAccount::try_from_slice(&auxiliary_account.data.borrow())?;
+
+                if card_data.owner != wallet_data.owner {
+                    return Err(FlexfiError::Unauthorized.into());
+                }
+
+                card_data.verification_key = Pubkey::default();
+                card_data.serialize(&mut *auxiliary_account.data.borrow_mut())?;
+            },
+            SavingsGoalAccount::SIZE => {
+                let mut goal_data = SavingsGoalAccount::try_from_slice(&auxiliary_account.data.borrow())?;
+
+                if goal_data.owner != wallet_data.owner {
+                    return Err(FlexfiError::Unauthorized.into());
+                }
+
+                goal_data.name_hash = [0; 32];
+                goal_data.serialize(&mut *auxiliary_account.data.borrow_mut())?;
+            },
+            _ => return Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    wallet_data.erased = true;
+    wallet_data.serialize(&mut *wallet_account.data.borrow_mut())?;
+
+    msg!("Data erasure of wallet {} acknowledged by {}, {} accounts tombstoned",
+        wallet_account.key, backend_authority.key, auxiliary_accounts.len());
+    Ok(())
+}
+
+pub struct WalletManager;
+
+impl WalletManager {
+    pub fn create(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_create_wallet(program_id, accounts)
+    }
+
+    pub fn deactivate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_deactivate_wallet(program_id, accounts)
+    }
+
+    pub fn reactivate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_reactivate_wallet(program_id, accounts)
+    }
+
+    pub fn set_guardians(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+    ) -> ProgramResult {
+        process_set_wallet_guardians(program_id, accounts, guardians, threshold)
+    }
+
+    pub fn approve_recovery(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_owner: Pubkey,
+    ) -> ProgramResult {
+        process_approve_wallet_recovery(program_id, accounts, new_owner)
+    }
+
+    pub fn cancel_recovery(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_cancel_wallet_recovery(program_id, accounts)
+    }
+
+    pub fn recover(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_recover_wallet(program_id, accounts)
+    }
+
+    pub fn link_backend_id(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        backend_id_hash: [u8; 32],
+    ) -> ProgramResult {
+        process_link_backend_id(program_id, accounts, backend_id_hash)
+    }
+
+    pub fn close(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_close_wallet(program_id, accounts)
+    }
+
+    pub fn refresh_whitelist_cache(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_refresh_whitelist_cache(program_id, accounts)
+    }
+
+    pub fn request_data_erasure(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_request_data_erasure(program_id, accounts)
+    }
+
+    pub fn acknowledge_data_erasure(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_acknowledge_data_erasure(program_id, accounts)
+    }
+}
@@ -5,14 +5,70 @@ use solana_program::{
     program::invoke_signed,
     pubkey::Pubkey,
     system_instruction,
-    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    sysvar::{
+        clock::Clock,
+        Sysvar,
+        rent::Rent,
+        instructions::{self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked},
+    },
+    ed25519_program,
+    log::sol_log_data,
     msg,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::error::FlexfiError;
-use crate::state::whitelist::{WhitelistAccount, UserWhitelistStatus};
-use crate::constants::{WHITELIST_SEED};
+use crate::state::whitelist::{WhitelistAccount, UserWhitelistStatus, UserLimits};
+use crate::state::wallet::WalletAccount;
+use crate::state::admin::AdminRole;
+use crate::constants::{WHITELIST_SEED, WHITELIST_VALIDITY_DAYS, MAX_AUTHORIZED_KEYS, WHITELIST_ATTESTATION_LEN, WALLET_SEED};
+use crate::core::admin::require_admin_role;
+use crate::error_context::{account_error, key_mismatch};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
+pub enum WhitelistChange {
+    Added,
+    Removed,
+    Renewed,
+    RiskLimitsChanged,
+    KeyAdded,
+    KeyRemoved,
+    Activated,
+    Deactivated,
+    AttestationSignerChanged,
+    Blocklisted,
+    Unblocklisted,
+    RegionChanged,
+}
+
+// Emitted by every whitelist instruction so indexers don't have to diff account state.
+// `user` is the affected user (the new signer for AttestationSignerChanged, default for global switches).
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct WhitelistChanged {
+    pub change: WhitelistChange,
+    pub user: Pubkey,
+    pub authority: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+pub(crate) fn emit_whitelist_change(
+    change: WhitelistChange,
+    user: Pubkey,
+    authority: Pubkey,
+    expires_at: i64,
+) -> ProgramResult {
+    let event = WhitelistChanged {
+        change,
+        user,
+        authority,
+        expires_at,
+        timestamp: Clock::get()?.unix_timestamp,
+    };
+
+    sol_log_data(&[b"WhitelistChanged", &event.try_to_vec()?]);
+    Ok(())
+}
 
 pub fn check_user_whitelisted(
     program_id: &Pubkey,
@@ -23,9 +79,21 @@ pub fn check_user_whitelisted(
     let account_info_iter = &mut accounts.iter();
     let user_status_account = next_account_info(account_info_iter)?;
 
+    // If the account doesn't exist, the user is not whitelisted
+    if user_status_account.owner != program_id || user_status_account.data_is_empty() {
+        return Ok(false);
+    }
+
+    // Load the status; a device key passes the status of the user it acts for
+    let user_status = UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())?;
+
+    if !user_status.is_authorized_key(user_pubkey) {
+        return Ok(false);
+    }
+
     // Check the PDA
     let (user_status_pda, _) = Pubkey::find_program_address(
-        &[WHITELIST_SEED, user_pubkey.as_ref()],
+        &[WHITELIST_SEED, user_status.user_pubkey.as_ref()],
         program_id
     );
 
@@ -33,32 +101,247 @@ pub fn check_user_whitelisted(
         return Ok(false);
     }
 
-    // If the account doesn't exist, the user is not whitelisted
-    if user_status_account.data_is_empty() {
+    // An expired entry no longer counts
+    let clock = Clock::get()?;
+
+    Ok(user_status.is_valid(clock.unix_timestamp))
+}
+
+// Read the message of an ed25519 program instruction holding one signature whose key, signature and
+// message all sit in that instruction, when the key is the expected signer
+pub(crate) fn parse_ed25519_message<'a>(data: &'a [u8], signer: &Pubkey) -> Option<&'a [u8]> {
+    let read_u16 = |offset: usize| data.get(offset..offset + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]));
+
+    if data.first() != Some(&1) {
+        return None;
+    }
+
+    // Offsets record: signature, key and message, each with the index of the instruction holding it
+    if read_u16(4)? != u16::MAX || read_u16(8)? != u16::MAX || read_u16(14)? != u16::MAX {
+        return None;
+    }
+
+    let public_key_offset = read_u16(6)? as usize;
+    let message_offset = read_u16(10)? as usize;
+    let message_size = read_u16(12)? as usize;
+
+    if data.get(public_key_offset..public_key_offset + 32)? != signer.as_ref() {
+        return None;
+    }
+
+    data.get(message_offset..message_offset + message_size)
+}
+
+// Read a backend attestation from an ed25519 program instruction signed by the attestation signer
+fn parse_whitelist_attestation(
+    data: &[u8],
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    attestation_signer: &Pubkey,
+) -> Option<i64> {
+    let message = parse_ed25519_message(data, attestation_signer)?;
+
+    if message.len() != WHITELIST_ATTESTATION_LEN {
+        return None;
+    }
+
+    if &message[..32] != program_id.as_ref() || &message[32..64] != user_pubkey.as_ref() {
+        return None;
+    }
+
+    Some(i64::from_le_bytes(message[64..72].try_into().ok()?))
+}
+
+// Check for an unexpired backend attestation of the user among the transaction's earlier instructions.
+// The ed25519 program has already verified the signature when this runs.
+pub fn check_whitelist_attestation(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    instructions_account: &AccountInfo,
+    attestation_signer: &Pubkey,
+) -> Result<bool, ProgramError> {
+    if *attestation_signer == Pubkey::default() {
         return Ok(false);
     }
 
-    // Load and check the status
+    let current_index = load_current_index_checked(instructions_account)?;
+    let clock = Clock::get()?;
+
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_account)?;
+
+        if instruction.program_id != ed25519_program::id() {
+            continue;
+        }
+
+        if let Some(expires_at) = parse_whitelist_attestation(&instruction.data, program_id, user_pubkey, attestation_signer) {
+            if clock.unix_timestamp < expires_at {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+// A wallet passed in place of the user status selects the cached mode
+pub fn is_wallet_account(program_id: &Pubkey, account: &AccountInfo) -> bool {
+    account.owner == program_id && account.data_len() == WalletAccount::SIZE
+}
+
+// The user a signer acts for: the user whose status registers it as a device key, or the signer itself.
+// Whitelisting is checked by the processor guard; handlers derive the user's PDAs and check ownership
+// against the result.
+pub fn resolve_acting_user(
+    program_id: &Pubkey,
+    user_account: &AccountInfo,
+    user_status_account: &AccountInfo,
+) -> Result<Pubkey, ProgramError> {
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Attestations and wallet caches only ever serve the user itself
+    if user_status_account.owner != program_id || is_wallet_account(program_id, user_status_account) {
+        return Ok(*user_account.key);
+    }
+
     let user_status = UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())?;
 
-    Ok(user_status.is_whitelisted)
+    if user_status.user_pubkey == *user_account.key {
+        return Ok(*user_account.key);
+    }
+
+    let (user_status_pda, _) = Pubkey::find_program_address(
+        &[WHITELIST_SEED, user_status.user_pubkey.as_ref()],
+        program_id
+    );
+
+    if user_status_account.key != &user_status_pda {
+        return Err(key_mismatch(user_status_account, &user_status_pda, ProgramError::InvalidAccountData));
+    }
+
+    if !user_status.is_authorized_key(user_account.key) {
+        return Err(account_error(user_account, FlexfiError::Unauthorized));
+    }
+
+    Ok(user_status.user_pubkey)
+}
+
+// Region and risk limits of the user behind the user status slot, read from the copy on the wallet in the
+// cached mode; users whitelisted by attestation have no status PDA, hence no limits
+pub fn load_user_limits(
+    program_id: &Pubkey,
+    user_status_account: &AccountInfo,
+) -> Result<Option<UserLimits>, ProgramError> {
+    if is_wallet_account(program_id, user_status_account) {
+        let wallet_data = WalletAccount::try_from_slice(&user_status_account.data.borrow())?;
+        Ok(Some(wallet_data.whitelist_limits))
+    } else if user_status_account.owner == program_id {
+        let user_status = UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())?;
+        Ok(Some(user_status.limits()))
+    } else {
+        Ok(None)
+    }
+}
+
+// Check the approval cached on the user's own wallet; a cache older than the whitelist version is
+// rejected so the client refreshes it, as a revocation may have happened since
+pub fn check_wallet_whitelist_cache(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    wallet_account: &AccountInfo,
+    whitelist_version: u64,
+) -> Result<bool, ProgramError> {
+    let wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+
+    // Only the owner benefits from the cache, device keys go through the user status
+    if wallet_data.owner != *user_pubkey {
+        return Ok(false);
+    }
+
+    let (wallet_pda, _) = Pubkey::find_program_address(
+        &[WALLET_SEED, user_pubkey.as_ref()],
+        program_id
+    );
+
+    if wallet_account.key != &wallet_pda {
+        return Ok(false);
+    }
+
+    if wallet_data.whitelist_version != whitelist_version {
+        msg!("Whitelist cache of {} is stale (version {}, current {})", user_pubkey, wallet_data.whitelist_version, whitelist_version);
+        return Err(account_error(wallet_account, FlexfiError::WhitelistCacheStale));
+    }
+
+    let clock = Clock::get()?;
+
+    Ok(wallet_data.whitelist_cached && clock.unix_timestamp < wallet_data.whitelist_expires_at)
 }
 
-// Helper function that generates an error if the user is not whitelisted
+// Helper function that generates an error if the user is not whitelisted,
+// or if the global whitelist is switched off.
+// Passing the instructions sysvar instead of the user status selects the attestation mode,
+// and passing the user's wallet selects the cached mode.
 pub fn require_whitelisted(
     program_id: &Pubkey,
     user_pubkey: &Pubkey,
     user_status_account: &AccountInfo,
+    whitelist_account: &AccountInfo,
 ) -> ProgramResult {
-    let is_whitelisted = check_user_whitelisted(
-        program_id,
-        user_pubkey,
-        &[user_status_account.clone()]
-    )?;
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if whitelist_account.key != &whitelist_pda {
+        return Err(key_mismatch(whitelist_account, &whitelist_pda, ProgramError::InvalidAccountData));
+    }
+
+    let whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
+
+    if !whitelist_data.is_active {
+        msg!("Whitelist-gated functions are paused");
+        return Err(account_error(whitelist_account, FlexfiError::ProtocolPaused));
+    }
+
+    let is_whitelisted = if instructions_sysvar::check_id(user_status_account.key) {
+        check_whitelist_attestation(program_id, user_pubkey, user_status_account, &whitelist_data.attestation_signer)?
+    } else if is_wallet_account(program_id, user_status_account) {
+        check_wallet_whitelist_cache(program_id, user_pubkey, user_status_account, whitelist_data.version)?
+    } else {
+        check_user_whitelisted(
+            program_id,
+            user_pubkey,
+            std::slice::from_ref(user_status_account)
+        )?
+    };
 
     if !is_whitelisted {
-        msg!("User {} is not whitelisted and cannot use this function", user_pubkey);
-        return Err(FlexfiError::Unauthorized.into());
+        msg!("User {} is not whitelisted (or the approval expired) and cannot use this function", user_pubkey);
+        return Err(account_error(user_status_account, FlexfiError::Unauthorized));
+    }
+
+    Ok(())
+}
+
+// Helper function that generates an error if the signer is not the whitelist authority
+pub fn require_whitelist_authority(
+    program_id: &Pubkey,
+    whitelist_account: &AccountInfo,
+    authority: &AccountInfo,
+) -> ProgramResult {
+    if !authority.is_signer {
+        return Err(account_error(authority, FlexfiError::Unauthorized));
+    }
+
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if whitelist_account.key != &whitelist_pda {
+        return Err(key_mismatch(whitelist_account, &whitelist_pda, ProgramError::InvalidAccountData));
+    }
+
+    let whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
+
+    if whitelist_data.authority != *authority.key {
+        return Err(key_mismatch(authority, &whitelist_data.authority, FlexfiError::Unauthorized));
     }
 
     Ok(())
@@ -112,6 +395,8 @@ pub fn process_initialize_whitelist(
         authority: *authority.key,
         is_active: true,
         total_users: 0,
+        attestation_signer: Pubkey::default(),
+        version: 0,
         bump,
     };
 
@@ -131,23 +416,23 @@ pub fn process_add_to_whitelist(
 
     let whitelist_account = next_account_info(account_info_iter)?;
     let user_status_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
     let authority = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
 
-    // Verify the authority
-    if !authority.is_signer {
-        return Err(FlexfiError::Unauthorized.into());
+    // Verify the authority holds the whitelist manager role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::WhitelistManager)?;
+
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if whitelist_account.key != &whitelist_pda {
+        return Err(ProgramError::InvalidAccountData);
     }
 
     // Load the whitelist
     let mut whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
 
-    // Verify that the authority is correct
-    if whitelist_data.authority != *authority.key {
-        return Err(FlexfiError::Unauthorized.into());
-    }
-
     // Create the PDA for the user's status
     let (user_status_pda, user_bump) = Pubkey::find_program_address(
         &[WHITELIST_SEED, user_pubkey.as_ref()],
@@ -184,6 +469,11 @@ pub fn process_add_to_whitelist(
         is_whitelisted: true,
         whitelisted_at: clock.unix_timestamp,
         whitelisted_by: *authority.key,
+        expires_at: clock.unix_timestamp + (WHITELIST_VALIDITY_DAYS * 86400),
+        authorized_keys: [Pubkey::default(); MAX_AUTHORIZED_KEYS],
+        max_bnpl_amount: 0,
+        max_active_contracts: 0,
+        region: 0,
         bump: user_bump,
     };
 
@@ -193,6 +483,8 @@ pub fn process_add_to_whitelist(
     whitelist_data.total_users += 1;
     whitelist_data.serialize(&mut *whitelist_account.data.borrow_mut())?;
 
+    emit_whitelist_change(WhitelistChange::Added, user_pubkey, *authority.key, user_status.expires_at)?;
+
     msg!("User {} added to whitelist", user_pubkey);
     Ok(())
 }
@@ -206,21 +498,21 @@ pub fn process_remove_from_whitelist(
 
     let whitelist_account = next_account_info(account_info_iter)?;
     let user_status_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
     let authority = next_account_info(account_info_iter)?;
 
-    // Verify the authority
-    if !authority.is_signer {
-        return Err(FlexfiError::Unauthorized.into());
+    // Verify the authority holds the whitelist manager role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::WhitelistManager)?;
+
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if whitelist_account.key != &whitelist_pda {
+        return Err(ProgramError::InvalidAccountData);
     }
 
     // Load the whitelist
     let mut whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
 
-    // Verify that the authority is correct
-    if whitelist_data.authority != *authority.key {
-        return Err(FlexfiError::Unauthorized.into());
-    }
-
     // Verify the user status PDA
     let (user_status_pda, _) = Pubkey::find_program_address(
         &[WHITELIST_SEED, user_pubkey.as_ref()],
@@ -243,10 +535,492 @@ pub fn process_remove_from_whitelist(
     user_status.is_whitelisted = false;
     user_status.serialize(&mut *user_status_account.data.borrow_mut())?;
 
-    // Decrement the counter (beware of underflows)
+    // Decrement the counter (beware of underflows) and invalidate the wallet caches
     whitelist_data.total_users = whitelist_data.total_users.saturating_sub(1);
+    whitelist_data.version = whitelist_data.version.wrapping_add(1);
     whitelist_data.serialize(&mut *whitelist_account.data.borrow_mut())?;
 
+    emit_whitelist_change(WhitelistChange::Removed, user_pubkey, *authority.key, user_status.expires_at)?;
+
     msg!("User {} removed from whitelist", user_pubkey);
     Ok(())
 }
+
+// Extend a whitelist entry after the user passed KYC again
+pub fn process_renew_whitelist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    user_pubkey: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let user_status_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the whitelist manager role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::WhitelistManager)?;
+
+    // Verify the user status PDA
+    let (user_status_pda, _) = Pubkey::find_program_address(
+        &[WHITELIST_SEED, user_pubkey.as_ref()],
+        program_id
+    );
+
+    if user_status_account.key != &user_status_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut user_status = UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())?;
+
+    // Removed users have to be added again
+    if !user_status.is_whitelisted {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    user_status.expires_at = clock.unix_timestamp + (WHITELIST_VALIDITY_DAYS * 86400);
+    user_status.whitelisted_by = *authority.key;
+    user_status.serialize(&mut *user_status_account.data.borrow_mut())?;
+
+    emit_whitelist_change(WhitelistChange::Renewed, user_pubkey, *authority.key, user_status.expires_at)?;
+
+    msg!("Whitelist entry of {} renewed until {}", user_pubkey, user_status.expires_at);
+    Ok(())
+}
+
+// Switch whitelist-gated functions on or off (pauser only)
+pub fn process_set_whitelist_active(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    is_active: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the pauser role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Pauser)?;
+
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if whitelist_account.key != &whitelist_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
+
+    whitelist_data.is_active = is_active;
+    whitelist_data.serialize(&mut *whitelist_account.data.borrow_mut())?;
+
+    let change = if is_active { WhitelistChange::Activated } else { WhitelistChange::Deactivated };
+    emit_whitelist_change(change, Pubkey::default(), *authority.key, 0)?;
+
+    msg!("Whitelist active: {}", is_active);
+    Ok(())
+}
+
+// Cap a user's BNPL exposure without removing them from the whitelist; 0 lifts a cap
+pub fn process_set_user_risk_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    user_pubkey: Pubkey,
+    max_bnpl_amount: u64,
+    max_active_contracts: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let user_status_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?; // Its version invalidates the cached limits
+
+    // Verify the authority holds the whitelist manager role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::WhitelistManager)?;
+
+    // Verify the user status PDA
+    let (user_status_pda, _) = Pubkey::find_program_address(
+        &[WHITELIST_SEED, user_pubkey.as_ref()],
+        program_id
+    );
+
+    if user_status_account.key != &user_status_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut user_status = UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())?;
+
+    user_status.max_bnpl_amount = max_bnpl_amount;
+    user_status.max_active_contracts = max_active_contracts;
+    user_status.serialize(&mut *user_status_account.data.borrow_mut())?;
+
+    bump_whitelist_version(program_id, whitelist_account)?;
+
+    emit_whitelist_change(WhitelistChange::RiskLimitsChanged, user_pubkey, *authority.key, user_status.expires_at)?;
+
+    msg!("Risk limits of {}: max BNPL amount {}, max active contracts {}", user_pubkey, max_bnpl_amount, max_active_contracts);
+    Ok(())
+}
+
+// Assign the user's region (ISO 3166-1 numeric), as established by KYC
+pub fn process_set_user_region(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    user_pubkey: Pubkey,
+    region: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let user_status_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?; // Its version invalidates the cached region
+
+    // Verify the authority holds the whitelist manager role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::WhitelistManager)?;
+
+    // Verify the user status PDA
+    let (user_status_pda, _) = Pubkey::find_program_address(
+        &[WHITELIST_SEED, user_pubkey.as_ref()],
+        program_id
+    );
+
+    if user_status_account.key != &user_status_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut user_status = UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())?;
+
+    user_status.region = region;
+    user_status.serialize(&mut *user_status_account.data.borrow_mut())?;
+
+    bump_whitelist_version(program_id, whitelist_account)?;
+
+    emit_whitelist_change(WhitelistChange::RegionChanged, user_pubkey, *authority.key, user_status.expires_at)?;
+
+    msg!("Region of {} set to {}", user_pubkey, region);
+    Ok(())
+}
+
+// Set the backend key whose ed25519 attestations whitelist users without a status PDA; default disables it
+pub fn process_set_attestation_signer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    signer: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the whitelist manager role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::WhitelistManager)?;
+
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if whitelist_account.key != &whitelist_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
+
+    whitelist_data.attestation_signer = signer;
+    whitelist_data.serialize(&mut *whitelist_account.data.borrow_mut())?;
+
+    emit_whitelist_change(WhitelistChange::AttestationSignerChanged, signer, *authority.key, 0)?;
+
+    msg!("Whitelist attestation signer set to {}", signer);
+    Ok(())
+}
+
+// Register a device/session key that may act for the user (signed by the user's primary key)
+pub fn process_add_authorized_key(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    key: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let user_status_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+
+    let mut user_status = load_own_user_status(program_id, user_status_account, user_account)?;
+
+    let clock = Clock::get()?;
+
+    if !user_status.is_valid(clock.unix_timestamp) {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if key == Pubkey::default() || user_status.is_authorized_key(&key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let slot = user_status.authorized_keys
+        .iter()
+        .position(|authorized_key| *authorized_key == Pubkey::default())
+        .ok_or(FlexfiError::AuthorizedKeyLimitReached)?;
+
+    user_status.authorized_keys[slot] = key;
+    user_status.serialize(&mut *user_status_account.data.borrow_mut())?;
+
+    emit_whitelist_change(WhitelistChange::KeyAdded, *user_account.key, *user_account.key, user_status.expires_at)?;
+
+    msg!("Authorized key {} added for user {}", key, user_account.key);
+    Ok(())
+}
+
+// Revoke a device/session key (signed by the user's primary key); the whitelist version is bumped
+// like for any other revocation
+pub fn process_remove_authorized_key(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    key: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let user_status_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+
+    let mut user_status = load_own_user_status(program_id, user_status_account, user_account)?;
+
+    let slot = user_status.authorized_keys
+        .iter()
+        .position(|authorized_key| *authorized_key == key && key != Pubkey::default())
+        .ok_or(ProgramError::InvalidArgument)?;
+
+    user_status.authorized_keys[slot] = Pubkey::default();
+    user_status.serialize(&mut *user_status_account.data.borrow_mut())?;
+
+    bump_whitelist_version(program_id, whitelist_account)?;
+
+    emit_whitelist_change(WhitelistChange::KeyRemoved, *user_account.key, *user_account.key, user_status.expires_at)?;
+
+    msg!("Authorized key {} removed for user {}", key, user_account.key);
+    Ok(())
+}
+
+// Invalidate the wallet caches, so they are refreshed from the user status before the next cached use
+fn bump_whitelist_version(
+    program_id: &Pubkey,
+    whitelist_account: &AccountInfo,
+) -> ProgramResult {
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+
+    if whitelist_account.key != &whitelist_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut whitelist_data = WhitelistAccount::try_from_slice(&whitelist_account.data.borrow())?;
+
+    whitelist_data.version = whitelist_data.version.wrapping_add(1);
+    whitelist_data.serialize(&mut *whitelist_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Load the whitelist status of the signing user; device keys can't manage keys
+fn load_own_user_status(
+    program_id: &Pubkey,
+    user_status_account: &AccountInfo,
+    user_account: &AccountInfo,
+) -> Result<UserWhitelistStatus, ProgramError> {
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let (user_status_pda, _) = Pubkey::find_program_address(
+        &[WHITELIST_SEED, user_account.key.as_ref()],
+        program_id
+    );
+
+    if user_status_account.key != &user_status_pda || user_status_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One-signature ed25519 instruction data with the key, signature and message inline
+    fn ed25519_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_offset: u16 = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for value in [
+            signature_offset,
+            u16::MAX,
+            public_key_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    fn attestation(program_id: &Pubkey, user: &Pubkey, expires_at: i64) -> Vec<u8> {
+        let mut message = program_id.to_bytes().to_vec();
+        message.extend_from_slice(user.as_ref());
+        message.extend_from_slice(&expires_at.to_le_bytes());
+        message
+    }
+
+    #[test]
+    fn attestation_returns_its_expiry() {
+        let (program_id, user, signer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let data = ed25519_data(&signer, &attestation(&program_id, &user, 1_700_000_000));
+
+        assert_eq!(parse_whitelist_attestation(&data, &program_id, &user, &signer), Some(1_700_000_000));
+    }
+
+    #[test]
+    fn attestation_rejects_another_signer_user_or_program() {
+        let (program_id, user, signer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let data = ed25519_data(&signer, &attestation(&program_id, &user, 1_700_000_000));
+
+        assert_eq!(parse_whitelist_attestation(&data, &program_id, &user, &Pubkey::new_unique()), None);
+        assert_eq!(parse_whitelist_attestation(&data, &program_id, &Pubkey::new_unique(), &signer), None);
+        assert_eq!(parse_whitelist_attestation(&data, &Pubkey::new_unique(), &user, &signer), None);
+    }
+
+    #[test]
+    fn attestation_rejects_malformed_messages() {
+        let (program_id, user, signer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let message = attestation(&program_id, &user, 1_700_000_000);
+
+        // Wrong message length
+        let data = ed25519_data(&signer, &message[..WHITELIST_ATTESTATION_LEN - 1]);
+        assert_eq!(parse_whitelist_attestation(&data, &program_id, &user, &signer), None);
+
+        let mut longer = message.clone();
+        longer.push(0);
+        let data = ed25519_data(&signer, &longer);
+        assert_eq!(parse_whitelist_attestation(&data, &program_id, &user, &signer), None);
+
+        // Truncated instruction data
+        let data = ed25519_data(&signer, &message);
+        assert_eq!(parse_whitelist_attestation(&data[..data.len() - 1], &program_id, &user, &signer), None);
+        assert_eq!(parse_whitelist_attestation(&[], &program_id, &user, &signer), None);
+    }
+
+    #[test]
+    fn attestation_rejects_data_held_by_other_instructions() {
+        let (program_id, user, signer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let data = ed25519_data(&signer, &attestation(&program_id, &user, 1_700_000_000));
+
+        // Signature, key or message taken from another instruction of the transaction
+        for index_offset in [4, 8, 14] {
+            let mut other = data.clone();
+            other[index_offset..index_offset + 2].copy_from_slice(&0u16.to_le_bytes());
+            assert_eq!(parse_whitelist_attestation(&other, &program_id, &user, &signer), None);
+        }
+
+        // More than one signature
+        let mut multiple = data.clone();
+        multiple[0] = 2;
+        assert_eq!(parse_whitelist_attestation(&multiple, &program_id, &user, &signer), None);
+    }
+
+    #[test]
+    fn cached_mode_reads_the_limits_copied_onto_the_wallet() {
+        let (program_id, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let limits = UserLimits { region: 250, max_bnpl_amount: 100_000_000, max_active_contracts: 1 };
+        let wallet_data = WalletAccount {
+            owner,
+            is_active: true,
+            card_type: 0,
+            created_at: 0,
+            backend_id_hash: [0u8; 32],
+            whitelist_cached: true,
+            whitelist_version: 3,
+            whitelist_expires_at: i64::MAX,
+            whitelist_limits: limits,
+            erasure_requested_at: 0,
+            erased: false,
+            staking_positions: 0,
+            recovered_from: Pubkey::default(),
+            bump: 255,
+        };
+        let mut data = borsh::to_vec(&wallet_data).unwrap();
+        assert_eq!(data.len(), WalletAccount::SIZE);
+
+        let (key, mut lamports) = (Pubkey::new_unique(), 0);
+        let wallet_account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &program_id, false, 0);
+
+        // The wallet is not deserialized as a user status
+        assert!(is_wallet_account(&program_id, &wallet_account));
+        assert_eq!(load_user_limits(&program_id, &wallet_account), Ok(Some(limits)));
+        assert!(limits.allows_bnpl(100_000_000, 0));
+        assert!(!limits.allows_bnpl(100_000_001, 0));
+        assert!(!limits.allows_bnpl(1, 1));
+    }
+
+    #[test]
+    fn attested_users_have_no_limits() {
+        let program_id = Pubkey::new_unique();
+        let (key, mut lamports, mut data) = (instructions_sysvar::id(), 0, vec![]);
+        let sysvar_owner = solana_program::sysvar::id();
+        let instructions_account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &sysvar_owner, false, 0);
+
+        assert_eq!(load_user_limits(&program_id, &instructions_account), Ok(None));
+    }
+
+    #[test]
+    fn device_keys_act_for_the_user_registering_them() {
+        let (program_id, user, device_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut authorized_keys = [Pubkey::default(); MAX_AUTHORIZED_KEYS];
+        authorized_keys[0] = device_key;
+        let user_status = UserWhitelistStatus {
+            user_pubkey: user,
+            is_whitelisted: true,
+            whitelisted_at: 0,
+            whitelisted_by: Pubkey::new_unique(),
+            expires_at: i64::MAX,
+            authorized_keys,
+            max_bnpl_amount: 0,
+            max_active_contracts: 0,
+            region: 0,
+            bump: 255,
+        };
+        let mut status_data = borsh::to_vec(&user_status).unwrap();
+        let (status_key, _) = Pubkey::find_program_address(&[WHITELIST_SEED, user.as_ref()], &program_id);
+        let mut status_lamports = 0;
+        let status_account = AccountInfo::new(&status_key, false, false, &mut status_lamports, &mut status_data, &program_id, false, 0);
+
+        let system_program = solana_program::system_program::id();
+        let (mut device_lamports, mut device_data) = (0, vec![]);
+        let device_account = AccountInfo::new(&device_key, true, false, &mut device_lamports, &mut device_data, &system_program, false, 0);
+        assert_eq!(resolve_acting_user(&program_id, &device_account, &status_account), Ok(user));
+
+        let (mut user_lamports, mut user_data) = (0, vec![]);
+        let user_account = AccountInfo::new(&user, true, false, &mut user_lamports, &mut user_data, &system_program, false, 0);
+        assert_eq!(resolve_acting_user(&program_id, &user_account, &status_account), Ok(user));
+
+        // A key the user never registered
+        let other_key = Pubkey::new_unique();
+        let (mut other_lamports, mut other_data) = (0, vec![]);
+        let other_account = AccountInfo::new(&other_key, true, false, &mut other_lamports, &mut other_data, &system_program, false, 0);
+        assert_eq!(
+            resolve_acting_user(&program_id, &other_account, &status_account),
+            Err(FlexfiError::Unauthorized.into())
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::authorization::AuthorizationAccount;
+use crate::state::donation::{CharityAccount, DonationAccount};
+use crate::constants::{CHARITY_SEED, DONATION_SEED, MAX_DONATION_BPS};
+use crate::core::whitelist::require_whitelist_authority;
+
+// Load a registered charity after checking its PDA
+pub fn load_charity(
+    program_id: &Pubkey,
+    charity_account: &AccountInfo,
+) -> Result<CharityAccount, ProgramError> {
+    if charity_account.owner != program_id {
+        return Err(FlexfiError::CharityNotRegistered.into());
+    }
+
+    let charity_data = CharityAccount::try_from_slice(&charity_account.data.borrow())?;
+
+    let (charity_pda, _) = Pubkey::find_program_address(
+        &[CHARITY_SEED, charity_data.wallet.as_ref()],
+        program_id,
+    );
+
+    if *charity_account.key != charity_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !charity_data.is_active {
+        return Err(FlexfiError::CharityNotRegistered.into());
+    }
+
+    Ok(charity_data)
+}
+
+// Register a charity wallet that users can route donations to (admin only)
+pub fn process_register_charity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let charity_account = next_account_info(account_info_iter)?;
+    let charity_wallet = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    // Check the charity PDA
+    let (charity_pda, charity_bump) = Pubkey::find_program_address(
+        &[CHARITY_SEED, charity_wallet.key.as_ref()],
+        program_id,
+    );
+
+    if *charity_account.key != charity_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the charity account
+    let rent = Rent::get()?;
+    let space = CharityAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &charity_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[authority.clone(), charity_account.clone(), system_program.clone()],
+        &[&[CHARITY_SEED, charity_wallet.key.as_ref(), &[charity_bump]]],
+    )?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let charity_data = CharityAccount {
+        wallet: *charity_wallet.key,
+        is_active: true,
+        registered_at: clock.unix_timestamp,
+        total_received: 0,
+        bump: charity_bump,
+    };
+
+    charity_data.serialize(&mut *charity_account.data.borrow_mut())?;
+
+    msg!("Charity {} registered", charity_wallet.key);
+    Ok(())
+}
+
+// Stop a charity from receiving new donations (admin only)
+pub fn process_deactivate_charity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let charity_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    let mut charity_data = load_charity(program_id, charity_account)?;
+
+    charity_data.is_active = false;
+    charity_data.serialize(&mut *charity_account.data.borrow_mut())?;
+
+    msg!("Charity {} deactivated", charity_data.wallet);
+    Ok(())
+}
+
+// Opt in to (or out of, with 0 bps) donating a share of each spend to a registered charity
+pub fn process_set_spend_donation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    donation_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authorization_account = next_account_info(account_info_iter)?;
+    let donation_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if donation_bps > MAX_DONATION_BPS {
+        return Err(FlexfiError::InvalidDonationRate.into());
+    }
+
+    let mut authorization = AuthorizationAccount::try_from_slice(
+        &authorization_account.data.borrow()
+    )?;
+
+    if authorization.user != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check the donation PDA
+    let (donation_pda, donation_bump) = Pubkey::find_program_address(
+        &[DONATION_SEED, user_account.key.as_ref()],
+        program_id,
+    );
+
+    if *donation_account.key != donation_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if donation_bps > 0 {
+        let charity_account = next_account_info(account_info_iter)?;
+        let charity_data = load_charity(program_id, charity_account)?;
+
+        // Create the giving summary on first opt-in
+        let mut donation_data = if donation_account.owner != program_id {
+            let rent = Rent::get()?;
+            let space = DonationAccount::SIZE;
+            let rent_lamports = rent.minimum_balance(space);
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    user_account.key,
+                    &donation_pda,
+                    rent_lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[user_account.clone(), donation_account.clone(), system_program.clone()],
+                &[&[DONATION_SEED, user_account.key.as_ref(), &[donation_bump]]],
+            )?;
+
+            DonationAccount {
+                owner: *user_account.key,
+                charity: charity_data.wallet,
+                giving_year: DonationAccount::get_giving_year(Clock::get()?.unix_timestamp),
+                year_donated: 0,
+                previous_year_donated: 0,
+                total_donated: 0,
+                bump: donation_bump,
+            }
+        } else {
+            DonationAccount::try_from_slice(&donation_account.data.borrow())?
+        };
+
+        donation_data.charity = charity_data.wallet;
+        donation_data.serialize(&mut *donation_account.data.borrow_mut())?;
+
+        msg!("Donating {} bps of each spend to charity {}", donation_bps, charity_data.wallet);
+    } else {
+        msg!("Spend donations turned off");
+    }
+
+    authorization.donation_bps = donation_bps;
+    authorization.serialize(&mut *authorization_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+pub struct CharityRegistry;
+
+impl CharityRegistry {
+    pub fn register(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_register_charity(program_id, accounts)
+    }
+
+    pub fn deactivate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_deactivate_charity(program_id, accounts)
+    }
+
+    pub fn set_spend_donation(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        donation_bps: u16,
+    ) -> ProgramResult {
+        process_set_spend_donation(program_id, accounts, donation_bps)
+    }
+}
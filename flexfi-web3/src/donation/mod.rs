@@ -0,0 +1,8 @@
+pub mod charity;
+
+pub use charity::{
+    process_register_charity,
+    process_deactivate_charity,
+    process_set_spend_donation,
+    load_charity,
+};
@@ -8,6 +8,7 @@ use solana_program::{
 };
 
 use crate::processor;
+use crate::error_context::{log_error_context, ContextValue};
 
 entrypoint!(process_instruction);
 
@@ -22,5 +23,12 @@ fn process_instruction(
 
     msg!("FlexFi program entrypoint");
     
-    processor::process_instruction(program_id, accounts, instruction_data)
+    let result = processor::process_instruction(program_id, accounts, instruction_data);
+
+    // Every failure carries at least its error code in an ErrorContext
+    if let Err(error) = &result {
+        log_error_context(error, None, ContextValue::None, ContextValue::None);
+    }
+
+    result
 }
\ No newline at end of file
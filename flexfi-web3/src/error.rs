@@ -71,6 +71,99 @@ pub enum FlexfiError {
 
     #[error("Insufficient collateral for auto debit")]
     InsufficientCollateralForAutoDebit,
+
+    #[error("No staking rewards to claim")]
+    NoStakingRewards,
+
+    #[error("Mint not accepted as collateral")]
+    MintNotAccepted,
+
+    #[error("Card verification commitment mismatch")]
+    CardCommitmentMismatch,
+
+    #[error("Contract is not defaulted")]
+    ContractNotDefaulted,
+
+    #[error("Merchant challenge period has ended")]
+    ChallengePeriodEnded,
+
+    #[error("Oracle price is stale")]
+    StaleOraclePrice,
+
+    #[error("Invalid oracle price")]
+    InvalidOraclePrice,
+
+    #[error("Charity not registered")]
+    CharityNotRegistered,
+
+    #[error("Invalid donation rate")]
+    InvalidDonationRate,
+
+    #[error("Invalid wallet guardians")]
+    InvalidGuardians,
+
+    #[error("Wallet recovery timelock has not passed")]
+    RecoveryTimelockActive,
+
+    #[error("Backend id already linked")]
+    BackendIdAlreadyLinked,
+
+    #[error("Invalid or exhausted promo code")]
+    InvalidPromoCode,
+
+    #[error("Wallet still has open positions")]
+    WalletHasOpenPositions,
+
+    #[error("Voting is closed")]
+    VotingClosed,
+
+    #[error("Proposal is not ready")]
+    ProposalNotReady,
+
+    #[error("Epoch has not ended")]
+    EpochNotEnded,
+
+    #[error("Rewards already distributed")]
+    RewardsAlreadyDistributed,
+
+    #[error("Payment already snoozed")]
+    PaymentAlreadySnoozed,
+
+    #[error("Merchant is not active")]
+    MerchantNotActive,
+
+    #[error("User is blocklisted")]
+    UserBlocklisted,
+
+    #[error("Authorized key limit reached")]
+    AuthorizedKeyLimitReached,
+
+    #[error("User risk limit exceeded")]
+    RiskLimitExceeded,
+
+    #[error("Region is not enabled")]
+    RegionNotEnabled,
+
+    #[error("Enabled region limit reached")]
+    RegionLimitReached,
+
+    #[error("Whitelist cache is stale")]
+    WhitelistCacheStale,
+
+    #[error("Strategy is paused")]
+    StrategyPaused,
+
+    #[error("Score too low")]
+    ScoreTooLow,
+
+    #[error("Treasury withdrawal must be streamed")]
+    TreasuryStreamRequired,
+
+    #[error("Borrower registry is full")]
+    BorrowerRegistryFull,
+
+    #[error("Merchant challenge period is still running")]
+    ChallengePeriodActive,
 }
 
 impl From<FlexfiError> for ProgramError {
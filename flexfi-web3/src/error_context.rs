@@ -0,0 +1,77 @@
+use solana_program::{
+    account_info::AccountInfo,
+    log::sol_log_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
+pub enum ContextValue {
+    None,
+    Key(Pubkey),
+    Amount(u64),
+}
+
+// Machine-readable context of a failure, logged as event data so the app can show an actionable
+// message instead of "custom program error 0x…". The most specific context is logged first; the
+// entrypoint always adds one with just the error code. `account` is the offending account
+// (default when the failure isn't tied to one); its index is its position in the instruction.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ErrorContext {
+    pub error_code: u64,
+    pub account: Pubkey,
+    pub expected: ContextValue,
+    pub actual: ContextValue,
+}
+
+pub fn log_error_context(
+    error: &ProgramError,
+    account: Option<&Pubkey>,
+    expected: ContextValue,
+    actual: ContextValue,
+) {
+    let context = ErrorContext {
+        error_code: u64::from(error.clone()),
+        account: account.copied().unwrap_or_default(),
+        expected,
+        actual,
+    };
+
+    if let Ok(data) = context.try_to_vec() {
+        sol_log_data(&[b"ErrorContext", &data]);
+    }
+}
+
+// Log an account that isn't the expected one (usually a PDA or an ATA) and return the error
+pub fn key_mismatch(
+    account: &AccountInfo,
+    expected: &Pubkey,
+    error: impl Into<ProgramError>,
+) -> ProgramError {
+    let error = error.into();
+    log_error_context(&error, Some(account.key), ContextValue::Key(*expected), ContextValue::Key(*account.key));
+    error
+}
+
+// Log an amount outside its bound and return the error
+pub fn amount_mismatch(
+    account: Option<&AccountInfo>,
+    expected: u64,
+    actual: u64,
+    error: impl Into<ProgramError>,
+) -> ProgramError {
+    let error = error.into();
+    log_error_context(&error, account.map(|account| account.key), ContextValue::Amount(expected), ContextValue::Amount(actual));
+    error
+}
+
+// Log a failure tied to an account without a comparable value (missing signature, role...) and return the error
+pub fn account_error(
+    account: &AccountInfo,
+    error: impl Into<ProgramError>,
+) -> ProgramError {
+    let error = error.into();
+    log_error_context(&error, Some(account.key), ContextValue::None, ContextValue::None);
+    error
+}
@@ -1,7 +1,7 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
-    program::{invoke, invoke_signed},
+    program::invoke_signed,
     pubkey::Pubkey,
     system_instruction,
     sysvar::{clock::Clock, Sysvar, rent::Rent},
@@ -11,10 +11,23 @@ use solana_program::{
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::error::FlexfiError;
-use crate::state::authorization::AuthorizationAccount;
+use crate::state::authorization::{AuthorizationAccount, CreditReservation};
 use crate::state::staking::StakingAccount;
-use crate::constants::{AUTHORIZATION_SEED, FLEXFI_AUTHORITY_SEED, USDC_VAULT_SEED};
-use crate::core::whitelist::require_whitelisted;
+use crate::state::card::CardAccount;
+use crate::state::savings::SavingsGoalAccount;
+use crate::state::donation::DonationAccount;
+use crate::constants::{
+    AUTHORIZATION_SEED, FLEXFI_AUTHORITY_SEED, USDC_VAULT_SEED, CARD_SEED, DONATION_SEED, PROMO_CODE_SEED,
+    TREASURY_SEED, MAX_CREDIT_RESERVATIONS, INSURANCE_FEE_SHARE_BPS,
+};
+use crate::donation::charity::load_charity;
+use crate::freeze_spend::promo::load_promo_code;
+use crate::merchant::registry::load_merchant;
+use crate::merchant::settlement::record_merchant_settlement;
+use crate::token::{transfer_checked, get_mint_decimals};
+use crate::math::{bps_share, Rounding};
+use crate::insurance::reserve::{check_reserve_token_account, get_insurance_share};
+use crate::core::whitelist::resolve_acting_user;
 
 pub fn process_initialize_flexfi_account(
     program_id: &Pubkey,
@@ -26,7 +39,7 @@ pub fn process_initialize_flexfi_account(
 
     let authorization_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
     let staking_account = next_account_info(account_info_iter)?;
     let flexfi_authority_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
@@ -37,9 +50,6 @@ pub fn process_initialize_flexfi_account(
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    // Check if the user is whitelisted
-    require_whitelisted(program_id, user_account.key, user_status_account)?;
-
     // Check if the user has sufficient staking
     let staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
     if staking_data.amount_staked < authorized_amount {
@@ -96,6 +106,11 @@ pub fn process_initialize_flexfi_account(
         is_active: true,
         created_at: current_time,
         expires_at,
+        round_up_goal: Pubkey::default(),
+        donation_bps: 0,
+        promo_code: Pubkey::default(),
+        reservation_count: 0,
+        reservations: [CreditReservation::default(); MAX_CREDIT_RESERVATIONS],
         bump: auth_bump,
     };
 
@@ -106,6 +121,7 @@ pub fn process_initialize_flexfi_account(
     Ok(())
 }
 
+// Spend on the user's FlexFi credit, signed by the user or one of their device keys
 pub fn process_flexfi_spend(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -115,18 +131,52 @@ pub fn process_flexfi_spend(
     let account_info_iter = &mut accounts.iter();
 
     let authorization_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let user_status_account = next_account_info(account_info_iter)?; // Whitelisting checked by the processor guard
     let user_staking_account = next_account_info(account_info_iter)?;
     let staking_vault_account = next_account_info(account_info_iter)?;
     let merchant_token_account = next_account_info(account_info_iter)?;
     let flexfi_authority_account = next_account_info(account_info_iter)?;
+    let staking_mint = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
+    let card_account = next_account_info(account_info_iter)?;
+    let merchant_settlement_account = next_account_info(account_info_iter)?;
+    let merchant_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let insurance_token_account = next_account_info(account_info_iter)?; // Receives the insurance slice of the fee
 
     // Load authorization data
     let mut authorization = AuthorizationAccount::try_from_slice(
         &authorization_account.data.borrow()
     )?;
 
+    // The round-up savings goal and its vault follow when the rule is on
+    let round_up_accounts = if authorization.has_round_up() {
+        Some((next_account_info(account_info_iter)?, next_account_info(account_info_iter)?))
+    } else {
+        None
+    };
+
+    // The giving summary, charity and charity token account follow when donations are on
+    let donation_accounts = if authorization.has_donation() {
+        Some((
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+        ))
+    } else {
+        None
+    };
+
+    // The redeemed promo code and its vault follow when a promo is pending
+    let promo_accounts = if authorization.has_promo() {
+        Some((next_account_info(account_info_iter)?, next_account_info(account_info_iter)?))
+    } else {
+        None
+    };
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
     // Verify the FlexFi authority
     let (flexfi_authority_pda, flexfi_bump) = Pubkey::find_program_address(
         &[FLEXFI_AUTHORITY_SEED],
@@ -137,6 +187,22 @@ pub fn process_flexfi_spend(
         return Err(FlexfiError::Unauthorized.into());
     }
 
+    // The spend draws on the authorization of the user the signer acts for
+    let user = resolve_acting_user(program_id, user_account, user_status_account)?;
+
+    let (authorization_pda, _) = Pubkey::find_program_address(
+        &[AUTHORIZATION_SEED, user.as_ref()],
+        program_id
+    );
+
+    if *authorization_account.key != authorization_pda || authorization_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if authorization.user != user {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
     // Verify the validity of the authorization
     let clock = Clock::from_account_info(clock_sysvar)?;
     let current_time = clock.unix_timestamp;
@@ -145,13 +211,119 @@ pub fn process_flexfi_spend(
         return Err(FlexfiError::Unauthorized.into());
     }
 
+    if user_staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let staking_data = StakingAccount::try_from_slice(&user_staking_account.data.borrow())?;
+
+    if staking_data.owner != user || *staking_mint.key != staking_data.usdc_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // The spend is paid from that position's vault
+    let (vault_pda, _) = Pubkey::find_program_address(
+        &[USDC_VAULT_SEED, user_staking_account.key.as_ref()],
+        program_id
+    );
+    let vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &vault_pda,
+        staking_mint.key,
+        token_program.key,
+    );
+
+    if *staking_vault_account.key != vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Only active registered merchants can be paid, to their settlement account
+    let merchant_data = load_merchant(program_id, merchant_account)?;
+
+    if merchant_data.owner != merchant {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !merchant_data.is_active(current_time) {
+        return Err(FlexfiError::MerchantNotActive.into());
+    }
+
+    let merchant_settlement_token = if merchant_data.settlement_token_account != Pubkey::default() {
+        merchant_data.settlement_token_account
+    } else {
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &merchant,
+            staking_mint.key,
+            token_program.key,
+        )
+    };
+
+    if *merchant_token_account.key != merchant_settlement_token {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // The merchant fee goes to the treasury of the mint
+    let (treasury_pda, _) = Pubkey::find_program_address(
+        &[TREASURY_SEED, staking_mint.key.as_ref()],
+        program_id
+    );
+    let treasury_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &treasury_pda,
+        staking_mint.key,
+        token_program.key,
+    );
+
+    if *treasury_token_account.key != treasury_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    check_reserve_token_account(program_id, staking_mint.key, token_program.key, insurance_token_account)?;
+
+    // A redeemed promo code covers part of the spend from the campaign budget
+    let mut promo = None;
+    if let Some((promo_code_account, promo_vault_token_account)) = promo_accounts {
+        if *promo_code_account.key != authorization.promo_code {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let promo_data = load_promo_code(program_id, promo_code_account)?;
+
+        // An expired code or one for another mint is dropped without a discount
+        if current_time < promo_data.expires_at && promo_data.mint == *staking_mint.key {
+            let promo_vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+                promo_code_account.key,
+                staking_mint.key,
+                token_program.key,
+            );
+
+            if *promo_vault_token_account.key != promo_vault_ata {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            promo = Some((promo_code_account, promo_vault_token_account, promo_data));
+        }
+
+        // The code is used up by this spend
+        authorization.promo_code = Pubkey::default();
+    }
+
+    let discount = promo.as_ref().map_or(0, |(_, _, promo_data)| promo_data.get_discount(amount));
+    let charged = amount - discount;
+    // Locking the stake longer lowers the fee rate
+    let lock_discount = staking_data.lock_fee_discount(current_time);
+    let merchant_fee = std::cmp::min(merchant_data.get_discounted_fee(amount, lock_discount), charged);
+
+    // The merchant's pending charge, if any, is settled by this spend and its credit freed for it
+    let reserved = authorization.release_reservation(&merchant, current_time);
+    if reserved > 0 {
+        msg!("Pending charge reservation of {} released", reserved);
+    }
+
     // Check if the credit is sufficient
-    if authorization.remaining_credit() < amount {
+    if authorization.available_credit(current_time) < charged {
         return Err(FlexfiError::InsufficientCollateral.into());
     }
 
     // Perform the transfer from the staking vault
-    let staking_data = StakingAccount::try_from_slice(&user_staking_account.data.borrow())?;
 
     let _staking_seeds = [
         USDC_VAULT_SEED,
@@ -159,36 +331,248 @@ pub fn process_flexfi_spend(
         &[staking_data.bump],
     ];
 
-    let transfer_ix = spl_token::instruction::transfer(
-        token_program.key,
-        staking_vault_account.key,
-        merchant_token_account.key,
-        &flexfi_authority_pda, // FlexFi has the authority!
-        &[],
-        amount,
-    )?;
-
-    invoke_signed(
-        &transfer_ix,
-        &[
-            staking_vault_account.clone(),
-            merchant_token_account.clone(),
-            flexfi_authority_account.clone(),
-            token_program.clone(),
-        ],
+    transfer_checked(
+        token_program,
+        staking_vault_account,
+        staking_mint,
+        merchant_token_account,
+        flexfi_authority_account, // FlexFi has the authority!
+        hook_accounts,
+        charged - merchant_fee,
         &[&[FLEXFI_AUTHORITY_SEED, &[flexfi_bump]]],
     )?;
 
+    // Withhold the merchant fee for the treasury, less the insurance reserve's slice
+    let insurance_share = get_insurance_share(merchant_fee, INSURANCE_FEE_SHARE_BPS);
+
+    if merchant_fee > insurance_share {
+        transfer_checked(
+            token_program,
+            staking_vault_account,
+            staking_mint,
+            treasury_token_account,
+            flexfi_authority_account,
+            hook_accounts,
+            merchant_fee - insurance_share,
+            &[&[FLEXFI_AUTHORITY_SEED, &[flexfi_bump]]],
+        )?;
+    }
+
+    if insurance_share > 0 {
+        transfer_checked(
+            token_program,
+            staking_vault_account,
+            staking_mint,
+            insurance_token_account,
+            flexfi_authority_account,
+            hook_accounts,
+            insurance_share,
+            &[&[FLEXFI_AUTHORITY_SEED, &[flexfi_bump]]],
+        )?;
+    }
+
+    // Pay the discounted part from the promo vault
+    if let Some((promo_code_account, promo_vault_token_account, promo_data)) = promo {
+        if discount > 0 {
+            transfer_checked(
+                token_program,
+                promo_vault_token_account,
+                staking_mint,
+                merchant_token_account,
+                promo_code_account,
+                hook_accounts,
+                discount,
+                &[&[PROMO_CODE_SEED, &promo_data.code_hash, &[promo_data.bump]]],
+            )?;
+
+            msg!("Promo discount applied: {}", discount);
+        }
+    }
+
+    // Round the spend up to the next whole token and save the difference
+    let mut round_up = 0;
+    if let Some((goal_account, goal_vault_token_account)) = round_up_accounts {
+        let unit = 10u64.pow(get_mint_decimals(staking_mint)? as u32);
+        round_up = (unit - amount % unit) % unit;
+
+        // Skip the round-up when the remaining credit can't cover it
+        if round_up > 0 && authorization.available_credit(current_time) >= charged.saturating_add(round_up) {
+            if *goal_account.key != authorization.round_up_goal {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let mut goal_data = SavingsGoalAccount::try_from_slice(&goal_account.data.borrow())?;
+
+            if goal_data.owner != authorization.user || goal_data.mint != *staking_mint.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let goal_vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+                goal_account.key,
+                staking_mint.key,
+                token_program.key,
+            );
+
+            if *goal_vault_token_account.key != goal_vault_ata {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            transfer_checked(
+                token_program,
+                staking_vault_account,
+                staking_mint,
+                goal_vault_token_account,
+                flexfi_authority_account,
+                hook_accounts,
+                round_up,
+                &[&[FLEXFI_AUTHORITY_SEED, &[flexfi_bump]]],
+            )?;
+
+            goal_data.amount_saved = goal_data.amount_saved.saturating_add(round_up);
+            goal_data.serialize(&mut *goal_account.data.borrow_mut())?;
+
+            msg!("Round-up saved: {}", round_up);
+        } else {
+            round_up = 0;
+        }
+    }
+
+    // Donate a share of the spend to the user's charity
+    let mut donation = 0;
+    if let Some((donation_account, charity_account, charity_token_account)) = donation_accounts {
+        donation = bps_share(amount, authorization.donation_bps, Rounding::Down);
+
+        // Skip the donation when the remaining credit can't cover it
+        let needed = charged.saturating_add(round_up).saturating_add(donation);
+        if donation > 0 && authorization.available_credit(current_time) >= needed {
+            let (donation_pda, _) = Pubkey::find_program_address(
+                &[DONATION_SEED, authorization.user.as_ref()],
+                program_id
+            );
+
+            if *donation_account.key != donation_pda {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let mut donation_data = DonationAccount::try_from_slice(&donation_account.data.borrow())?;
+            let mut charity_data = load_charity(program_id, charity_account)?;
+
+            if charity_data.wallet != donation_data.charity {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let charity_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+                &charity_data.wallet,
+                staking_mint.key,
+                token_program.key,
+            );
+
+            if *charity_token_account.key != charity_ata {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            transfer_checked(
+                token_program,
+                staking_vault_account,
+                staking_mint,
+                charity_token_account,
+                flexfi_authority_account,
+                hook_accounts,
+                donation,
+                &[&[FLEXFI_AUTHORITY_SEED, &[flexfi_bump]]],
+            )?;
+
+            donation_data.record_donation(donation, current_time);
+            donation_data.serialize(&mut *donation_account.data.borrow_mut())?;
+
+            charity_data.total_received = charity_data.total_received.saturating_add(donation);
+            charity_data.serialize(&mut *charity_account.data.borrow_mut())?;
+
+            msg!("Donated {} to charity {}", donation, charity_data.wallet);
+        } else {
+            donation = 0;
+        }
+    }
+
     // Update the used amount
-    authorization.used_amount = authorization.used_amount.saturating_add(amount);
+    authorization.used_amount = authorization.used_amount
+        .saturating_add(charged)
+        .saturating_add(round_up)
+        .saturating_add(donation);
     authorization.serialize(&mut *authorization_account.data.borrow_mut())?;
 
+    // Record the spend on the user's card for the yearly tier review
+    let (card_pda, _) = Pubkey::find_program_address(
+        &[CARD_SEED, authorization.user.as_ref()],
+        program_id
+    );
+
+    if *card_account.key != card_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if card_account.owner == program_id {
+        let mut card_data = CardAccount::try_from_slice(&card_account.data.borrow())?;
+        card_data.record_spend(amount);
+        card_data.serialize(&mut *card_account.data.borrow_mut())?;
+    }
+
+    // Credit the merchant's settlement totals for this epoch
+    record_merchant_settlement(program_id, merchant_settlement_account, &merchant, clock.epoch, amount, merchant_fee)?;
+
     msg!("FlexFi spend: {} USDC to merchant {}", amount / 1_000_000, merchant);
+    msg!("Merchant fee: {} at {} bps ({}), less {} bps lock discount", merchant_fee, merchant_data.fee_rate_bps(),
+        if merchant_data.has_fee_override() { "negotiated" } else { "standard" }, lock_discount);
     msg!("Remaining credit: {} USDC", authorization.remaining_credit() / 1_000_000);
 
     Ok(())
 }
 
+// Turn the spend round-up rule on (savings goal address) or off (default pubkey)
+pub fn process_set_spend_round_up(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    savings_goal: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authorization_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut authorization = AuthorizationAccount::try_from_slice(
+        &authorization_account.data.borrow()
+    )?;
+
+    if authorization.user != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // The goal must be one of the user's savings goals
+    if savings_goal != Pubkey::default() {
+        let goal_account = next_account_info(account_info_iter)?;
+
+        if *goal_account.key != savings_goal || goal_account.owner != program_id {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let goal_data = SavingsGoalAccount::try_from_slice(&goal_account.data.borrow())?;
+
+        if goal_data.owner != *user_account.key {
+            return Err(FlexfiError::Unauthorized.into());
+        }
+    }
+
+    authorization.round_up_goal = savings_goal;
+    authorization.serialize(&mut *authorization_account.data.borrow_mut())?;
+
+    msg!("Spend round-up goal set to {}", savings_goal);
+    Ok(())
+}
+
 pub fn process_revoke_authorization(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
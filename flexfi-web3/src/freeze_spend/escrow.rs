@@ -0,0 +1,331 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    log::sol_log_data,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+    program_error::ProgramError,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::escrow::{DepositEscrowAccount, EscrowStatus};
+use crate::state::staking::{StakingAccount, StakingStatus};
+use crate::constants::{DEPOSIT_ESCROW_SEED, USDC_VAULT_SEED, STAKING_SEED};
+use crate::token::transfer_checked;
+use crate::core::staking::get_staking_reward_index;
+
+// Emitted on settlement, the escrow account being closed
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct DepositEscrowSettled {
+    pub escrow: Pubkey,
+    pub user: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub paid_to_beneficiary: u64,
+    pub timestamp: i64,
+}
+
+// Accounts shared by both settlement paths, before the signers and hook accounts
+const SETTLE_ACCOUNTS_LEN: usize = 10;
+
+// Reserve part of the user's stake to back a third-party deposit. The beneficiary co-signs, agreeing
+// to the amount and to the arbiter.
+pub fn process_create_deposit_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let escrow_account = next_account_info(account_info_iter)?;
+    let staking_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+    let beneficiary_account = next_account_info(account_info_iter)?;
+    let arbiter_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check the user and beneficiary signatures
+    if !user_account.is_signer || !beneficiary_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    let (staking_pda, _) = Pubkey::find_program_address(
+        &[
+            STAKING_SEED,
+            user_account.key.as_ref(),
+            staking_data.usdc_mint.as_ref(),
+            &[staking_data.position_id],
+        ],
+        program_id
+    );
+
+    if *staking_account.key != staking_pda || staking_data.owner != *user_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let status = staking_data.get_status()?;
+    if status != StakingStatus::Active && status != StakingStatus::Locked {
+        return Err(FlexfiError::StakingNotActive.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    // Check if the unreserved stake covers the deposit; stake weighing in a running vote stays unreserved
+    let voting = if staking_data.is_vote_locked(current_time) {
+        staking_data.locked_through(staking_data.vote_locked_until)
+    } else {
+        0
+    };
+
+    if staking_data.available_amount().saturating_sub(voting) < amount {
+        return Err(FlexfiError::InsufficientStaking.into());
+    }
+
+    // Create the PDA for the escrow
+    let (escrow_pda, escrow_bump) = Pubkey::find_program_address(
+        &[DEPOSIT_ESCROW_SEED, staking_account.key.as_ref(), beneficiary_account.key.as_ref()],
+        program_id
+    );
+
+    if *escrow_account.key != escrow_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent = Rent::get()?;
+    let space = DepositEscrowAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            user_account.key,
+            &escrow_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[user_account.clone(), escrow_account.clone(), system_program.clone()],
+        &[&[DEPOSIT_ESCROW_SEED, staking_account.key.as_ref(), beneficiary_account.key.as_ref(), &[escrow_bump]]],
+    )?;
+
+    // Initialize the data
+    let escrow = DepositEscrowAccount {
+        user: *user_account.key,
+        beneficiary: *beneficiary_account.key,
+        arbiter: *arbiter_account.key,
+        staking_account: *staking_account.key,
+        amount,
+        status: EscrowStatus::Active.to_u8(),
+        created_at: current_time,
+        bump: escrow_bump,
+    };
+
+    escrow.serialize(&mut *escrow_account.data.borrow_mut())?;
+
+    // Reserve the stake
+    staking_data.escrowed_amount = staking_data.escrowed_amount.saturating_add(amount);
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    msg!("Deposit escrow created: {} USDC for {}", amount / 1_000_000, beneficiary_account.key);
+    Ok(())
+}
+
+// Release the deposit with both parties' consent
+pub fn process_release_deposit_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    to_beneficiary: u64,
+) -> ProgramResult {
+    if accounts.len() < SETTLE_ACCOUNTS_LEN {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (settle_accounts, rest) = accounts.split_at(SETTLE_ACCOUNTS_LEN);
+    let account_info_iter = &mut rest.iter();
+
+    let user_account = next_account_info(account_info_iter)?;
+    let beneficiary_account = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    if !user_account.is_signer || !beneficiary_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    settle_deposit_escrow(program_id, settle_accounts, hook_accounts, to_beneficiary, |escrow| {
+        escrow.user == *user_account.key && escrow.beneficiary == *beneficiary_account.key
+    })
+}
+
+// Settle the deposit on the arbiter's decision
+pub fn process_arbitrate_deposit_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    to_beneficiary: u64,
+) -> ProgramResult {
+    if accounts.len() < SETTLE_ACCOUNTS_LEN {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (settle_accounts, rest) = accounts.split_at(SETTLE_ACCOUNTS_LEN);
+    let account_info_iter = &mut rest.iter();
+
+    let arbiter_account = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    if !arbiter_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    settle_deposit_escrow(program_id, settle_accounts, hook_accounts, to_beneficiary, |escrow| {
+        escrow.arbiter == *arbiter_account.key
+    })
+}
+
+// Pay the beneficiary's share from the staking vault, release the rest of the reservation and close
+// the escrow, refunding its rent to the user
+fn settle_deposit_escrow<'a, F>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    hook_accounts: &[AccountInfo<'a>],
+    to_beneficiary: u64,
+    is_authorized: F,
+) -> ProgramResult
+where
+    F: Fn(&DepositEscrowAccount) -> bool,
+{
+    let account_info_iter = &mut accounts.iter();
+
+    let escrow_account = next_account_info(account_info_iter)?;
+    let staking_account = next_account_info(account_info_iter)?;
+    let staking_vault_account = next_account_info(account_info_iter)?;
+    let beneficiary_token_account = next_account_info(account_info_iter)?;
+    let vault_authority = next_account_info(account_info_iter)?;
+    let staking_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?; // Holds the reward index
+    let rent_receiver = next_account_info(account_info_iter)?; // The user, refunded the escrow's rent
+
+    // Load escrow data
+    if escrow_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let escrow = DepositEscrowAccount::try_from_slice(&escrow_account.data.borrow())?;
+
+    if !is_authorized(&escrow) {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if escrow.get_status()? != EscrowStatus::Active {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if to_beneficiary > escrow.amount {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Load staking data
+    if *staking_account.key != escrow.staking_account {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    if *staking_mint.key != staking_data.usdc_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if *rent_receiver.key != escrow.user {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the vault of the staking position and its authority
+    let (vault_pda, vault_bump) = Pubkey::find_program_address(
+        &[USDC_VAULT_SEED, staking_account.key.as_ref()],
+        program_id
+    );
+
+    if *vault_authority.key != vault_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &vault_pda,
+        staking_mint.key,
+        token_program.key,
+    );
+
+    if *staking_vault_account.key != vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the beneficiary's token account
+    let beneficiary_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &escrow.beneficiary,
+        staking_mint.key,
+        token_program.key,
+    );
+
+    if *beneficiary_token_account.key != beneficiary_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+    let reward_index = get_staking_reward_index(program_id, config_account, current_time)?;
+
+    // Release the reservation and take the paid share out of the stake
+    staking_data.accrue_rewards(reward_index, current_time);
+    staking_data.escrowed_amount = staking_data.escrowed_amount.saturating_sub(escrow.amount);
+    staking_data.amount_staked = staking_data.amount_staked.saturating_sub(to_beneficiary);
+    staking_data.last_update = current_time;
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    if to_beneficiary > 0 {
+        transfer_checked(
+            token_program,
+            staking_vault_account,
+            staking_mint,
+            beneficiary_token_account,
+            vault_authority,
+            hook_accounts,
+            to_beneficiary,
+            &[&[USDC_VAULT_SEED, staking_account.key.as_ref(), &[vault_bump]]],
+        )?;
+    }
+
+    // Close the escrow so the stake can back a new deposit for the same beneficiary
+    let escrow_lamports = escrow_account.lamports();
+    **rent_receiver.lamports.borrow_mut() = rent_receiver.lamports()
+        .checked_add(escrow_lamports)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **escrow_account.lamports.borrow_mut() = 0;
+    escrow_account.data.borrow_mut().fill(0);
+
+    let event = DepositEscrowSettled {
+        escrow: *escrow_account.key,
+        user: escrow.user,
+        beneficiary: escrow.beneficiary,
+        amount: escrow.amount,
+        paid_to_beneficiary: to_beneficiary,
+        timestamp: current_time,
+    };
+
+    sol_log_data(&[b"DepositEscrowSettled", &event.try_to_vec()?]);
+
+    msg!("Deposit escrow settled: {} to beneficiary, {} released",
+         to_beneficiary, escrow.amount.saturating_sub(to_beneficiary));
+    Ok(())
+}
@@ -1,7 +1,21 @@
 pub mod authorization;
+pub mod escrow;
+pub mod split;
+pub mod promo;
+pub mod pending_charge;
 
 pub use authorization::{
     process_initialize_flexfi_account,
     process_flexfi_spend,
     process_revoke_authorization,
-};
\ No newline at end of file
+    process_set_spend_round_up,
+};
+pub use escrow::{
+    process_create_deposit_escrow,
+    process_release_deposit_escrow,
+    process_arbitrate_deposit_escrow,
+    DepositEscrowSettled,
+};
+pub use split::process_create_split_purchase;
+pub use promo::{process_create_promo_code, process_redeem_promo_code, load_promo_code};
+pub use pending_charge::{process_create_pending_charge, process_cancel_pending_charge};
@@ -0,0 +1,135 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::authorization::AuthorizationAccount;
+use crate::constants::{AUTHORIZATION_SEED, PENDING_CHARGE_TTL_SECS};
+use crate::merchant::registry::load_merchant;
+
+// Load a user's FlexFi authorization after checking its PDA
+fn load_authorization(
+    program_id: &Pubkey,
+    authorization_account: &AccountInfo,
+) -> Result<AuthorizationAccount, ProgramError> {
+    if authorization_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let authorization = AuthorizationAccount::try_from_slice(&authorization_account.data.borrow())?;
+
+    let (authorization_pda, _) = Pubkey::find_program_address(
+        &[AUTHORIZATION_SEED, authorization.user.as_ref()],
+        program_id
+    );
+
+    if *authorization_account.key != authorization_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(authorization)
+}
+
+// A merchant opens a charge awaiting the user's approval: the amount is held on the user's credit for
+// PENDING_CHARGE_TTL_SECS so parallel checkouts can't commit the same credit twice. The spend to the
+// merchant uses the reservation; otherwise it lapses on its own.
+pub fn process_create_pending_charge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authorization_account = next_account_info(account_info_iter)?;
+    let merchant_account = next_account_info(account_info_iter)?;
+    let merchant_owner = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check merchant signature
+    if !merchant_owner.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let merchant_data = load_merchant(program_id, merchant_account)?;
+
+    if merchant_data.owner != *merchant_owner.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    if !merchant_data.is_active(current_time) {
+        return Err(FlexfiError::MerchantNotActive.into());
+    }
+
+    let mut authorization = load_authorization(program_id, authorization_account)?;
+
+    if !authorization.is_valid(current_time) {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let available = authorization.available_credit(current_time);
+    if amount > available {
+        msg!("Only {} credit available for a pending charge of {}", available, amount);
+        return Err(FlexfiError::InsufficientCollateral.into());
+    }
+
+    let expires_at = current_time + PENDING_CHARGE_TTL_SECS;
+
+    if !authorization.reserve(*merchant_owner.key, amount, expires_at, current_time) {
+        msg!("Merchant {} already has a pending charge or no reservation slot is free", merchant_owner.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    authorization.serialize(&mut *authorization_account.data.borrow_mut())?;
+
+    msg!("Pending charge of {} by {} reserved until {}", amount, merchant_owner.key, expires_at);
+    Ok(())
+}
+
+// Drop a pending charge before it expires: declined by the user or withdrawn by the merchant
+pub fn process_cancel_pending_charge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    merchant: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authorization_account = next_account_info(account_info_iter)?;
+    let signer_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    if !signer_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut authorization = load_authorization(program_id, authorization_account)?;
+
+    if *signer_account.key != authorization.user && *signer_account.key != merchant {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    let released = authorization.release_reservation(&merchant, clock.unix_timestamp);
+    if released == 0 {
+        msg!("No pending charge from merchant {}", merchant);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    authorization.serialize(&mut *authorization_account.data.borrow_mut())?;
+
+    msg!("Pending charge of {} by {} cancelled by {}", released, merchant, signer_account.key);
+    Ok(())
+}
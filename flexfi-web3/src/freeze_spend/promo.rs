@@ -0,0 +1,202 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hashv,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::authorization::AuthorizationAccount;
+use crate::state::promo::PromoCodeAccount;
+use crate::constants::{PROMO_CODE_SEED, MAX_PROMO_DISCOUNT_BPS, MAX_PROMO_CODE_LEN};
+use crate::core::whitelist::require_whitelist_authority;
+
+// Load a promo code after checking its PDA
+pub fn load_promo_code(
+    program_id: &Pubkey,
+    promo_code_account: &AccountInfo,
+) -> Result<PromoCodeAccount, ProgramError> {
+    if promo_code_account.owner != program_id {
+        return Err(FlexfiError::InvalidPromoCode.into());
+    }
+
+    let promo_data = PromoCodeAccount::try_from_slice(&promo_code_account.data.borrow())?;
+
+    let (promo_pda, _) = Pubkey::find_program_address(
+        &[PROMO_CODE_SEED, &promo_data.code_hash],
+        program_id,
+    );
+
+    if *promo_code_account.key != promo_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(promo_data)
+}
+
+// Create a promo code campaign; its budget is funded to the promo PDA's token account (admin only)
+pub fn process_create_promo_code(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    code_hash: [u8; 32],
+    discount_bps: u16,
+    max_discount: u64,
+    max_uses: u32,
+    expires_at: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let promo_code_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    if discount_bps == 0 || discount_bps > MAX_PROMO_DISCOUNT_BPS || max_uses == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if expires_at <= clock.unix_timestamp {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check the promo code PDA
+    let (promo_pda, promo_bump) = Pubkey::find_program_address(
+        &[PROMO_CODE_SEED, &code_hash],
+        program_id,
+    );
+
+    if *promo_code_account.key != promo_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the promo code account
+    let rent = Rent::get()?;
+    let space = PromoCodeAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &promo_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[authority.clone(), promo_code_account.clone(), system_program.clone()],
+        &[&[PROMO_CODE_SEED, &code_hash, &[promo_bump]]],
+    )?;
+
+    let promo_data = PromoCodeAccount {
+        authority: *authority.key,
+        code_hash,
+        mint: *mint_account.key,
+        discount_bps,
+        max_discount,
+        max_uses,
+        use_count: 0,
+        expires_at,
+        bump: promo_bump,
+    };
+
+    promo_data.serialize(&mut *promo_code_account.data.borrow_mut())?;
+
+    msg!("Promo code created: {} bps, {} uses, expires at {}", discount_bps, max_uses, expires_at);
+    Ok(())
+}
+
+// Redeem a promo code by revealing it; the discount applies to the user's next FlexFi spend
+pub fn process_redeem_promo_code(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    preimage: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authorization_account = next_account_info(account_info_iter)?;
+    let promo_code_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if preimage.is_empty() || preimage.len() > MAX_PROMO_CODE_LEN {
+        return Err(FlexfiError::InvalidPromoCode.into());
+    }
+
+    let mut authorization = AuthorizationAccount::try_from_slice(
+        &authorization_account.data.borrow()
+    )?;
+
+    if authorization.user != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // One pending promo at a time; redeeming again would burn a use the next spend never consumes
+    if authorization.has_promo() {
+        return Err(FlexfiError::InvalidPromoCode.into());
+    }
+
+    let mut promo_data = load_promo_code(program_id, promo_code_account)?;
+
+    // Check the revealed code against the stored hash
+    if hashv(&[&preimage]).to_bytes() != promo_data.code_hash {
+        return Err(FlexfiError::InvalidPromoCode.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if !promo_data.is_redeemable(clock.unix_timestamp) {
+        return Err(FlexfiError::InvalidPromoCode.into());
+    }
+
+    // Reserve one use for this user
+    promo_data.use_count += 1;
+    promo_data.serialize(&mut *promo_code_account.data.borrow_mut())?;
+
+    authorization.promo_code = *promo_code_account.key;
+    authorization.serialize(&mut *authorization_account.data.borrow_mut())?;
+
+    msg!("Promo code redeemed ({}/{} uses)", promo_data.use_count, promo_data.max_uses);
+    Ok(())
+}
+
+pub struct PromoManager;
+
+impl PromoManager {
+    pub fn create(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        code_hash: [u8; 32],
+        discount_bps: u16,
+        max_discount: u64,
+        max_uses: u32,
+        expires_at: i64,
+    ) -> ProgramResult {
+        process_create_promo_code(program_id, accounts, code_hash, discount_bps, max_discount, max_uses, expires_at)
+    }
+
+    pub fn redeem(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        preimage: Vec<u8>,
+    ) -> ProgramResult {
+        process_redeem_promo_code(program_id, accounts, preimage)
+    }
+}
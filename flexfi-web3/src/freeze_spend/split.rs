@@ -0,0 +1,315 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+    program_error::ProgramError,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::instructions::SplitShare;
+use crate::state::authorization::AuthorizationAccount;
+use crate::state::merchant::MerchantAccount;
+use crate::state::staking::StakingAccount;
+use crate::constants::{MAX_SPLIT_PARTICIPANTS, STAKING_SEED, TREASURY_SEED, INSURANCE_FEE_SHARE_BPS};
+use crate::bnpl::micro::{check_treasury_token_account, get_merchant_settlement_token};
+use crate::insurance::reserve::{check_reserve_token_account, get_insurance_share};
+use crate::merchant::registry::load_merchant;
+use crate::merchant::settlement::record_merchant_settlement;
+use crate::token::transfer_checked;
+use crate::guard::require_allowed_user;
+
+// Accounts backing a financed share: the user's authorization and the staking position it draws on
+struct Financing<'a, 'b> {
+    authorization_account: &'b AccountInfo<'a>,
+    staking_account: &'b AccountInfo<'a>,
+}
+
+// One participant: the signing key and the user it acts for, the token account their share is paid
+// from (the signer's own, or the treasury for a financed share) and the financing accounts
+struct Participant<'a, 'b> {
+    user_account: &'b AccountInfo<'a>,
+    user: Pubkey,
+    source_account: &'b AccountInfo<'a>,
+    financing: Option<Financing<'a, 'b>>,
+}
+
+// Split a share between the merchant and the treasury: the merchant fee, with its rate lowered by the
+// lock discount of a financed share, never exceeds the share
+fn share_payout(merchant_data: &MerchantAccount, amount: u64, discount_bps: u16) -> (u64, u64) {
+    let fee = std::cmp::min(merchant_data.get_discounted_fee(amount, discount_bps), amount);
+    (amount - fee, fee)
+}
+
+// Pay one merchant purchase with shares from several users, settled in one instruction.
+// Each share is paid from the user's token account, or fronted by the treasury against the user's
+// FlexFi authorization, the stake staying in its vault. The merchant must be active in the registry
+// and its fee is withheld from every share for the treasury and the insurance reserve, as for a
+// FlexFi spend.
+pub fn process_create_split_purchase(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    merchant: Pubkey,
+    shares: Vec<SplitShare>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let merchant_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let merchant_settlement_account = next_account_info(account_info_iter)?;
+    let merchant_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?; // Receives the merchant fee
+    let insurance_token_account = next_account_info(account_info_iter)?; // Receives the insurance slice of the fee
+    let treasury_authority = next_account_info(account_info_iter)?; // Signs for the treasury on financed shares
+
+    if shares.len() < 2 || shares.len() > MAX_SPLIT_PARTICIPANTS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    // Only active registered merchants can be paid, to their settlement account
+    let merchant_data = load_merchant(program_id, merchant_account)?;
+
+    if merchant_data.owner != merchant {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !merchant_data.is_active(current_time) {
+        return Err(FlexfiError::MerchantNotActive.into());
+    }
+
+    if *merchant_token_account.key != get_merchant_settlement_token(&merchant_data, mint.key, token_program.key) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let treasury_bump = check_treasury_token_account(program_id, mint.key, token_program.key, treasury_token_account)?;
+    check_reserve_token_account(program_id, mint.key, token_program.key, insurance_token_account)?;
+
+    // Collect each participant's accounts
+    let mut participants: Vec<Participant> = Vec::with_capacity(shares.len());
+    for share in shares.iter() {
+        let user_account = next_account_info(account_info_iter)?; // The user or one of their device keys
+        let user_status_account = next_account_info(account_info_iter)?;
+
+        if !user_account.is_signer {
+            return Err(FlexfiError::Unauthorized.into());
+        }
+
+        // Every participant must be whitelisted and not blocklisted
+        let user = require_allowed_user(program_id, user_account, user_status_account, accounts)?;
+
+        if share.amount == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if participants.iter().any(|participant| participant.user == user) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if share.financed {
+            let authorization_account = next_account_info(account_info_iter)?;
+            let staking_account = next_account_info(account_info_iter)?;
+            participants.push(Participant {
+                user_account,
+                user,
+                source_account: treasury_token_account,
+                financing: Some(Financing { authorization_account, staking_account }),
+            });
+        } else {
+            let user_token_account = next_account_info(account_info_iter)?;
+            participants.push(Participant { user_account, user, source_account: user_token_account, financing: None });
+        }
+    }
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    let mut total: u64 = 0;
+    let mut total_fee: u64 = 0;
+    for (share, participant) in shares.iter().zip(participants.iter()) {
+        let user_account = participant.user_account;
+        let source_account = participant.source_account;
+
+        match &participant.financing {
+            Some(financing) => {
+                // Financed share: draw on the user's FlexFi credit
+                let authorization_account = financing.authorization_account;
+                let staking_account = financing.staking_account;
+
+                if authorization_account.owner != program_id {
+                    return Err(ProgramError::IncorrectProgramId);
+                }
+
+                let mut authorization = AuthorizationAccount::try_from_slice(
+                    &authorization_account.data.borrow()
+                )?;
+
+                if authorization.user != participant.user || !authorization.is_valid(current_time) {
+                    return Err(FlexfiError::Unauthorized.into());
+                }
+
+                if authorization.available_credit(current_time) < share.amount {
+                    return Err(FlexfiError::InsufficientCollateral.into());
+                }
+
+                if staking_account.owner != program_id {
+                    return Err(ProgramError::IncorrectProgramId);
+                }
+
+                let staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+                if staking_data.owner != participant.user || staking_data.usdc_mint != *mint.key {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let (staking_pda, _) = Pubkey::find_program_address(
+                    &[STAKING_SEED, participant.user.as_ref(), mint.key.as_ref(), &[staking_data.position_id]],
+                    program_id
+                );
+
+                if *staking_account.key != staking_pda {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                // The treasury fronts the share and keeps the fee less the insurance slice; the stake
+                // stays in its vault and the share counts against the authorization
+                let treasury_seeds: &[&[u8]] = &[TREASURY_SEED, mint.key.as_ref(), &[treasury_bump]];
+                let (to_merchant, fee) = share_payout(&merchant_data, share.amount, staking_data.lock_fee_discount(current_time));
+
+                transfer_checked(
+                    token_program,
+                    source_account,
+                    mint,
+                    merchant_token_account,
+                    treasury_authority,
+                    hook_accounts,
+                    to_merchant,
+                    &[treasury_seeds],
+                )?;
+
+                let insurance_share = get_insurance_share(fee, INSURANCE_FEE_SHARE_BPS);
+
+                if insurance_share > 0 {
+                    transfer_checked(
+                        token_program,
+                        source_account,
+                        mint,
+                        insurance_token_account,
+                        treasury_authority,
+                        hook_accounts,
+                        insurance_share,
+                        &[treasury_seeds],
+                    )?;
+                }
+
+                authorization.used_amount = authorization.used_amount.saturating_add(share.amount);
+                authorization.serialize(&mut *authorization_account.data.borrow_mut())?;
+
+                total_fee = total_fee.saturating_add(fee);
+            },
+            None => {
+                // Direct share: paid by the user
+                let (to_merchant, fee) = share_payout(&merchant_data, share.amount, 0);
+
+                transfer_checked(
+                    token_program,
+                    source_account,
+                    mint,
+                    merchant_token_account,
+                    user_account,
+                    hook_accounts,
+                    to_merchant,
+                    &[],
+                )?;
+
+                let insurance_share = get_insurance_share(fee, INSURANCE_FEE_SHARE_BPS);
+
+                if fee > insurance_share {
+                    transfer_checked(
+                        token_program,
+                        source_account,
+                        mint,
+                        treasury_token_account,
+                        user_account,
+                        hook_accounts,
+                        fee - insurance_share,
+                        &[],
+                    )?;
+                }
+
+                if insurance_share > 0 {
+                    transfer_checked(
+                        token_program,
+                        source_account,
+                        mint,
+                        insurance_token_account,
+                        user_account,
+                        hook_accounts,
+                        insurance_share,
+                        &[],
+                    )?;
+                }
+
+                total_fee = total_fee.saturating_add(fee);
+            },
+        }
+
+        total = total.checked_add(share.amount).ok_or(FlexfiError::MathOverflow)?;
+    }
+
+    // Credit the merchant's settlement totals for this epoch
+    record_merchant_settlement(program_id, merchant_settlement_account, &merchant, clock.epoch, total, total_fee)?;
+
+    msg!("Split purchase settled: {} from {} participants to merchant {}, {} fee at {} bps ({})",
+        total, shares.len(), merchant, total_fee, merchant_data.fee_rate_bps(),
+        if merchant_data.has_fee_override() { "negotiated" } else { "standard" });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merchant(fee_bps: u16) -> MerchantAccount {
+        let mut merchant_data = MerchantAccount::new(Pubkey::new_unique(), 0, 0, 0, 255);
+        merchant_data.fee_bps = fee_bps;
+        merchant_data
+    }
+
+    #[test]
+    fn payout_withholds_the_merchant_fee() {
+        assert_eq!(share_payout(&merchant(300), 10_000, 0), (9_700, 300));
+        assert_eq!(share_payout(&merchant(0), 10_000, 0), (10_000, 0));
+    }
+
+    #[test]
+    fn payout_rounds_the_fee_up() {
+        assert_eq!(share_payout(&merchant(300), 10_001, 0), (9_700, 301));
+        assert_eq!(share_payout(&merchant(300), 1, 0), (0, 1));
+    }
+
+    #[test]
+    fn payout_applies_the_lock_discount() {
+        assert_eq!(share_payout(&merchant(300), 10_000, 100), (9_800, 200));
+        assert_eq!(share_payout(&merchant(300), 10_000, 500), (10_000, 0));
+    }
+
+    #[test]
+    fn payout_fee_never_exceeds_the_share() {
+        assert_eq!(share_payout(&merchant(u16::MAX), 10_000, 0), (0, 10_000));
+        assert_eq!(share_payout(&merchant(300), 0, 0), (0, 0));
+    }
+
+    #[test]
+    fn payout_prefers_the_negotiated_fee() {
+        let mut merchant_data = merchant(300);
+        merchant_data.fee_override_bps = 100;
+
+        assert_eq!(share_payout(&merchant_data, 10_000, 0), (9_900, 100));
+        assert_eq!(share_payout(&merchant_data, 10_000, 50), (9_950, 50));
+    }
+}
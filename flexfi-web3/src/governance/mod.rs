@@ -0,0 +1,9 @@
+pub mod proposal;
+
+pub use proposal::{
+    process_initialize_governance,
+    process_create_proposal,
+    process_cast_vote,
+    process_queue_proposal,
+    process_execute_proposal,
+};
@@ -0,0 +1,511 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::instructions::{FlexfiInstruction, decode_instruction};
+use crate::state::governance::{GovernanceAccount, ProposalAccount, ProposalStatus, VoteRecordAccount};
+use crate::state::staking::{StakingAccount, StakingStatus};
+use crate::constants::{
+    GOVERNANCE_SEED, PROPOSAL_SEED, VOTE_RECORD_SEED, STAKING_SEED,
+    MAX_PROPOSAL_DATA_LEN, GOVERNANCE_VOTING_PERIOD_DAYS, GOVERNANCE_EXECUTION_DELAY_DAYS,
+    GOVERNANCE_MIN_PROPOSAL_STAKE,
+};
+use crate::core::whitelist::require_whitelist_authority;
+
+// Load the governance account after checking its PDA
+fn load_governance(
+    program_id: &Pubkey,
+    governance_account: &AccountInfo,
+) -> Result<GovernanceAccount, ProgramError> {
+    let (governance_pda, _) = Pubkey::find_program_address(&[GOVERNANCE_SEED], program_id);
+
+    if *governance_account.key != governance_pda || governance_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    GovernanceAccount::try_from_slice(&governance_account.data.borrow())
+        .map_err(|_| ProgramError::UninitializedAccount)
+}
+
+// Load a proposal after checking its PDA
+fn load_proposal(
+    program_id: &Pubkey,
+    proposal_account: &AccountInfo,
+) -> Result<ProposalAccount, ProgramError> {
+    if proposal_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let proposal_data = ProposalAccount::try_from_slice(&proposal_account.data.borrow())?;
+
+    let (proposal_pda, _) = Pubkey::find_program_address(
+        &[PROPOSAL_SEED, &proposal_data.proposal_id.to_le_bytes()],
+        program_id,
+    );
+
+    if *proposal_account.key != proposal_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(proposal_data)
+}
+
+// Only parameter updates can be proposed
+fn is_governable(instruction: &FlexfiInstruction) -> bool {
+    matches!(
+        instruction,
+        FlexfiInstruction::SetCollateralRatio { .. }
+            | FlexfiInstruction::SetTreasuryDeployCap { .. }
+            | FlexfiInstruction::SetMicroBNPLConfig { .. }
+            | FlexfiInstruction::SetMinBNPLScore { .. }
+            | FlexfiInstruction::SetRegionGating { .. }
+            | FlexfiInstruction::SetRegionEnabled { .. }
+            | FlexfiInstruction::SetStakingRewardAPY { .. }
+            | FlexfiInstruction::SetEmergencyWithdrawPenalty { .. }
+            | FlexfiInstruction::SetYieldPerformanceFee { .. }
+            | FlexfiInstruction::SetKeeperBounty { .. }
+            | FlexfiInstruction::SetForgivenessBudget { .. }
+            | FlexfiInstruction::SetMerchantFeeOverride { .. }
+            | FlexfiInstruction::SetConfigAuthority { .. }
+    )
+}
+
+// Create the governance account (called once by an admin)
+pub fn process_initialize_governance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    quorum_votes: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let governance_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    // Check the governance PDA
+    let (governance_pda, governance_bump) = Pubkey::find_program_address(&[GOVERNANCE_SEED], program_id);
+
+    if *governance_account.key != governance_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the governance account
+    let rent = Rent::get()?;
+    let space = GovernanceAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &governance_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[authority.clone(), governance_account.clone(), system_program.clone()],
+        &[&[GOVERNANCE_SEED, &[governance_bump]]],
+    )?;
+
+    let governance_data = GovernanceAccount {
+        authority: *authority.key,
+        proposal_count: 0,
+        voting_period: GOVERNANCE_VOTING_PERIOD_DAYS * 86400,
+        execution_delay: GOVERNANCE_EXECUTION_DELAY_DAYS * 86400,
+        quorum_votes,
+        bump: governance_bump,
+    };
+
+    governance_data.serialize(&mut *governance_account.data.borrow_mut())?;
+
+    msg!("Governance initialized, quorum {}", quorum_votes);
+    Ok(())
+}
+
+// Propose a config update; votes are weighted by the stake locked until voting ends. The proposer must
+// have at least GOVERNANCE_MIN_PROPOSAL_STAKE locked until then, pinned like a vote.
+pub fn process_create_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let proposal_account = next_account_info(account_info_iter)?;
+    let governance_account = next_account_info(account_info_iter)?;
+    let proposer_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let staking_accounts = account_info_iter.as_slice(); // The proposer's staking positions
+
+    // Check proposer signature
+    if !proposer_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check the proposed instruction
+    if instruction_data.is_empty() || instruction_data.len() > MAX_PROPOSAL_DATA_LEN {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if !is_governable(&decode_instruction(&instruction_data)?) {
+        msg!("Only config updates can be proposed");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut governance_data = load_governance(program_id, governance_account)?;
+    let proposal_id = governance_data.proposal_count;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let voting_ends_at = clock.unix_timestamp + governance_data.voting_period;
+
+    let proposer_stake = lock_vote_weight(program_id, proposer_account.key, staking_accounts, voting_ends_at)?;
+
+    if proposer_stake < GOVERNANCE_MIN_PROPOSAL_STAKE {
+        msg!("Proposing takes {} locked until voting ends, {} is", GOVERNANCE_MIN_PROPOSAL_STAKE, proposer_stake);
+        return Err(FlexfiError::InsufficientStaking.into());
+    }
+
+    // Check the proposal PDA
+    let (proposal_pda, proposal_bump) = Pubkey::find_program_address(
+        &[PROPOSAL_SEED, &proposal_id.to_le_bytes()],
+        program_id,
+    );
+
+    if *proposal_account.key != proposal_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the proposal account
+    let rent = Rent::get()?;
+    let space = ProposalAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            proposer_account.key,
+            &proposal_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[proposer_account.clone(), proposal_account.clone(), system_program.clone()],
+        &[&[PROPOSAL_SEED, &proposal_id.to_le_bytes(), &[proposal_bump]]],
+    )?;
+
+    let mut stored_data = [0u8; MAX_PROPOSAL_DATA_LEN];
+    stored_data[..instruction_data.len()].copy_from_slice(&instruction_data);
+
+    let proposal_data = ProposalAccount {
+        proposer: *proposer_account.key,
+        proposal_id,
+        instruction_len: instruction_data.len() as u8,
+        instruction_data: stored_data,
+        votes_for: 0,
+        votes_against: 0,
+        voting_ends_at,
+        executable_at: 0,
+        status: ProposalStatus::Voting.to_u8(),
+        bump: proposal_bump,
+    };
+
+    proposal_data.serialize(&mut *proposal_account.data.borrow_mut())?;
+
+    governance_data.proposal_count += 1;
+    governance_data.serialize(&mut *governance_account.data.borrow_mut())?;
+
+    msg!("Proposal {} created, voting ends at {}", proposal_id, proposal_data.voting_ends_at);
+    Ok(())
+}
+
+// Sum the voter's stake locked until voting ends, pinning it so the lock can't be broken during the vote
+fn lock_vote_weight(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    staking_accounts: &[AccountInfo],
+    voting_ends_at: i64,
+) -> Result<u64, ProgramError> {
+    if staking_accounts.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    // Sum the positions, each counted once
+    let mut weight: u64 = 0;
+    for (index, staking_account) in staking_accounts.iter().enumerate() {
+        if staking_accounts[..index].iter().any(|other| other.key == staking_account.key) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if staking_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+        let (staking_pda, _) = Pubkey::find_program_address(
+            &[STAKING_SEED, voter.as_ref(), staking_data.usdc_mint.as_ref(), &[staking_data.position_id]],
+            program_id,
+        );
+
+        if *staking_account.key != staking_pda || staking_data.owner != *voter {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Only locked positions carry voting power
+        if staking_data.get_status()? != StakingStatus::Locked {
+            continue;
+        }
+
+        let locked = staking_data.locked_through(voting_ends_at);
+        if locked == 0 {
+            continue;
+        }
+
+        weight = weight.saturating_add(locked);
+
+        staking_data.vote_locked_until = std::cmp::max(staking_data.vote_locked_until, voting_ends_at);
+        staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+    }
+
+    Ok(weight)
+}
+
+// Vote with the weight of the voter's stake locked until voting ends
+pub fn process_cast_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    support: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let proposal_account = next_account_info(account_info_iter)?;
+    let vote_record_account = next_account_info(account_info_iter)?;
+    let voter_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let staking_accounts = account_info_iter.as_slice(); // The voter's staking positions
+
+    // Check voter signature
+    if !voter_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let mut proposal_data = load_proposal(program_id, proposal_account)?;
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if proposal_data.get_status()? != ProposalStatus::Voting
+        || clock.unix_timestamp >= proposal_data.voting_ends_at
+    {
+        return Err(FlexfiError::VotingClosed.into());
+    }
+
+    // A position has a single owner and its locked stake can't leave it before voting ends,
+    // so the same tokens can't weigh in the vote twice
+    let weight = lock_vote_weight(program_id, voter_account.key, staking_accounts, proposal_data.voting_ends_at)?;
+
+    if weight == 0 {
+        return Err(FlexfiError::InsufficientStaking.into());
+    }
+
+    // The vote record PDA can only be created once per voter
+    let (vote_record_pda, vote_record_bump) = Pubkey::find_program_address(
+        &[VOTE_RECORD_SEED, proposal_account.key.as_ref(), voter_account.key.as_ref()],
+        program_id,
+    );
+
+    if *vote_record_account.key != vote_record_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent = Rent::get()?;
+    let space = VoteRecordAccount::SIZE;
+    let rent_lamports = rent.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            voter_account.key,
+            &vote_record_pda,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[voter_account.clone(), vote_record_account.clone(), system_program.clone()],
+        &[&[VOTE_RECORD_SEED, proposal_account.key.as_ref(), voter_account.key.as_ref(), &[vote_record_bump]]],
+    )?;
+
+    let vote_record = VoteRecordAccount {
+        proposal: *proposal_account.key,
+        voter: *voter_account.key,
+        weight,
+        support,
+        bump: vote_record_bump,
+    };
+
+    vote_record.serialize(&mut *vote_record_account.data.borrow_mut())?;
+
+    if support {
+        proposal_data.votes_for = proposal_data.votes_for.saturating_add(weight);
+    } else {
+        proposal_data.votes_against = proposal_data.votes_against.saturating_add(weight);
+    }
+
+    proposal_data.serialize(&mut *proposal_account.data.borrow_mut())?;
+
+    msg!("Vote on proposal {}: {} with weight {}", proposal_data.proposal_id, support, weight);
+    Ok(())
+}
+
+// Close voting: queue a passed proposal behind the execution delay (permissionless)
+pub fn process_queue_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let proposal_account = next_account_info(account_info_iter)?;
+    let governance_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    let governance_data = load_governance(program_id, governance_account)?;
+    let mut proposal_data = load_proposal(program_id, proposal_account)?;
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if proposal_data.get_status()? != ProposalStatus::Voting {
+        return Err(FlexfiError::VotingClosed.into());
+    }
+
+    if clock.unix_timestamp < proposal_data.voting_ends_at {
+        return Err(FlexfiError::ProposalNotReady.into());
+    }
+
+    let total_votes = proposal_data.votes_for.saturating_add(proposal_data.votes_against);
+
+    if proposal_data.votes_for > proposal_data.votes_against && total_votes >= governance_data.quorum_votes {
+        proposal_data.executable_at = clock.unix_timestamp + governance_data.execution_delay;
+        proposal_data.set_status(ProposalStatus::Queued);
+        msg!("Proposal {} queued, executable at {}", proposal_data.proposal_id, proposal_data.executable_at);
+    } else {
+        proposal_data.set_status(ProposalStatus::Defeated);
+        msg!("Proposal {} defeated", proposal_data.proposal_id);
+    }
+
+    proposal_data.serialize(&mut *proposal_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// Execute a queued proposal by invoking its config instruction with the governance PDA as signer
+pub fn process_execute_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let proposal_account = next_account_info(account_info_iter)?;
+    let governance_account = next_account_info(account_info_iter)?;
+    let flexfi_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let target_accounts = account_info_iter.as_slice(); // Accounts of the proposed instruction
+
+    if flexfi_program.key != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let governance_data = load_governance(program_id, governance_account)?;
+    let mut proposal_data = load_proposal(program_id, proposal_account)?;
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if proposal_data.get_status()? != ProposalStatus::Queued
+        || clock.unix_timestamp < proposal_data.executable_at
+    {
+        return Err(FlexfiError::ProposalNotReady.into());
+    }
+
+    // Mark executed first so the proposal can't run twice
+    proposal_data.set_status(ProposalStatus::Executed);
+    proposal_data.serialize(&mut *proposal_account.data.borrow_mut())?;
+
+    let account_metas = target_accounts
+        .iter()
+        .map(|account| {
+            let is_signer = account.is_signer || account.key == governance_account.key;
+            if account.is_writable {
+                AccountMeta::new(*account.key, is_signer)
+            } else {
+                AccountMeta::new_readonly(*account.key, is_signer)
+            }
+        })
+        .collect();
+
+    let instruction = Instruction {
+        program_id: *program_id,
+        accounts: account_metas,
+        data: proposal_data.instruction().to_vec(),
+    };
+
+    let mut account_infos = target_accounts.to_vec();
+    account_infos.push(flexfi_program.clone());
+
+    invoke_signed(
+        &instruction,
+        &account_infos,
+        &[&[GOVERNANCE_SEED, &[governance_data.bump]]],
+    )?;
+
+    msg!("Proposal {} executed", proposal_data.proposal_id);
+    Ok(())
+}
+
+pub struct GovernanceManager;
+
+impl GovernanceManager {
+    pub fn initialize(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        quorum_votes: u64,
+    ) -> ProgramResult {
+        process_initialize_governance(program_id, accounts, quorum_votes)
+    }
+
+    pub fn propose(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: Vec<u8>,
+    ) -> ProgramResult {
+        process_create_proposal(program_id, accounts, instruction_data)
+    }
+
+    pub fn vote(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        support: bool,
+    ) -> ProgramResult {
+        process_cast_vote(program_id, accounts, support)
+    }
+
+    pub fn queue(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_queue_proposal(program_id, accounts)
+    }
+
+    pub fn execute(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_execute_proposal(program_id, accounts)
+    }
+}
@@ -0,0 +1,115 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::instructions as instructions_sysvar,
+    msg,
+};
+use borsh::BorshDeserialize;
+
+use crate::instructions::FlexfiInstruction;
+use crate::state::whitelist::UserWhitelistStatus;
+use crate::core::whitelist::{require_whitelisted, is_wallet_account};
+use crate::core::blocklist::require_not_blocklisted;
+use crate::constants::WHITELIST_SEED;
+use crate::error::FlexfiError;
+
+// Positions of the (user, user status) accounts for instructions reserved to whitelisted users;
+// the instructions sysvar (attestation) or the user's wallet (cached approval) may take the user status slot.
+// CreateSplitPurchase has one pair per participant, checked by its handler with require_allowed_user.
+// Borrower registries are only created through CreateMicroBNPL. The user instructions left out open no new
+// credit: they manage or wind down what the user already holds (closing positions, savings goal transfers,
+// revoking access) or vote with existing stake, so a user dropped from the whitelist can still exit.
+fn get_whitelisted_user_accounts(instruction: &FlexfiInstruction) -> Option<(usize, usize)> {
+    match instruction {
+        FlexfiInstruction::DepositStaking { .. }
+        | FlexfiInstruction::WithdrawStaking { .. }
+        | FlexfiInstruction::EmergencyWithdrawStaking { .. }
+        | FlexfiInstruction::ExtendStakingLock { .. }
+        | FlexfiInstruction::ClaimStakingRewards
+        | FlexfiInstruction::InitializeFlexFiAccount { .. }
+        | FlexfiInstruction::CreateSavingsGoal { .. }
+        | FlexfiInstruction::InitializeScore
+        | FlexfiInstruction::SetCardCommitment { .. }
+        | FlexfiInstruction::SetYieldStrategy { .. }
+        | FlexfiInstruction::RestakeYield { .. }
+        | FlexfiInstruction::CreateWallet
+        | FlexfiInstruction::ReactivateWallet
+        | FlexfiInstruction::CreateMicroBNPL { .. }
+        | FlexfiInstruction::FlexFiSpend { .. }
+        | FlexfiInstruction::RepayMicroBNPL
+        | FlexfiInstruction::PrepayBNPL { .. } => Some((1, 2)),
+        FlexfiInstruction::ClaimYield { .. } => Some((1, 3)),
+        FlexfiInstruction::UpgradeCard { .. }
+        | FlexfiInstruction::CreateDepositEscrow { .. }
+        | FlexfiInstruction::RedeemPromoCode { .. } => Some((2, 3)),
+        FlexfiInstruction::MintNFT { .. }
+        | FlexfiInstruction::AttachNFT { .. } => Some((3, 4)),
+        _ => None,
+    }
+}
+
+// Guarded instructions a registered device key may sign; their handlers act for the primary user through
+// resolve_acting_user. Every other handler derives PDAs from the signer, so only the user signs them.
+// CreateSplitPurchase also accepts device keys, resolved per participant by require_allowed_user.
+fn allows_device_keys(instruction: &FlexfiInstruction) -> bool {
+    matches!(
+        instruction,
+        FlexfiInstruction::FlexFiSpend { .. } | FlexfiInstruction::RepayMicroBNPL
+    )
+}
+
+// Check that the user is whitelisted and not blocklisted, and return the user it acts for:
+// the primary user when a device key signs, the user itself otherwise
+pub fn require_allowed_user(
+    program_id: &Pubkey,
+    user_account: &AccountInfo,
+    user_status_account: &AccountInfo,
+    accounts: &[AccountInfo],
+) -> Result<Pubkey, ProgramError> {
+    // The global whitelist must be passed with every guarded instruction, so its switch always applies
+    let (whitelist_pda, _) = Pubkey::find_program_address(&[WHITELIST_SEED], program_id);
+    let whitelist_account = accounts.iter()
+        .find(|account| account.key == &whitelist_pda)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    require_whitelisted(program_id, user_account.key, user_status_account, whitelist_account)?;
+
+    // A wallet cache only serves its owner, and was refreshed after the last blocklisting,
+    // which bumps the whitelist version
+    if is_wallet_account(program_id, user_status_account) {
+        return Ok(*user_account.key);
+    }
+
+    // A blocklist entry overrides whitelist membership; a device key is checked as the user it acts for
+    let primary_user = if instructions_sysvar::check_id(user_status_account.key) {
+        *user_account.key
+    } else {
+        UserWhitelistStatus::try_from_slice(&user_status_account.data.borrow())?.user_pubkey
+    };
+    require_not_blocklisted(program_id, &primary_user, accounts)?;
+
+    Ok(primary_user)
+}
+
+// Checks shared by every instruction, run once before dispatching to the handler
+pub fn check_instruction_guards(
+    program_id: &Pubkey,
+    instruction: &FlexfiInstruction,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    if let Some((user_index, status_index)) = get_whitelisted_user_accounts(instruction) {
+        let user_account = accounts.get(user_index).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let user_status_account = accounts.get(status_index).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        let primary_user = require_allowed_user(program_id, user_account, user_status_account, accounts)?;
+
+        if primary_user != *user_account.key && !allows_device_keys(instruction) {
+            msg!("Device keys of {} can't sign this instruction", primary_user);
+            return Err(FlexfiError::Unauthorized.into());
+        }
+    }
+
+    Ok(())
+}
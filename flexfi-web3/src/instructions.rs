@@ -5,6 +5,13 @@ use solana_program::{
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 
+// One participant's part of a split purchase
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
+pub struct SplitShare {
+    pub amount: u64,
+    pub financed: bool, // Paid through the participant's FlexFi authorization
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
 pub enum FlexfiInstruction {
     // Core instructions
@@ -12,6 +19,8 @@ pub enum FlexfiInstruction {
     DepositStaking {
         amount: u64,
         lock_days: u16,
+        position_id: u8,
+        vesting: bool,
     },
     WithdrawStaking {
         amount: u64,
@@ -68,6 +77,454 @@ pub enum FlexfiInstruction {
         amount: u64,
         merchant: Pubkey,
     },
+
+    // Staking rewards and locks
+    ClaimStakingRewards,
+    ExtendStakingLock {
+        additional_days: u16,
+    },
+
+    // Protocol status
+    GetProtocolStatus,
+
+    // Emergency staking withdrawal
+    EmergencyWithdrawStaking {
+        amount: u64,
+    },
+
+    // Accepted collateral mints
+    InitializeAcceptedMints,
+    AddAcceptedMint {
+        mint: Pubkey,
+    },
+    RemoveAcceptedMint {
+        mint: Pubkey,
+    },
+
+    // Staking administration
+    FreezeStaking {
+        reason: u8,
+    },
+    UnfreezeStaking,
+
+    // Staking account closing
+    CloseStakingAccount,
+
+    // Permissionless unlock crank
+    CheckUnlockStatus,
+
+    // Staking delegation
+    SetStakingDelegate {
+        delegate: Pubkey,
+    },
+
+    // Protocol config
+    InitializeConfig,
+    SetCollateralRatio {
+        card_type: u8,
+        score_tier: u8,
+        ltv_bps: u16,
+    },
+
+    // Yield restaking
+    RestakeYield {
+        amount: u64,
+    },
+
+    // Card verification commitment
+    SetCardCommitment {
+        verification_key: Pubkey,
+    },
+    VerifyCardCommitment,
+
+    // Governance snapshots
+    TakeStakingSnapshot,
+
+    // Insurance reserve
+    FundInsuranceReserve {
+        amount: u64,
+    },
+    CoverInsuranceShortfall {
+        amount: u64,
+    },
+
+    // Loyalty tier review
+    ReviewCardTier,
+
+    // Merchant onboarding
+    RegisterMerchant,
+    VetoMerchant,
+    DeregisterMerchant,
+
+    // Deposit escrow
+    CreateDepositEscrow {
+        amount: u64,
+    },
+    ReleaseDepositEscrow {
+        to_beneficiary: u64,
+    },
+    ArbitrateDepositEscrow {
+        to_beneficiary: u64,
+    },
+
+    // Staking tiers
+    InitializeStakingTiers,
+    SetStakingTier {
+        index: u8,
+        min_staked: u64,
+        fee_discount_bps: u16,
+        max_bnpl_multiplier_bps: u16,
+    },
+
+    // Staking reward compounding
+    SetAutoCompound {
+        enabled: bool,
+    },
+    CompoundStakingRewards,
+
+    // Savings goals
+    CreateSavingsGoal {
+        name_hash: [u8; 32],
+        target_amount: u64,
+        target_date: i64,
+    },
+    DepositSavingsGoal {
+        amount: u64,
+    },
+    WithdrawSavingsGoal {
+        amount: u64,
+    },
+
+    // Spend round-up
+    SetSpendRoundUp {
+        savings_goal: Pubkey,
+    },
+
+    // Group payments
+    CreateSplitPurchase {
+        merchant: Pubkey,
+        shares: Vec<SplitShare>,
+    },
+
+    // Collateral price feeds
+    SetMintPriceFeed {
+        mint: Pubkey,
+        price_feed: Pubkey,
+    },
+
+    // Charitable donations
+    RegisterCharity,
+    DeactivateCharity,
+    SetSpendDonation {
+        donation_bps: u16,
+    },
+
+    // Wallet lifecycle
+    /// Accounts: [wallet (w), user (s, w), user_status, system_program, clock]
+    CreateWallet,
+    /// Accounts: [wallet (w), owner (s)]
+    DeactivateWallet,
+    /// Accounts: [wallet (w), owner (s), user_status]
+    ReactivateWallet,
+
+    // Wallet social recovery
+    SetWalletGuardians {
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+    },
+    ApproveWalletRecovery {
+        new_owner: Pubkey,
+    },
+    CancelWalletRecovery,
+    RecoverWallet,
+    MigrateRecoveredProfile,
+    MigrateRecoveredStaking,
+
+    // Backend id linkage
+    LinkBackendId {
+        backend_id_hash: [u8; 32],
+    },
+
+    // Wallet closing
+    CloseWallet,
+
+    // Consent-based data sharing
+    GrantDataAccess {
+        viewer: Pubkey,
+        scope: u8,
+        expiry: i64,
+    },
+    RevokeDataAccess,
+
+    // Governance
+    SetConfigAuthority {
+        new_authority: Pubkey,
+    },
+    InitializeGovernance {
+        quorum_votes: u64,
+    },
+    CreateProposal {
+        instruction_data: Vec<u8>,
+    },
+    CastVote {
+        support: bool,
+    },
+    QueueProposal,
+    ExecuteProposal,
+
+    // Epoch reward emissions
+    SetEmissionsSchedule {
+        emission_per_epoch: u64,
+        staking_share_bps: u16,
+        repayment_share_bps: u16,
+        liquidity_share_bps: u16,
+    },
+    DistributeEpochRewards {
+        epoch: u64,
+    },
+
+    // Promo codes
+    CreatePromoCode {
+        code_hash: [u8; 32],
+        discount_bps: u16,
+        max_discount: u64,
+        max_uses: u32,
+        expires_at: i64,
+    },
+    RedeemPromoCode {
+        preimage: Vec<u8>,
+    },
+
+    // Merchant settlement reporting
+    GetMerchantSettlementSummary {
+        epoch: u64,
+    },
+
+    // Role-based admin list
+    InitializeAdminList,
+    SetAdminRoles {
+        admin: Pubkey,
+        roles: u8,
+    },
+
+    // Treasury yield deployment
+    SetTreasuryDeployCap {
+        cap_bps: u16,
+    },
+    DeployTreasury {
+        amount: u64,
+    },
+    RecallTreasury {
+        amount: u64,
+    },
+
+    // Installment reminders
+    SendPaymentReminder,
+    SnoozePayment,
+
+    // Whitelist re-verification
+    RenewWhitelist {
+        user_pubkey: Pubkey,
+    },
+
+    // Merchant registry
+    ConfigureMerchant {
+        name_hash: [u8; 32],
+        settlement_token_account: Pubkey,
+        fee_bps: u16,
+        is_active: bool,
+    },
+
+    // Risk reporting
+    GetUserExposure,
+
+    // Fee-free micro-BNPL
+    SetMicroBNPLConfig {
+        threshold: u64,
+        ltv_bps: u16,
+    },
+    CreateMicroBNPL {
+        amount: u64,
+        escrow: bool,
+    },
+    RepayMicroBNPL,
+
+    // Whitelist switch
+    SetWhitelistActive {
+        is_active: bool,
+    },
+
+    // Vault housekeeping
+    SweepDust {
+        vault_kind: u8,
+    },
+
+    // Legal holds
+    PlaceLegalHold {
+        contract: Pubkey,
+    },
+    ReleaseLegalHold {
+        contract: Pubkey,
+    },
+
+    // Blocklist
+    AddToBlocklist {
+        user_pubkey: Pubkey,
+        reason: u8,
+    },
+    RemoveFromBlocklist {
+        user_pubkey: Pubkey,
+    },
+
+    // Parameter discovery
+    GetActiveParameters,
+
+    // Device/session keys on the whitelist status
+    AddAuthorizedKey {
+        key: Pubkey,
+    },
+    RemoveAuthorizedKey {
+        key: Pubkey,
+    },
+
+    // Reminder calendar anchor
+    AnchorInstallmentCalendar,
+
+    // Signature-based whitelisting
+    SetAttestationSigner {
+        signer: Pubkey,
+    },
+
+    // Per-user risk limits
+    SetUserRiskLimits {
+        user_pubkey: Pubkey,
+        max_bnpl_amount: u64,
+        max_active_contracts: u8,
+    },
+
+    // Regional rollout
+    SetUserRegion {
+        user_pubkey: Pubkey,
+        region: u16,
+    },
+    SetRegionGating {
+        enabled: bool,
+    },
+    SetRegionEnabled {
+        region: u16,
+        enabled: bool,
+    },
+
+    // Wallet whitelist cache
+    RefreshWhitelistCache,
+
+    // Strategy reconciliation
+    ReconcileStrategy {
+        reported_value: u64,
+    },
+    ResumeStrategy,
+
+    // Underwriting policy
+    SetMinBNPLScore {
+        card_type: u8,
+        min_score: u16,
+    },
+
+    // Partial prepayment
+    PrepayBNPL {
+        amount: u64,
+        mode: u8,
+    },
+
+    // Merchant refunds
+    RefundBNPLContract {
+        amount: u64,
+    },
+
+    // Escrowed merchant settlement
+    ReleaseEscrow,
+
+    // Staking reward rate
+    SetStakingRewardAPY {
+        apy_bps: u16,
+    },
+    GetAccruedRewards,
+
+    // Late fees
+    CheckRepayment,
+
+    // Streamed treasury withdrawals
+    WithdrawTreasury {
+        recipient: Pubkey,
+        amount: u64,
+        stream_id: u64,
+        stream_days: u16, // 0 for an instant withdrawal
+    },
+    ClaimTreasuryStream,
+    CancelTreasuryStream,
+
+    // GDPR data erasure
+    RequestDataErasure,
+    AcknowledgeDataErasure,
+
+    // Conversion of unlocked stake into the yield strategy
+    SetConvertOnUnlock {
+        enabled: bool,
+    },
+    ConvertUnlockedStaking,
+
+    // Merchant reassignment
+    ReassignMerchant {
+        contract: Pubkey,
+        new_merchant: Pubkey,
+    },
+
+    // Credit reservations for pending charges
+    CreatePendingCharge {
+        amount: u64,
+    },
+    CancelPendingCharge {
+        merchant: Pubkey,
+    },
+
+    // Stablecoin payment mints
+    SetPaymentMint {
+        mint: Pubkey,
+        enabled: bool,
+    },
+
+    // Penalty forgiveness
+    SetForgivenessBudget {
+        monthly_cap: u64,
+    },
+    ForgivePenalty {
+        contract: Pubkey,
+        amount: u64,
+    },
+
+    // Keeper bounties
+    SetKeeperBounty {
+        bounty_lamports: u64,
+    },
+
+    // Purchase receipts
+    IssuePurchaseReceipt,
+
+    // Yield performance fees
+    SetYieldPerformanceFee {
+        card_type: u8,
+        fee_bps: u16,
+    },
+
+    // Emergency withdrawals
+    SetEmergencyWithdrawPenalty {
+        penalty_bps: u16,
+    },
+
+    // Negotiated merchant fees
+    SetMerchantFeeOverride {
+        fee_override_bps: u16,
+    },
 }
 
 pub fn decode_instruction(instruction_data: &[u8]) -> Result<FlexfiInstruction, ProgramError> {
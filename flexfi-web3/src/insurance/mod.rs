@@ -0,0 +1,7 @@
+pub mod reserve;
+
+pub use reserve::{
+    process_fund_insurance_reserve,
+    process_cover_insurance_shortfall,
+    get_insurance_share,
+};
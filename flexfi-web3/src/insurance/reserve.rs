@@ -0,0 +1,310 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::admin::AdminRole;
+use crate::state::staking::StakingAccount;
+use crate::constants::{INSURANCE_RESERVE_SEED, MICRO_BNPL_INTERVAL_DAYS, USDC_VAULT_SEED};
+use crate::core::admin::require_admin_role;
+use crate::core::staking::{get_staking_reward_index, get_vault_bump};
+use crate::error_context::{key_mismatch, amount_mismatch};
+use crate::token::transfer_checked;
+use crate::math::{bps_share, Rounding};
+use crate::bnpl::micro::{load_open_micro_bnpl, check_treasury_token_account, close_micro_bnpl_account, release_stake_hold};
+use crate::bnpl::registry::record_registry_repayment;
+use crate::rewards::emissions::{record_epoch_activity, Activity, ActivityAccounts};
+
+// Slice of a fee or penalty that goes to the insurance reserve
+pub fn get_insurance_share(amount: u64, share_bps: u16) -> u64 {
+    bps_share(amount, share_bps, Rounding::Down)
+}
+
+// Check the reserve authority PDA (one reserve per mint)
+fn check_reserve_authority(
+    program_id: &Pubkey,
+    reserve_authority: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (reserve_pda, reserve_bump) = Pubkey::find_program_address(
+        &[INSURANCE_RESERVE_SEED, mint.as_ref()],
+        program_id,
+    );
+
+    if *reserve_authority.key != reserve_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(reserve_bump)
+}
+
+// Check the reserve's token account for a mint, which receives the insurance slice of fees and
+// penalties; returns the reserve authority bump
+pub(crate) fn check_reserve_token_account(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    reserve_token_account: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (reserve_pda, reserve_bump) = Pubkey::find_program_address(
+        &[INSURANCE_RESERVE_SEED, mint.as_ref()],
+        program_id,
+    );
+    let reserve_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &reserve_pda,
+        mint,
+        token_program,
+    );
+
+    if *reserve_token_account.key != reserve_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(reserve_bump)
+}
+
+// Move funds (BNPL fee or penalty slices) into the insurance reserve
+pub fn process_fund_insurance_reserve(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let payer_account = next_account_info(account_info_iter)?;
+    let payer_token_account = next_account_info(account_info_iter)?;
+    let reserve_authority = next_account_info(account_info_iter)?;
+    let reserve_token_account = next_account_info(account_info_iter)?;
+    let reserve_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let activity_accounts = ActivityAccounts {
+        schedule_account: next_account_info(account_info_iter)?,
+        epoch_activity_account: next_account_info(account_info_iter)?,
+        user_activity_account: next_account_info(account_info_iter)?,
+        system_program: next_account_info(account_info_iter)?,
+    };
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Check payer signature
+    if !payer_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    check_reserve_authority(program_id, reserve_authority, reserve_mint.key)?;
+    check_reserve_token_account(program_id, reserve_mint.key, token_program.key, reserve_token_account)?;
+
+    transfer_checked(
+        token_program,
+        payer_token_account,
+        reserve_mint,
+        reserve_token_account,
+        payer_account,
+        hook_accounts,
+        amount,
+        &[],
+    )?;
+
+    // Reserve funding earns liquidity emissions
+    record_epoch_activity(program_id, &activity_accounts, payer_account.key, payer_account, Activity::Liquidity(amount))?;
+
+    msg!("Insurance reserve funded: {} units", amount);
+    Ok(())
+}
+
+// Repay to the treasury what a defaulted micro loan owes on its overdue installment and its late fees
+// (admin only). The stake held for the loan is seized first, the reserve pays the rest. The cover
+// counts as repaid by the borrower: late fees are settled first, and partial covers add up until the
+// installment is paid and the next one falls due.
+pub fn process_cover_insurance_shortfall(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let micro_bnpl_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let reserve_authority = next_account_info(account_info_iter)?;
+    let reserve_token_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let reserve_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let borrower_account = next_account_info(account_info_iter)?; // Refunded the rent once the loan is repaid
+    let registry_account = next_account_info(account_info_iter)?; // The borrower's registry
+    let staking_account = next_account_info(account_info_iter)?; // Position backing the loan
+    let vault_authority = next_account_info(account_info_iter)?; // The position's vault PDA
+    let staking_vault_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?; // Staking rewards are accrued before the stake shrinks
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Verify the authority holds the treasurer role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Treasurer)?;
+
+    let mut micro_bnpl_data = load_open_micro_bnpl(program_id, micro_bnpl_account)?;
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    // Only loans overdue beyond their grace period can be covered
+    if !micro_bnpl_data.is_defaulted(current_time) {
+        return Err(FlexfiError::ContractNotDefaulted.into());
+    }
+
+    micro_bnpl_data.accrue_late_fees(current_time);
+    let owed = micro_bnpl_data.next_installment().saturating_add(micro_bnpl_data.accrued_late_fees);
+
+    if amount == 0 || amount > owed {
+        return Err(amount_mismatch(Some(micro_bnpl_account), owed, amount, ProgramError::InvalidArgument));
+    }
+
+    // Verify the mint, the reserve and the treasury
+    if *reserve_mint.key != micro_bnpl_data.token_mint || *borrower_account.key != micro_bnpl_data.borrower {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let reserve_bump = check_reserve_authority(program_id, reserve_authority, reserve_mint.key)?;
+    check_reserve_token_account(program_id, reserve_mint.key, token_program.key, reserve_token_account)?;
+    check_treasury_token_account(program_id, reserve_mint.key, token_program.key, treasury_token_account)?;
+
+    // The position backing the loan and its vault
+    if *staking_account.key != micro_bnpl_data.staking_account {
+        return Err(key_mismatch(staking_account, &micro_bnpl_data.staking_account, ProgramError::InvalidAccountData));
+    }
+
+    if staking_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    if staking_data.usdc_mint != *reserve_mint.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let vault_bump = get_vault_bump(program_id, staking_account.key, vault_authority.key)?;
+    let vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        vault_authority.key,
+        reserve_mint.key,
+        token_program.key,
+    );
+
+    if *staking_vault_account.key != vault_ata {
+        return Err(key_mismatch(staking_vault_account, &vault_ata, ProgramError::InvalidAccountData));
+    }
+
+    // The held stake goes to the treasury first
+    let from_stake = std::cmp::min(amount, std::cmp::min(micro_bnpl_data.stake_hold, staking_data.amount_staked));
+
+    if from_stake > 0 {
+        let reward_index = get_staking_reward_index(program_id, config_account, current_time)?;
+
+        staking_data.accrue_rewards(reward_index, current_time);
+        staking_data.release_locked(from_stake, current_time);
+        staking_data.amount_staked -= from_stake;
+        staking_data.loan_hold = staking_data.loan_hold.saturating_sub(from_stake);
+        staking_data.last_update = current_time;
+        staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+        micro_bnpl_data.stake_hold -= from_stake;
+
+        transfer_checked(
+            token_program,
+            staking_vault_account,
+            reserve_mint,
+            treasury_token_account,
+            vault_authority,
+            hook_accounts,
+            from_stake,
+            &[&[USDC_VAULT_SEED, staking_account.key.as_ref(), &[vault_bump]]],
+        )?;
+    }
+
+    // The reserve covers the rest
+    let from_reserve = amount - from_stake;
+
+    if from_reserve > 0 {
+        transfer_checked(
+            token_program,
+            reserve_token_account,
+            reserve_mint,
+            treasury_token_account,
+            reserve_authority,
+            hook_accounts,
+            from_reserve,
+            &[&[INSURANCE_RESERVE_SEED, reserve_mint.key.as_ref(), &[reserve_bump]]],
+        )?;
+    }
+
+    // Late fees first, then the installment; the loan stays defaulted until the installment is settled
+    let late_fees = std::cmp::min(amount, micro_bnpl_data.accrued_late_fees);
+    micro_bnpl_data.accrued_late_fees -= late_fees;
+
+    let repaid = amount - late_fees;
+
+    if repaid > 0 && micro_bnpl_data.pay_installment(repaid) {
+        micro_bnpl_data.days_overdue = 0;
+        micro_bnpl_data.next_payment_due += MICRO_BNPL_INTERVAL_DAYS * 86400;
+    }
+
+    release_stake_hold(program_id, &mut micro_bnpl_data, staking_account)?;
+    let settled = micro_bnpl_data.remaining_amount() == 0;
+
+    record_registry_repayment(
+        program_id,
+        borrower_account.key,
+        registry_account,
+        micro_bnpl_account.key,
+        repaid,
+        settled,
+        current_time,
+    )?;
+
+    if settled && micro_bnpl_data.escrowed_amount == 0 {
+        close_micro_bnpl_account(micro_bnpl_account, borrower_account)?;
+    } else {
+        micro_bnpl_data.serialize(&mut *micro_bnpl_account.data.borrow_mut())?;
+    }
+
+    msg!(
+        "Insurance covered {} ({} from the stake, {} late fees) of micro-BNPL {}, {} left on the installment",
+        amount,
+        from_stake,
+        late_fees,
+        micro_bnpl_account.key,
+        micro_bnpl_data.next_installment()
+    );
+    Ok(())
+}
+
+pub struct InsuranceReserve;
+
+impl InsuranceReserve {
+    pub fn fund(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        process_fund_insurance_reserve(program_id, accounts, amount)
+    }
+
+    pub fn cover_shortfall(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        process_cover_insurance_shortfall(program_id, accounts, amount)
+    }
+}
@@ -6,12 +6,22 @@ pub mod score;
 pub mod yield_module;
 pub mod state;
 pub mod freeze_spend;
+pub mod insurance;
+pub mod merchant;
+pub mod donation;
+pub mod governance;
+pub mod rewards;
 
 pub mod entrypoint;
 pub mod processor;
 pub mod error;
 pub mod constants;
 pub mod instructions;
+pub mod token;
+pub mod oracle;
+pub mod math;
+pub mod guard;
+pub mod error_context;
 
 
 pub use crate::core::staking;
@@ -27,6 +37,11 @@ pub use crate::yield_module::router;
 pub use crate::yield_module::tracker;
 
 pub use crate::freeze_spend::authorization;
+pub use crate::insurance::reserve;
+pub use crate::merchant::registry;
+pub use crate::donation::charity;
+pub use crate::governance::proposal;
+pub use crate::rewards::emissions;
 
 pub use crate::state::wallet::WalletAccount;
 pub use crate::state::staking::{StakingAccount, StakingStatus};
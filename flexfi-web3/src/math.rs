@@ -0,0 +1,200 @@
+// Fee, APR and penalty arithmetic shared by the program.
+// Products are taken in u128 so no amount can overflow before the division, and every
+// helper takes an explicit rounding mode: amounts the protocol charges (fees, penalties)
+// round up, amounts it pays out or lends against (discounts, shares, interest) round down.
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const SECONDS_PER_APR_YEAR: u64 = 365 * 86400;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+// value * numerator / denominator in u128; None on a zero denominator or overflow
+pub fn mul_div_u128(value: u128, numerator: u128, denominator: u128, rounding: Rounding) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+
+    let product = value.checked_mul(numerator)?;
+    let quotient = product / denominator;
+
+    match rounding {
+        Rounding::Up if product % denominator != 0 => quotient.checked_add(1),
+        _ => Some(quotient),
+    }
+}
+
+// value * numerator / denominator; None on a zero denominator or a result above u64::MAX
+pub fn mul_div(value: u64, numerator: u64, denominator: u64, rounding: Rounding) -> Option<u64> {
+    let result = mul_div_u128(value as u128, numerator as u128, denominator as u128, rounding)?;
+    u64::try_from(result).ok()
+}
+
+// amount * bps / 10000; bps may exceed 10000 (collateral ratios), hence the Option
+pub fn apply_bps(amount: u64, bps: u16, rounding: Rounding) -> Option<u64> {
+    mul_div(amount, bps as u64, BPS_DENOMINATOR, rounding)
+}
+
+// A share of at most 100% of an amount, which can't overflow
+pub fn bps_share(amount: u64, bps: u16, rounding: Rounding) -> u64 {
+    let bps = std::cmp::min(bps as u64, BPS_DENOMINATOR);
+    mul_div(amount, bps, BPS_DENOMINATOR, rounding).unwrap_or(amount)
+}
+
+// Fee charged on an amount, rounded up so dust amounts still pay the fee
+pub fn fee_amount(amount: u64, fee_bps: u16) -> u64 {
+    bps_share(amount, fee_bps, Rounding::Up)
+}
+
+// Penalty withheld from an amount, rounded up like a fee
+pub fn penalty_amount(amount: u64, penalty_bps: u16) -> u64 {
+    bps_share(amount, penalty_bps, Rounding::Up)
+}
+
+// Simple interest of an annual rate over elapsed seconds (365-day year)
+pub fn apr_interest(principal: u64, apr_bps: u16, elapsed_secs: i64, rounding: Rounding) -> Option<u64> {
+    if elapsed_secs <= 0 {
+        return Some(0);
+    }
+
+    let rate_time = (apr_bps as u128).checked_mul(elapsed_secs as u128)?;
+    let interest = mul_div_u128(
+        principal as u128,
+        rate_time,
+        (BPS_DENOMINATOR as u128) * (SECONDS_PER_APR_YEAR as u128),
+        rounding,
+    )?;
+
+    u64::try_from(interest).ok()
+}
+
+// Interest accrued over a span of a cumulative rate index (APR bps × seconds, 365-day year);
+// exact across rate changes inside the span since each second is priced at its own rate
+pub fn rate_index_interest(principal: u64, index_delta: u128, rounding: Rounding) -> Option<u64> {
+    let interest = mul_div_u128(
+        principal as u128,
+        index_delta,
+        (BPS_DENOMINATOR as u128) * (SECONDS_PER_APR_YEAR as u128),
+        rounding,
+    )?;
+
+    u64::try_from(interest).ok()
+}
+
+// Convert an amount between two decimal precisions (e.g. a mint's and USD's)
+pub fn rescale(value: u128, from_decimals: u32, to_decimals: u32, rounding: Rounding) -> Option<u64> {
+    let scaled = if from_decimals >= to_decimals {
+        let divisor = 10u128.checked_pow(from_decimals - to_decimals)?;
+        mul_div_u128(value, 1, divisor, rounding)?
+    } else {
+        value.checked_mul(10u128.checked_pow(to_decimals - from_decimals)?)?
+    };
+
+    u64::try_from(scaled).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_rounds_as_requested() {
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down), Some(3));
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up), Some(4));
+        assert_eq!(mul_div(9, 1, 3, Rounding::Up), Some(3));
+        assert_eq!(mul_div(0, 7, 3, Rounding::Up), Some(0));
+    }
+
+    #[test]
+    fn mul_div_rejects_zero_denominator() {
+        assert_eq!(mul_div(10, 1, 0, Rounding::Down), None);
+        assert_eq!(mul_div_u128(10, 1, 0, Rounding::Up), None);
+    }
+
+    #[test]
+    fn mul_div_handles_max_amounts() {
+        assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX, Rounding::Down), Some(u64::MAX));
+        assert_eq!(mul_div(u64::MAX, 2, 1, Rounding::Down), None);
+        assert_eq!(mul_div(u64::MAX, 1, 2, Rounding::Up), Some(u64::MAX / 2 + 1));
+        assert_eq!(mul_div_u128(u128::MAX, 2, 1, Rounding::Down), None);
+        assert_eq!(mul_div_u128(u128::MAX, 1, 1, Rounding::Up), Some(u128::MAX));
+    }
+
+    #[test]
+    fn apply_bps_boundaries() {
+        assert_eq!(apply_bps(1_000_000, 0, Rounding::Down), Some(0));
+        assert_eq!(apply_bps(1_000_000, 10_000, Rounding::Down), Some(1_000_000));
+        assert_eq!(apply_bps(1_000_000, 15_000, Rounding::Down), Some(1_500_000));
+        assert_eq!(apply_bps(u64::MAX, 10_000, Rounding::Up), Some(u64::MAX));
+        assert_eq!(apply_bps(u64::MAX, 10_001, Rounding::Down), None);
+        assert_eq!(apply_bps(u64::MAX, u16::MAX, Rounding::Down), None);
+    }
+
+    #[test]
+    fn bps_share_never_exceeds_amount() {
+        assert_eq!(bps_share(1_000, 20_000, Rounding::Up), 1_000);
+        assert_eq!(bps_share(u64::MAX, u16::MAX, Rounding::Up), u64::MAX);
+        assert_eq!(bps_share(u64::MAX, 5_000, Rounding::Down), u64::MAX / 2);
+        assert_eq!(bps_share(u64::MAX, 5_000, Rounding::Up), u64::MAX / 2 + 1);
+    }
+
+    #[test]
+    fn fees_round_up_on_dust() {
+        assert_eq!(fee_amount(0, 300), 0);
+        assert_eq!(fee_amount(1, 300), 1);
+        assert_eq!(fee_amount(33, 300), 1);
+        assert_eq!(fee_amount(34, 300), 2);
+        assert_eq!(fee_amount(10_000, 300), 300);
+        assert_eq!(fee_amount(10_001, 300), 301);
+        assert_eq!(fee_amount(1_000, 0), 0);
+    }
+
+    #[test]
+    fn penalties_round_up() {
+        assert_eq!(penalty_amount(1, 1_000), 1);
+        assert_eq!(penalty_amount(10, 1_000), 1);
+        assert_eq!(penalty_amount(11, 1_000), 2);
+        assert_eq!(penalty_amount(u64::MAX, 10_000), u64::MAX);
+    }
+
+    #[test]
+    fn discounts_round_down() {
+        assert_eq!(bps_share(1, 9_999, Rounding::Down), 0);
+        assert_eq!(bps_share(10_000, 9_999, Rounding::Down), 9_999);
+        assert_eq!(bps_share(19_999, 5_000, Rounding::Down), 9_999);
+    }
+
+    #[test]
+    fn apr_interest_over_a_year() {
+        let year = SECONDS_PER_APR_YEAR as i64;
+
+        assert_eq!(apr_interest(1_000_000, 500, year, Rounding::Down), Some(50_000));
+        assert_eq!(apr_interest(1_000_000, 500, year / 2, Rounding::Down), Some(25_000));
+        assert_eq!(apr_interest(1_000_000, 0, year, Rounding::Down), Some(0));
+        assert_eq!(apr_interest(1, 500, 1, Rounding::Down), Some(0));
+        assert_eq!(apr_interest(1, 500, 1, Rounding::Up), Some(1));
+    }
+
+    #[test]
+    fn apr_interest_boundaries() {
+        assert_eq!(apr_interest(1_000_000, 500, 0, Rounding::Up), Some(0));
+        assert_eq!(apr_interest(1_000_000, 500, -10, Rounding::Up), Some(0));
+        assert_eq!(apr_interest(u64::MAX, 10_000, SECONDS_PER_APR_YEAR as i64, Rounding::Down), Some(u64::MAX));
+        assert_eq!(apr_interest(u64::MAX, 10_000, 2 * SECONDS_PER_APR_YEAR as i64, Rounding::Down), None);
+        assert_eq!(apr_interest(u64::MAX, u16::MAX, i64::MAX, Rounding::Down), None);
+    }
+
+    #[test]
+    fn rescale_between_precisions() {
+        assert_eq!(rescale(1_234_567, 9, 6, Rounding::Down), Some(1_234));
+        assert_eq!(rescale(1_234_567, 9, 6, Rounding::Up), Some(1_235));
+        assert_eq!(rescale(1_234, 6, 9, Rounding::Down), Some(1_234_000));
+        assert_eq!(rescale(42, 6, 6, Rounding::Up), Some(42));
+        assert_eq!(rescale(u64::MAX as u128, 0, 1, Rounding::Down), None);
+        assert_eq!(rescale(u128::MAX, 20, 0, Rounding::Down), Some((u128::MAX / 10u128.pow(20)) as u64));
+        assert_eq!(rescale(1, 0, 40, Rounding::Down), None);
+    }
+}
@@ -0,0 +1,11 @@
+pub mod registry;
+pub mod settlement;
+
+pub use registry::{
+    process_register_merchant,
+    process_veto_merchant,
+    process_configure_merchant,
+    process_deregister_merchant,
+    load_merchant,
+};
+pub use settlement::{process_get_merchant_settlement_summary, record_merchant_settlement};
@@ -0,0 +1,364 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::merchant::{MerchantAccount, MerchantStatus, MerchantSettlementAccount};
+use crate::constants::{
+    MERCHANT_SEED, MERCHANT_SETTLEMENT_SEED, MERCHANT_BOND_LAMPORTS, MERCHANT_CHALLENGE_PERIOD_DAYS,
+    MAX_MERCHANT_FEE_BPS, MIN_MERCHANT_FEE_OVERRIDE_BPS, MERCHANT_FEE_OVERRIDE_NONE, TREASURY_SEED,
+};
+use crate::core::whitelist::require_whitelist_authority;
+use crate::error_context::{account_error, key_mismatch};
+
+// Load a merchant account after checking its PDA
+pub fn load_merchant(
+    program_id: &Pubkey,
+    merchant_account: &AccountInfo,
+) -> Result<MerchantAccount, ProgramError> {
+    if merchant_account.owner != program_id {
+        return Err(account_error(merchant_account, ProgramError::IncorrectProgramId));
+    }
+
+    let merchant_data = MerchantAccount::try_from_slice(&merchant_account.data.borrow())?;
+
+    let (merchant_pda, _) = Pubkey::find_program_address(
+        &[MERCHANT_SEED, merchant_data.owner.as_ref()],
+        program_id,
+    );
+
+    if *merchant_account.key != merchant_pda {
+        return Err(key_mismatch(merchant_account, &merchant_pda, ProgramError::InvalidAccountData));
+    }
+
+    Ok(merchant_data)
+}
+
+// Self-service merchant registration backed by a refundable bond
+pub fn process_register_merchant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let merchant_account = next_account_info(account_info_iter)?;
+    let settlement_account = next_account_info(account_info_iter)?;
+    let owner_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check owner signature
+    if !owner_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check the merchant PDA
+    let (merchant_pda, merchant_bump) = Pubkey::find_program_address(
+        &[MERCHANT_SEED, owner_account.key.as_ref()],
+        program_id,
+    );
+
+    if *merchant_account.key != merchant_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check the settlement PDA
+    let (settlement_pda, settlement_bump) = Pubkey::find_program_address(
+        &[MERCHANT_SETTLEMENT_SEED, owner_account.key.as_ref()],
+        program_id,
+    );
+
+    if *settlement_account.key != settlement_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the account holding rent plus the bond
+    let rent = Rent::get()?;
+    let space = MerchantAccount::SIZE;
+    let lamports = rent.minimum_balance(space)
+        .checked_add(MERCHANT_BOND_LAMPORTS)
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            owner_account.key,
+            &merchant_pda,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[owner_account.clone(), merchant_account.clone(), system_program.clone()],
+        &[&[MERCHANT_SEED, owner_account.key.as_ref(), &[merchant_bump]]],
+    )?;
+
+    // Create the settlement account that tracks payouts per epoch
+    let settlement_space = MerchantSettlementAccount::SIZE;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            owner_account.key,
+            &settlement_pda,
+            rent.minimum_balance(settlement_space),
+            settlement_space as u64,
+            program_id,
+        ),
+        &[owner_account.clone(), settlement_account.clone(), system_program.clone()],
+        &[&[MERCHANT_SETTLEMENT_SEED, owner_account.key.as_ref(), &[settlement_bump]]],
+    )?;
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+
+    let merchant_data = MerchantAccount::new(
+        *owner_account.key,
+        MERCHANT_BOND_LAMPORTS,
+        current_time,
+        current_time + (MERCHANT_CHALLENGE_PERIOD_DAYS * 86400),
+        merchant_bump,
+    );
+
+    merchant_data.serialize(&mut *merchant_account.data.borrow_mut())?;
+
+    let settlement_data = MerchantSettlementAccount::new(*owner_account.key, settlement_bump);
+    settlement_data.serialize(&mut *settlement_account.data.borrow_mut())?;
+
+    msg!("Merchant {} registered, active from {}", owner_account.key, merchant_data.challenge_period_end);
+    Ok(())
+}
+
+// Veto a merchant during its challenge period; the bond is forfeited to the protocol's lamport
+// treasury ([treasury] PDA)
+pub fn process_veto_merchant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let merchant_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    let (treasury_pda, _) = Pubkey::find_program_address(&[TREASURY_SEED], program_id);
+
+    if *treasury_account.key != treasury_pda {
+        return Err(key_mismatch(treasury_account, &treasury_pda, ProgramError::InvalidAccountData));
+    }
+
+    let mut merchant_data = load_merchant(program_id, merchant_account)?;
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if merchant_data.get_status(clock.unix_timestamp)? != MerchantStatus::Pending {
+        return Err(FlexfiError::ChallengePeriodEnded.into());
+    }
+
+    // Forfeit the bond
+    let bond = merchant_data.bond_lamports;
+    **merchant_account.lamports.borrow_mut() = merchant_account.lamports()
+        .checked_sub(bond)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **treasury_account.lamports.borrow_mut() = treasury_account.lamports()
+        .checked_add(bond)
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    merchant_data.bond_lamports = 0;
+    merchant_data.set_status(MerchantStatus::Vetoed);
+    merchant_data.serialize(&mut *merchant_account.data.borrow_mut())?;
+
+    msg!("Merchant {} vetoed by {}, bond of {} lamports forfeited", merchant_data.owner, authority.key, bond);
+    Ok(())
+}
+
+// Set a merchant's profile and fee schedule, and approve or suspend it (admin only)
+pub fn process_configure_merchant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name_hash: [u8; 32],
+    settlement_token_account: Pubkey,
+    fee_bps: u16,
+    is_active: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let merchant_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    if fee_bps > MAX_MERCHANT_FEE_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut merchant_data = load_merchant(program_id, merchant_account)?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    // A vetoed merchant lost its bond and has to register again
+    if merchant_data.get_status(clock.unix_timestamp)? == MerchantStatus::Vetoed {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    merchant_data.name_hash = name_hash;
+    merchant_data.settlement_token_account = settlement_token_account;
+    merchant_data.fee_bps = fee_bps;
+    merchant_data.set_status(if is_active { MerchantStatus::Active } else { MerchantStatus::Suspended });
+    merchant_data.serialize(&mut *merchant_account.data.borrow_mut())?;
+
+    msg!("Merchant {} configured: fee {} bps, active: {}", merchant_data.owner, fee_bps, is_active);
+    Ok(())
+}
+
+// Set the fee a merchant negotiated, charged instead of its standard fee, or clear it with
+// MERCHANT_FEE_OVERRIDE_NONE (admin only, or governance). Negotiated rates stay within
+// MIN_MERCHANT_FEE_OVERRIDE_BPS and MAX_MERCHANT_FEE_BPS.
+pub fn process_set_merchant_fee_override(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_override_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let merchant_account = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    if fee_override_bps != MERCHANT_FEE_OVERRIDE_NONE
+        && !(MIN_MERCHANT_FEE_OVERRIDE_BPS..=MAX_MERCHANT_FEE_BPS).contains(&fee_override_bps)
+    {
+        msg!("Negotiated fees must be between {} and {} bps", MIN_MERCHANT_FEE_OVERRIDE_BPS, MAX_MERCHANT_FEE_BPS);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut merchant_data = load_merchant(program_id, merchant_account)?;
+    merchant_data.fee_override_bps = fee_override_bps;
+    merchant_data.serialize(&mut *merchant_account.data.borrow_mut())?;
+
+    if merchant_data.has_fee_override() {
+        msg!("Merchant {} fee set to {} bps (negotiated)", merchant_data.owner, fee_override_bps);
+    } else {
+        msg!("Merchant {} back on its standard fee of {} bps", merchant_data.owner, merchant_data.fee_bps);
+    }
+    Ok(())
+}
+
+// Close the merchant account and refund the remaining bond and rent to the owner. Not during the
+// challenge period, so a merchant can't take its bond back ahead of a veto.
+pub fn process_deregister_merchant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let merchant_account = next_account_info(account_info_iter)?;
+    let settlement_account = next_account_info(account_info_iter)?;
+    let owner_account = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check owner signature
+    if !owner_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let merchant_data = load_merchant(program_id, merchant_account)?;
+
+    if merchant_data.owner != *owner_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if merchant_data.get_status(clock.unix_timestamp)? == MerchantStatus::Pending {
+        return Err(FlexfiError::ChallengePeriodActive.into());
+    }
+
+    let (settlement_pda, _) = Pubkey::find_program_address(
+        &[MERCHANT_SETTLEMENT_SEED, owner_account.key.as_ref()],
+        program_id,
+    );
+
+    if *settlement_account.key != settlement_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Refund everything held by both accounts
+    let merchant_lamports = merchant_account.lamports()
+        .checked_add(settlement_account.lamports())
+        .ok_or(FlexfiError::MathOverflow)?;
+    **owner_account.lamports.borrow_mut() = owner_account.lamports()
+        .checked_add(merchant_lamports)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **merchant_account.lamports.borrow_mut() = 0;
+    **settlement_account.lamports.borrow_mut() = 0;
+
+    // Zero the account data
+    merchant_account.data.borrow_mut().fill(0);
+    settlement_account.data.borrow_mut().fill(0);
+
+    msg!("Merchant {} deregistered, {} lamports refunded", owner_account.key, merchant_lamports);
+    Ok(())
+}
+
+pub struct MerchantRegistry;
+
+impl MerchantRegistry {
+    pub fn register(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_register_merchant(program_id, accounts)
+    }
+
+    pub fn veto(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_veto_merchant(program_id, accounts)
+    }
+
+    pub fn configure(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        name_hash: [u8; 32],
+        settlement_token_account: Pubkey,
+        fee_bps: u16,
+        is_active: bool,
+    ) -> ProgramResult {
+        process_configure_merchant(program_id, accounts, name_hash, settlement_token_account, fee_bps, is_active)
+    }
+
+    pub fn set_fee_override(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        fee_override_bps: u16,
+    ) -> ProgramResult {
+        process_set_merchant_fee_override(program_id, accounts, fee_override_bps)
+    }
+
+    pub fn deregister(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_deregister_merchant(program_id, accounts)
+    }
+}
@@ -0,0 +1,99 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::set_return_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::state::merchant::{MerchantSettlementAccount, SettlementPeriod};
+use crate::constants::MERCHANT_SETTLEMENT_SEED;
+
+// Add a payment to a registered merchant's settlement totals; unregistered merchants have no account and are skipped
+pub fn record_merchant_settlement(
+    program_id: &Pubkey,
+    settlement_account: &AccountInfo,
+    merchant: &Pubkey,
+    epoch: u64,
+    amount: u64,
+    fee: u64,
+) -> ProgramResult {
+    let (settlement_pda, _) = Pubkey::find_program_address(
+        &[MERCHANT_SETTLEMENT_SEED, merchant.as_ref()],
+        program_id,
+    );
+
+    if *settlement_account.key != settlement_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if settlement_account.owner == program_id && !settlement_account.data_is_empty() {
+        let mut settlement_data = MerchantSettlementAccount::try_from_slice(&settlement_account.data.borrow())?;
+        settlement_data.record_payment(epoch, amount, fee);
+        settlement_data.serialize(&mut *settlement_account.data.borrow_mut())?;
+    }
+
+    Ok(())
+}
+
+// Return a merchant's settlement totals for one epoch via return data
+pub fn process_get_merchant_settlement_summary(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    epoch: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let settlement_account = next_account_info(account_info_iter)?;
+
+    if settlement_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let settlement_data = MerchantSettlementAccount::try_from_slice(&settlement_account.data.borrow())?;
+
+    // Verify the settlement PDA
+    let (settlement_pda, _) = Pubkey::find_program_address(
+        &[MERCHANT_SETTLEMENT_SEED, settlement_data.merchant.as_ref()],
+        program_id,
+    );
+
+    if *settlement_account.key != settlement_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // An epoch without payments (or already out of the window) reports zero totals
+    let summary = settlement_data.get_period(epoch)
+        .copied()
+        .unwrap_or(SettlementPeriod {
+            epoch,
+            ..SettlementPeriod::default()
+        });
+
+    set_return_data(&summary.try_to_vec()?);
+
+    msg!(
+        "Merchant {} epoch {}: gross {}, fees {}, refunds {}, net {}",
+        settlement_data.merchant,
+        epoch,
+        summary.gross_volume,
+        summary.fees,
+        summary.refunds,
+        summary.net_payout
+    );
+    Ok(())
+}
+
+pub struct MerchantSettlement;
+
+impl MerchantSettlement {
+    pub fn get_summary(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        epoch: u64,
+    ) -> ProgramResult {
+        process_get_merchant_settlement_summary(program_id, accounts, epoch)
+    }
+}
@@ -9,7 +9,6 @@ use solana_program::{
     msg,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
-use crate::core::whitelist::require_whitelisted;
 use crate::error::FlexfiError;
 use crate::state::nft::{NFTMetadataAccount, NFTAttachmentAccount};
 use crate::constants::{NFT_METADATA_SEED, NFT_ATTACHMENT_SEED};
@@ -25,7 +24,7 @@ pub fn process_attach_nft(
     let nft_metadata_account = next_account_info(account_info_iter)?;
     let nft_mint = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
     let system_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
 
@@ -34,12 +33,6 @@ pub fn process_attach_nft(
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    require_whitelisted(
-        program_id,
-        user_account.key,
-        user_status_account
-    )?;
-
     // Verify NFT metadata
     let nft_seeds = [
         NFT_METADATA_SEED,
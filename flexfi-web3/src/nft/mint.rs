@@ -9,9 +9,13 @@ use solana_program::{
     msg,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
-use crate::core::whitelist::require_whitelisted;
+use crate::token::{check_token_program, check_token_account, transfer_checked};
 use crate::error::FlexfiError;
 use crate::state::nft::{NFTMetadataAccount, NFTType};
+use crate::core::config::{load_config, require_region_enabled};
+use crate::core::mint_registry::get_mint_price_feed;
+use crate::bnpl::micro::check_treasury_token_account;
+use crate::oracle::get_token_amount_for_usd;
 use crate::constants::{NFT_METADATA_SEED, NFT_MINT_COST, NFT_NONE, NFT_BRONZE, NFT_SILVER, NFT_GOLD};
 
 pub fn process_mint_nft(
@@ -25,33 +29,38 @@ pub fn process_mint_nft(
     let mint_account = next_account_info(account_info_iter)?;
     let mint_authority = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?;
+    let user_status_account = next_account_info(account_info_iter)?; // Whitelisting checked by the processor guard
     let user_token_account = next_account_info(account_info_iter)?;
-    let fee_account = next_account_info(account_info_iter)?;
+    let fee_account = next_account_info(account_info_iter)?; // Treasury token account of the payment mint
     let system_program = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let fee_source_account = next_account_info(account_info_iter)?;
+    let payment_mint = next_account_info(account_info_iter)?;
+    let payment_token_program = next_account_info(account_info_iter)?;
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let price_feed_account = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts of the payment mint
 
     // Check signatures
     if !user_account.is_signer {
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    require_whitelisted(
-        program_id,
-        user_account.key,
-        user_status_account
-    )?;
-
     if !mint_authority.is_signer {
         return Err(FlexfiError::Unauthorized.into());
     }
 
     // Check if the NFT type is valid
-    if nft_type < NFT_BRONZE || nft_type > NFT_GOLD {
+    if !(NFT_BRONZE..=NFT_GOLD).contains(&nft_type) {
         return Err(FlexfiError::InvalidNFTType.into());
     }
 
+    // Cards are only issued in the regions rolled out so far
+    let config_data = load_config(program_id, config_account)?;
+    require_region_enabled(program_id, &config_data, user_status_account)?;
+
     // Create a PDA for NFT metadata
     let seeds = [
         NFT_METADATA_SEED,
@@ -101,7 +110,9 @@ pub fn process_mint_nft(
     metadata.serialize(&mut *metadata_account.data.borrow_mut())?;
 
     // Mint an NFT token for the user
-    let mint_to_ix = spl_token::instruction::mint_to(
+    check_token_program(token_program)?;
+
+    let mint_to_ix = spl_token_2022::instruction::mint_to(
         token_program.key,
         mint_account.key,
         user_token_account.key,
@@ -120,24 +131,22 @@ pub fn process_mint_nft(
         ],
     )?;
 
-    // Transfer mint fees
-    let transfer_fee_ix = spl_token::instruction::transfer(
-        token_program.key,
-        user_token_account.key,
-        fee_account.key,
-        user_account.key,
+    // The fee is priced in USD and paid to the treasury in any accepted mint
+    let price_feed = get_mint_price_feed(program_id, payment_mint.key, accepted_mints_account)?;
+    check_token_account(fee_source_account, user_account.key, payment_mint.key)?;
+    check_treasury_token_account(program_id, payment_mint.key, payment_token_program.key, fee_account)?;
+
+    let fee_amount = get_token_amount_for_usd(payment_mint, &price_feed, price_feed_account, NFT_MINT_COST, current_time)?;
+
+    transfer_checked(
+        payment_token_program,
+        fee_source_account,
+        payment_mint,
+        fee_account,
+        user_account,
+        hook_accounts,
+        fee_amount,
         &[],
-        NFT_MINT_COST,
-    )?;
-
-    invoke(
-        &transfer_fee_ix,
-        &[
-            user_token_account.clone(),
-            fee_account.clone(),
-            user_account.clone(),
-            token_program.clone(),
-        ],
     )?;
 
     let nft_type_name = match nft_type {
@@ -198,8 +207,10 @@ pub fn process_extend_nft_duration(
     let owner_account = next_account_info(account_info_iter)?;
     let fee_account = next_account_info(account_info_iter)?;
     let user_token_account = next_account_info(account_info_iter)?;
+    let payment_mint = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     let _clock_sysvar = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
 
     // Check owner signature
     if !owner_account.is_signer {
@@ -229,23 +240,15 @@ pub fn process_extend_nft_duration(
     let extension_cost = (additional_days as u64).saturating_mul(1_000_000); // 1 USDC per day
 
     // Transfer extension fees
-    let transfer_fee_ix = spl_token::instruction::transfer(
-        token_program.key,
-        user_token_account.key,
-        fee_account.key,
-        owner_account.key,
-        &[],
+    transfer_checked(
+        token_program,
+        user_token_account,
+        payment_mint,
+        fee_account,
+        owner_account,
+        hook_accounts,
         extension_cost,
-    )?;
-
-    invoke(
-        &transfer_fee_ix,
-        &[
-            user_token_account.clone(),
-            fee_account.clone(),
-            owner_account.clone(),
-            token_program.clone(),
-        ],
+        &[],
     )?;
 
     // Update the NFT duration
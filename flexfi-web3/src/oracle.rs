@@ -0,0 +1,141 @@
+use solana_program::{
+    account_info::AccountInfo,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use pyth_sdk_solana::{state::SolanaPriceAccount, Price};
+
+use crate::error::FlexfiError;
+use crate::constants::{USD_DECIMALS, MAX_ORACLE_PRICE_AGE_SECS, MAX_COLLATERAL_HAIRCUT_BPS};
+use crate::token::get_mint_decimals;
+use crate::math::{rescale, mul_div_u128, bps_share, Rounding};
+
+// Fresh price of the Pyth feed registered for a mint
+fn load_price(
+    price_feed: &Pubkey,
+    price_account: &AccountInfo,
+    current_time: i64,
+) -> Result<Price, ProgramError> {
+    if *price_account.key != *price_feed {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let feed = SolanaPriceAccount::account_info_to_feed(price_account)
+        .map_err(|_| FlexfiError::InvalidOraclePrice)?;
+
+    feed.get_price_no_older_than(current_time, MAX_ORACLE_PRICE_AGE_SECS)
+        .ok_or(FlexfiError::StaleOraclePrice.into())
+}
+
+// USD value (USDC units) of a token amount.
+// Mints without a price feed are stablecoins valued 1:1; others are priced
+// with the Pyth feed registered for them, using price minus confidence.
+pub fn get_usd_value(
+    mint: &AccountInfo,
+    price_feed: &Pubkey,
+    price_account: &AccountInfo,
+    amount: u64,
+    current_time: i64,
+) -> Result<u64, ProgramError> {
+    let decimals = get_mint_decimals(mint)? as u32;
+
+    if *price_feed == Pubkey::default() {
+        return rescale(amount as u128, decimals, USD_DECIMALS as u32, Rounding::Down)
+            .ok_or(FlexfiError::MathOverflow.into());
+    }
+
+    let price = load_price(price_feed, price_account, current_time)?;
+
+    // Be conservative with collateral: use the lower bound of the price
+    let conservative_price = (price.price as i128) - (price.conf as i128);
+    if conservative_price <= 0 || price.expo > 0 {
+        msg!("Unusable oracle price: {} +/- {}", price.price, price.conf);
+        return Err(FlexfiError::InvalidOraclePrice.into());
+    }
+
+    // amount * price, with (decimals + |expo|) decimals, scaled to USD decimals
+    let raw_value = (amount as u128)
+        .checked_mul(conservative_price as u128)
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    rescale(raw_value, decimals + price.expo.unsigned_abs(), USD_DECIMALS as u32, Rounding::Down)
+        .ok_or(FlexfiError::MathOverflow.into())
+}
+
+// Token amount a USD value (USDC units) buys.
+// Stablecoins convert 1:1; others use price plus confidence, so the amount is never overstated.
+pub fn get_token_amount_for_usd(
+    mint: &AccountInfo,
+    price_feed: &Pubkey,
+    price_account: &AccountInfo,
+    usd_value: u64,
+    current_time: i64,
+) -> Result<u64, ProgramError> {
+    let decimals = get_mint_decimals(mint)? as u32;
+
+    if *price_feed == Pubkey::default() {
+        return rescale(usd_value as u128, USD_DECIMALS as u32, decimals, Rounding::Down)
+            .ok_or(FlexfiError::MathOverflow.into());
+    }
+
+    let price = load_price(price_feed, price_account, current_time)?;
+
+    let upper_price = (price.price as i128) + (price.conf as i128);
+    if price.price <= 0 || price.expo > 0 {
+        msg!("Unusable oracle price: {} +/- {}", price.price, price.conf);
+        return Err(FlexfiError::InvalidOraclePrice.into());
+    }
+
+    // usd * 10^(decimals + |expo|) / (10^USD_DECIMALS * price)
+    let scale = 10u128
+        .checked_pow(decimals + price.expo.unsigned_abs())
+        .ok_or(FlexfiError::MathOverflow)?;
+    let denominator = 10u128.pow(USD_DECIMALS as u32)
+        .checked_mul(upper_price as u128)
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    let amount = mul_div_u128(usd_value as u128, scale, denominator, Rounding::Down)
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    u64::try_from(amount).map_err(|_| FlexfiError::MathOverflow.into())
+}
+
+// A mint with the price feed registered for it and the feed's price account
+pub struct PricedMint<'a, 'b> {
+    pub mint: &'b AccountInfo<'a>,
+    pub price_feed: &'b Pubkey,
+    pub price_account: &'b AccountInfo<'a>,
+}
+
+// Convert seized collateral into the contract mint at oracle prices, minus a haircut for the
+// price risk of the swap; the haircut is bounded by MAX_COLLATERAL_HAIRCUT_BPS
+pub fn convert_collateral(
+    collateral: &PricedMint,
+    contract: &PricedMint,
+    collateral_amount: u64,
+    haircut_bps: u16,
+    current_time: i64,
+) -> Result<u64, ProgramError> {
+    if haircut_bps > MAX_COLLATERAL_HAIRCUT_BPS {
+        msg!("Haircut of {} bps is above the {} bps bound", haircut_bps, MAX_COLLATERAL_HAIRCUT_BPS);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let usd_value = get_usd_value(
+        collateral.mint,
+        collateral.price_feed,
+        collateral.price_account,
+        collateral_amount,
+        current_time,
+    )?;
+    let usd_after_haircut = usd_value.saturating_sub(bps_share(usd_value, haircut_bps, Rounding::Up));
+
+    get_token_amount_for_usd(
+        contract.mint,
+        contract.price_feed,
+        contract.price_account,
+        usd_after_haircut,
+        current_time,
+    )
+}
@@ -6,13 +6,20 @@ use solana_program::{
 };
 
 use crate::instructions::{FlexfiInstruction, decode_instruction};
-use crate::core::{staking, whitelist};
-use crate::bnpl::checker;  // Keep only checker
+use crate::guard::check_instruction_guards;
+use crate::core::{staking, whitelist, blocklist, status, mint_registry, config, snapshot, staking_tier, savings, wallet, admin, exposure, dust, treasury_stream};
+use crate::state::staking_tier::StakingTier;
+use crate::bnpl::{checker, reminder, micro, hold, calendar, prepay, refund, late_fee, reassign, forgiveness, receipt};
 use crate::card::manager;
 use crate::nft::{mint, attach};
-use crate::score::{contract as score_contract, query as score_query};
-use crate::yield_module::{router, tracker};
-use crate::freeze_spend::authorization;
+use crate::score::{contract as score_contract, query as score_query, access as score_access};
+use crate::yield_module::{router, tracker, treasury};
+use crate::freeze_spend::{authorization, escrow, split, promo, pending_charge};
+use crate::insurance::reserve;
+use crate::merchant::{registry, settlement};
+use crate::donation::charity;
+use crate::governance::proposal;
+use crate::rewards::emissions;
 
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -21,6 +28,9 @@ pub fn process_instruction(
 ) -> ProgramResult {
     let instruction = decode_instruction(instruction_data)?;
 
+    // Whitelist checks are resolved here once instead of in every handler
+    check_instruction_guards(program_id, &instruction, accounts)?;
+
     match instruction {
         // New Freeze & Spend instructions (REPLACE old BNPL)
         FlexfiInstruction::InitializeFlexFiAccount { authorized_amount, duration_days } => {
@@ -37,6 +47,48 @@ pub fn process_instruction(
             msg!("Instruction: Revoke Funds Authorization");
             authorization::process_revoke_authorization(program_id, accounts)
         },
+        FlexfiInstruction::SetSpendRoundUp { savings_goal } => {
+            msg!("Instruction: Set Spend Round Up");
+            authorization::process_set_spend_round_up(program_id, accounts, savings_goal)
+        },
+        FlexfiInstruction::CreateSplitPurchase { merchant, shares } => {
+            msg!("Instruction: Create Split Purchase");
+            split::process_create_split_purchase(program_id, accounts, merchant, shares)
+        },
+        FlexfiInstruction::CreatePromoCode { code_hash, discount_bps, max_discount, max_uses, expires_at } => {
+            msg!("Instruction: Create Promo Code");
+            promo::process_create_promo_code(
+                program_id, accounts, code_hash, discount_bps, max_discount, max_uses, expires_at
+            )
+        },
+        FlexfiInstruction::RedeemPromoCode { preimage } => {
+            msg!("Instruction: Redeem Promo Code");
+            promo::process_redeem_promo_code(program_id, accounts, preimage)
+        },
+        FlexfiInstruction::CreatePendingCharge { amount } => {
+            msg!("Instruction: Create Pending Charge");
+            pending_charge::process_create_pending_charge(program_id, accounts, amount)
+        },
+        FlexfiInstruction::CancelPendingCharge { merchant } => {
+            msg!("Instruction: Cancel Pending Charge");
+            pending_charge::process_cancel_pending_charge(program_id, accounts, merchant)
+        },
+        FlexfiInstruction::SetSpendDonation { donation_bps } => {
+            msg!("Instruction: Set Spend Donation");
+            charity::process_set_spend_donation(program_id, accounts, donation_bps)
+        },
+        FlexfiInstruction::CreateDepositEscrow { amount } => {
+            msg!("Instruction: Create Deposit Escrow");
+            escrow::process_create_deposit_escrow(program_id, accounts, amount)
+        },
+        FlexfiInstruction::ReleaseDepositEscrow { to_beneficiary } => {
+            msg!("Instruction: Release Deposit Escrow");
+            escrow::process_release_deposit_escrow(program_id, accounts, to_beneficiary)
+        },
+        FlexfiInstruction::ArbitrateDepositEscrow { to_beneficiary } => {
+            msg!("Instruction: Arbitrate Deposit Escrow");
+            escrow::process_arbitrate_deposit_escrow(program_id, accounts, to_beneficiary)
+        },
 
         // Core instructions
         FlexfiInstruction::InitializeWhitelist => {
@@ -51,15 +103,223 @@ pub fn process_instruction(
             msg!("Instruction: Remove from Whitelist");
             whitelist::process_remove_from_whitelist(program_id, accounts, user_pubkey)
         },
+        FlexfiInstruction::RenewWhitelist { user_pubkey } => {
+            msg!("Instruction: Renew Whitelist");
+            whitelist::process_renew_whitelist(program_id, accounts, user_pubkey)
+        },
+        FlexfiInstruction::SetWhitelistActive { is_active } => {
+            msg!("Instruction: Set Whitelist Active");
+            whitelist::process_set_whitelist_active(program_id, accounts, is_active)
+        },
+        FlexfiInstruction::AddAuthorizedKey { key } => {
+            msg!("Instruction: Add Authorized Key");
+            whitelist::process_add_authorized_key(program_id, accounts, key)
+        },
+        FlexfiInstruction::RemoveAuthorizedKey { key } => {
+            msg!("Instruction: Remove Authorized Key");
+            whitelist::process_remove_authorized_key(program_id, accounts, key)
+        },
+        FlexfiInstruction::SetAttestationSigner { signer } => {
+            msg!("Instruction: Set Attestation Signer");
+            whitelist::process_set_attestation_signer(program_id, accounts, signer)
+        },
+        FlexfiInstruction::SetUserRiskLimits { user_pubkey, max_bnpl_amount, max_active_contracts } => {
+            msg!("Instruction: Set User Risk Limits");
+            whitelist::process_set_user_risk_limits(program_id, accounts, user_pubkey, max_bnpl_amount, max_active_contracts)
+        },
+        FlexfiInstruction::SetUserRegion { user_pubkey, region } => {
+            msg!("Instruction: Set User Region");
+            whitelist::process_set_user_region(program_id, accounts, user_pubkey, region)
+        },
+
+        // Blocklist
+        FlexfiInstruction::AddToBlocklist { user_pubkey, reason } => {
+            msg!("Instruction: Add To Blocklist");
+            blocklist::process_add_to_blocklist(program_id, accounts, user_pubkey, reason)
+        },
+        FlexfiInstruction::RemoveFromBlocklist { user_pubkey } => {
+            msg!("Instruction: Remove From Blocklist");
+            blocklist::process_remove_from_blocklist(program_id, accounts, user_pubkey)
+        },
+
+        // Role-based admin list
+        FlexfiInstruction::InitializeAdminList => {
+            msg!("Instruction: Initialize Admin List");
+            admin::process_initialize_admin_list(program_id, accounts)
+        },
+        FlexfiInstruction::SetAdminRoles { admin, roles } => {
+            msg!("Instruction: Set Admin Roles");
+            admin::process_set_admin_roles(program_id, accounts, admin, roles)
+        },
+
+        FlexfiInstruction::InitializeAcceptedMints => {
+            msg!("Instruction: Initialize Accepted Mints");
+            mint_registry::process_initialize_accepted_mints(program_id, accounts)
+        },
+        FlexfiInstruction::AddAcceptedMint { mint } => {
+            msg!("Instruction: Add Accepted Mint");
+            mint_registry::process_add_accepted_mint(program_id, accounts, mint)
+        },
+        FlexfiInstruction::RemoveAcceptedMint { mint } => {
+            msg!("Instruction: Remove Accepted Mint");
+            mint_registry::process_remove_accepted_mint(program_id, accounts, mint)
+        },
+        FlexfiInstruction::SetMintPriceFeed { mint, price_feed } => {
+            msg!("Instruction: Set Mint Price Feed");
+            mint_registry::process_set_mint_price_feed(program_id, accounts, mint, price_feed)
+        },
+        FlexfiInstruction::SetPaymentMint { mint, enabled } => {
+            msg!("Instruction: Set Payment Mint");
+            mint_registry::process_set_payment_mint(program_id, accounts, mint, enabled)
+        },
 
-        FlexfiInstruction::DepositStaking { amount, lock_days } => {
+        FlexfiInstruction::InitializeConfig => {
+            msg!("Instruction: Initialize Config");
+            config::process_initialize_config(program_id, accounts)
+        },
+        FlexfiInstruction::SetCollateralRatio { card_type, score_tier, ltv_bps } => {
+            msg!("Instruction: Set Collateral Ratio");
+            config::process_set_collateral_ratio(program_id, accounts, card_type, score_tier, ltv_bps)
+        },
+        FlexfiInstruction::SetConfigAuthority { new_authority } => {
+            msg!("Instruction: Set Config Authority");
+            config::process_set_config_authority(program_id, accounts, new_authority)
+        },
+
+        // Governance
+        FlexfiInstruction::InitializeGovernance { quorum_votes } => {
+            msg!("Instruction: Initialize Governance");
+            proposal::process_initialize_governance(program_id, accounts, quorum_votes)
+        },
+        FlexfiInstruction::CreateProposal { instruction_data } => {
+            msg!("Instruction: Create Proposal");
+            proposal::process_create_proposal(program_id, accounts, instruction_data)
+        },
+        FlexfiInstruction::CastVote { support } => {
+            msg!("Instruction: Cast Vote");
+            proposal::process_cast_vote(program_id, accounts, support)
+        },
+        FlexfiInstruction::QueueProposal => {
+            msg!("Instruction: Queue Proposal");
+            proposal::process_queue_proposal(program_id, accounts)
+        },
+        FlexfiInstruction::ExecuteProposal => {
+            msg!("Instruction: Execute Proposal");
+            proposal::process_execute_proposal(program_id, accounts)
+        },
+
+        // Epoch reward emissions
+        FlexfiInstruction::SetEmissionsSchedule {
+            emission_per_epoch,
+            staking_share_bps,
+            repayment_share_bps,
+            liquidity_share_bps,
+        } => {
+            msg!("Instruction: Set Emissions Schedule");
+            emissions::process_set_emissions_schedule(
+                program_id, accounts, emission_per_epoch, staking_share_bps, repayment_share_bps, liquidity_share_bps
+            )
+        },
+        FlexfiInstruction::DistributeEpochRewards { epoch } => {
+            msg!("Instruction: Distribute Epoch Rewards");
+            emissions::process_distribute_epoch_rewards(program_id, accounts, epoch)
+        },
+        FlexfiInstruction::InitializeStakingTiers => {
+            msg!("Instruction: Initialize Staking Tiers");
+            staking_tier::process_initialize_staking_tiers(program_id, accounts)
+        },
+        FlexfiInstruction::SetStakingTier { index, min_staked, fee_discount_bps, max_bnpl_multiplier_bps } => {
+            msg!("Instruction: Set Staking Tier");
+            staking_tier::process_set_staking_tier(
+                program_id,
+                accounts,
+                index,
+                StakingTier { min_staked, fee_discount_bps, max_bnpl_multiplier_bps },
+            )
+        },
+
+        FlexfiInstruction::DepositStaking { amount, lock_days, position_id, vesting } => {
             msg!("Instruction: Deposit Staking");
-            staking::process_deposit_staking(program_id, accounts, amount, lock_days)
+            staking::process_deposit_staking(program_id, accounts, amount, lock_days, position_id, vesting)
         },
         FlexfiInstruction::WithdrawStaking { amount } => {
             msg!("Instruction: Withdraw Staking");
             staking::process_withdraw_staking(program_id, accounts, amount)
         },
+        FlexfiInstruction::EmergencyWithdrawStaking { amount } => {
+            msg!("Instruction: Emergency Withdraw Staking");
+            staking::process_emergency_withdraw_staking(program_id, accounts, amount)
+        },
+        FlexfiInstruction::ExtendStakingLock { additional_days } => {
+            msg!("Instruction: Extend Staking Lock");
+            staking::process_extend_staking_lock(program_id, accounts, additional_days)
+        },
+        FlexfiInstruction::CheckUnlockStatus => {
+            msg!("Instruction: Check Unlock Status");
+            staking::process_check_unlock_status(program_id, accounts)
+        },
+        FlexfiInstruction::SetStakingDelegate { delegate } => {
+            msg!("Instruction: Set Staking Delegate");
+            staking::process_set_staking_delegate(program_id, accounts, delegate)
+        },
+        FlexfiInstruction::CloseStakingAccount => {
+            msg!("Instruction: Close Staking Account");
+            staking::process_close_staking_account(program_id, accounts)
+        },
+        FlexfiInstruction::FreezeStaking { reason } => {
+            msg!("Instruction: Freeze Staking");
+            staking::process_freeze_staking(program_id, accounts, reason)
+        },
+        FlexfiInstruction::UnfreezeStaking => {
+            msg!("Instruction: Unfreeze Staking");
+            staking::process_unfreeze_staking(program_id, accounts)
+        },
+        FlexfiInstruction::ClaimStakingRewards => {
+            msg!("Instruction: Claim Staking Rewards");
+            staking::process_claim_staking_rewards(program_id, accounts)
+        },
+        FlexfiInstruction::SetAutoCompound { enabled } => {
+            msg!("Instruction: Set Auto Compound");
+            staking::process_set_auto_compound(program_id, accounts, enabled)
+        },
+        FlexfiInstruction::CompoundStakingRewards => {
+            msg!("Instruction: Compound Staking Rewards");
+            staking::process_compound_staking_rewards(program_id, accounts)
+        },
+        FlexfiInstruction::SetStakingRewardAPY { apy_bps } => {
+            msg!("Instruction: Set Staking Reward APY");
+            config::process_set_staking_reward_apy(program_id, accounts, apy_bps)
+        },
+        FlexfiInstruction::GetAccruedRewards => {
+            msg!("Instruction: Get Accrued Rewards");
+            staking::process_get_accrued_rewards(program_id, accounts)
+        },
+        FlexfiInstruction::SetConvertOnUnlock { enabled } => {
+            msg!("Instruction: Set Convert On Unlock");
+            staking::process_set_convert_on_unlock(program_id, accounts, enabled)
+        },
+        FlexfiInstruction::ConvertUnlockedStaking => {
+            msg!("Instruction: Convert Unlocked Staking");
+            staking::process_convert_unlocked_staking(program_id, accounts)
+        },
+        FlexfiInstruction::TakeStakingSnapshot => {
+            msg!("Instruction: Take Staking Snapshot");
+            snapshot::process_take_staking_snapshot(program_id, accounts)
+        },
+
+        // Savings goals
+        FlexfiInstruction::CreateSavingsGoal { name_hash, target_amount, target_date } => {
+            msg!("Instruction: Create Savings Goal");
+            savings::process_create_savings_goal(program_id, accounts, name_hash, target_amount, target_date)
+        },
+        FlexfiInstruction::DepositSavingsGoal { amount } => {
+            msg!("Instruction: Deposit Savings Goal");
+            savings::process_deposit_savings_goal(program_id, accounts, amount)
+        },
+        FlexfiInstruction::WithdrawSavingsGoal { amount } => {
+            msg!("Instruction: Withdraw Savings Goal");
+            savings::process_withdraw_savings_goal(program_id, accounts, amount)
+        },
 
         // NFT instructions
         FlexfiInstruction::MintNFT { nft_type } => {
@@ -80,6 +340,18 @@ pub fn process_instruction(
             msg!("Instruction: Upgrade Card");
             manager::process_upgrade_card(program_id, accounts, new_card_type)
         },
+        FlexfiInstruction::SetCardCommitment { verification_key } => {
+            msg!("Instruction: Set Card Commitment");
+            manager::process_set_card_commitment(program_id, accounts, verification_key)
+        },
+        FlexfiInstruction::VerifyCardCommitment => {
+            msg!("Instruction: Verify Card Commitment");
+            manager::process_verify_card_commitment(program_id, accounts)
+        },
+        FlexfiInstruction::ReviewCardTier => {
+            msg!("Instruction: Review Card Tier");
+            manager::process_review_card_tier(program_id, accounts)
+        },
 
         // Score instructions
         FlexfiInstruction::InitializeScore => {
@@ -94,6 +366,14 @@ pub fn process_instruction(
             msg!("Instruction: Get Score");
             score_query::process_get_score(program_id, accounts)
         },
+        FlexfiInstruction::GrantDataAccess { viewer, scope, expiry } => {
+            msg!("Instruction: Grant Data Access");
+            score_access::process_grant_data_access(program_id, accounts, viewer, scope, expiry)
+        },
+        FlexfiInstruction::RevokeDataAccess => {
+            msg!("Instruction: Revoke Data Access");
+            score_access::process_revoke_data_access(program_id, accounts)
+        },
 
         // Yield instructions
         FlexfiInstruction::SetYieldStrategy { strategy, auto_reinvest } => {
@@ -108,5 +388,263 @@ pub fn process_instruction(
             msg!("Instruction: Claim Yield");
             tracker::process_claim_yield(program_id, accounts, amount)
         },
+        FlexfiInstruction::RestakeYield { amount } => {
+            msg!("Instruction: Restake Yield");
+            tracker::process_restake_yield(program_id, accounts, amount)
+        },
+
+        // Treasury yield deployment
+        FlexfiInstruction::SetTreasuryDeployCap { cap_bps } => {
+            msg!("Instruction: Set Treasury Deploy Cap");
+            config::process_set_treasury_deploy_cap(program_id, accounts, cap_bps)
+        },
+        FlexfiInstruction::DeployTreasury { amount } => {
+            msg!("Instruction: Deploy Treasury");
+            treasury::process_deploy_treasury(program_id, accounts, amount)
+        },
+        FlexfiInstruction::RecallTreasury { amount } => {
+            msg!("Instruction: Recall Treasury");
+            treasury::process_recall_treasury(program_id, accounts, amount)
+        },
+        FlexfiInstruction::ReconcileStrategy { reported_value } => {
+            msg!("Instruction: Reconcile Strategy");
+            treasury::process_reconcile_strategy(program_id, accounts, reported_value)
+        },
+        FlexfiInstruction::ResumeStrategy => {
+            msg!("Instruction: Resume Strategy");
+            treasury::process_resume_strategy(program_id, accounts)
+        },
+        FlexfiInstruction::WithdrawTreasury { recipient, amount, stream_id, stream_days } => {
+            msg!("Instruction: Withdraw Treasury");
+            treasury_stream::process_withdraw_treasury(program_id, accounts, recipient, amount, stream_id, stream_days)
+        },
+        FlexfiInstruction::ClaimTreasuryStream => {
+            msg!("Instruction: Claim Treasury Stream");
+            treasury_stream::process_claim_treasury_stream(program_id, accounts)
+        },
+        FlexfiInstruction::CancelTreasuryStream => {
+            msg!("Instruction: Cancel Treasury Stream");
+            treasury_stream::process_cancel_treasury_stream(program_id, accounts)
+        },
+        FlexfiInstruction::SweepDust { vault_kind } => {
+            msg!("Instruction: Sweep Dust");
+            dust::process_sweep_dust(program_id, accounts, vault_kind)
+        },
+
+        // Installment reminders
+        FlexfiInstruction::SendPaymentReminder => {
+            msg!("Instruction: Send Payment Reminder");
+            reminder::process_send_payment_reminder(program_id, accounts)
+        },
+        FlexfiInstruction::SnoozePayment => {
+            msg!("Instruction: Snooze Payment");
+            reminder::process_snooze_payment(program_id, accounts)
+        },
+        FlexfiInstruction::AnchorInstallmentCalendar => {
+            msg!("Instruction: Anchor Installment Calendar");
+            calendar::process_anchor_installment_calendar(program_id, accounts)
+        },
+        FlexfiInstruction::PrepayBNPL { amount, mode } => {
+            msg!("Instruction: Prepay BNPL");
+            prepay::process_prepay_bnpl(program_id, accounts, amount, mode)
+        },
+        FlexfiInstruction::RefundBNPLContract { amount } => {
+            msg!("Instruction: Refund BNPL Contract");
+            refund::process_refund_bnpl_contract(program_id, accounts, amount)
+        },
+        FlexfiInstruction::ReleaseEscrow => {
+            msg!("Instruction: Release Escrow");
+            micro::process_release_escrow(program_id, accounts)
+        },
+        FlexfiInstruction::CheckRepayment => {
+            msg!("Instruction: Check Repayment");
+            late_fee::process_check_repayment(program_id, accounts)
+        },
+        FlexfiInstruction::ReassignMerchant { contract, new_merchant } => {
+            msg!("Instruction: Reassign Merchant");
+            reassign::process_reassign_merchant(program_id, accounts, contract, new_merchant)
+        },
+        FlexfiInstruction::SetForgivenessBudget { monthly_cap } => {
+            msg!("Instruction: Set Forgiveness Budget");
+            forgiveness::process_set_forgiveness_budget(program_id, accounts, monthly_cap)
+        },
+        FlexfiInstruction::ForgivePenalty { contract, amount } => {
+            msg!("Instruction: Forgive Penalty");
+            forgiveness::process_forgive_penalty(program_id, accounts, contract, amount)
+        },
+        FlexfiInstruction::SetKeeperBounty { bounty_lamports } => {
+            msg!("Instruction: Set Keeper Bounty");
+            late_fee::process_set_keeper_bounty(program_id, accounts, bounty_lamports)
+        },
+        FlexfiInstruction::IssuePurchaseReceipt => {
+            msg!("Instruction: Issue Purchase Receipt");
+            receipt::process_issue_purchase_receipt(program_id, accounts)
+        },
+        FlexfiInstruction::SetYieldPerformanceFee { card_type, fee_bps } => {
+            msg!("Instruction: Set Yield Performance Fee");
+            config::process_set_yield_performance_fee(program_id, accounts, card_type, fee_bps)
+        },
+        FlexfiInstruction::SetEmergencyWithdrawPenalty { penalty_bps } => {
+            msg!("Instruction: Set Emergency Withdraw Penalty");
+            config::process_set_emergency_withdraw_penalty(program_id, accounts, penalty_bps)
+        },
+
+        // Legal holds
+        FlexfiInstruction::PlaceLegalHold { contract } => {
+            msg!("Instruction: Place Legal Hold");
+            hold::process_place_legal_hold(program_id, accounts, contract)
+        },
+        FlexfiInstruction::ReleaseLegalHold { contract } => {
+            msg!("Instruction: Release Legal Hold");
+            hold::process_release_legal_hold(program_id, accounts, contract)
+        },
+
+        // Fee-free micro-BNPL
+        FlexfiInstruction::SetMicroBNPLConfig { threshold, ltv_bps } => {
+            msg!("Instruction: Set Micro BNPL Config");
+            config::process_set_micro_bnpl_config(program_id, accounts, threshold, ltv_bps)
+        },
+        FlexfiInstruction::SetMinBNPLScore { card_type, min_score } => {
+            msg!("Instruction: Set Min BNPL Score");
+            config::process_set_min_bnpl_score(program_id, accounts, card_type, min_score)
+        },
+        FlexfiInstruction::SetRegionGating { enabled } => {
+            msg!("Instruction: Set Region Gating");
+            config::process_set_region_gating(program_id, accounts, enabled)
+        },
+        FlexfiInstruction::SetRegionEnabled { region, enabled } => {
+            msg!("Instruction: Set Region Enabled");
+            config::process_set_region_enabled(program_id, accounts, region, enabled)
+        },
+        FlexfiInstruction::CreateMicroBNPL { amount, escrow } => {
+            msg!("Instruction: Create Micro BNPL");
+            micro::process_create_micro_bnpl(program_id, accounts, amount, escrow)
+        },
+        FlexfiInstruction::RepayMicroBNPL => {
+            msg!("Instruction: Repay Micro BNPL");
+            micro::process_repay_micro_bnpl(program_id, accounts)
+        },
+
+        // Merchant onboarding
+        FlexfiInstruction::RegisterMerchant => {
+            msg!("Instruction: Register Merchant");
+            registry::process_register_merchant(program_id, accounts)
+        },
+        FlexfiInstruction::VetoMerchant => {
+            msg!("Instruction: Veto Merchant");
+            registry::process_veto_merchant(program_id, accounts)
+        },
+        FlexfiInstruction::DeregisterMerchant => {
+            msg!("Instruction: Deregister Merchant");
+            registry::process_deregister_merchant(program_id, accounts)
+        },
+        FlexfiInstruction::ConfigureMerchant { name_hash, settlement_token_account, fee_bps, is_active } => {
+            msg!("Instruction: Configure Merchant");
+            registry::process_configure_merchant(
+                program_id, accounts, name_hash, settlement_token_account, fee_bps, is_active
+            )
+        },
+        FlexfiInstruction::SetMerchantFeeOverride { fee_override_bps } => {
+            msg!("Instruction: Set Merchant Fee Override");
+            registry::process_set_merchant_fee_override(program_id, accounts, fee_override_bps)
+        },
+        FlexfiInstruction::GetMerchantSettlementSummary { epoch } => {
+            msg!("Instruction: Get Merchant Settlement Summary");
+            settlement::process_get_merchant_settlement_summary(program_id, accounts, epoch)
+        },
+
+        // Wallet lifecycle
+        FlexfiInstruction::CreateWallet => {
+            msg!("Instruction: Create Wallet");
+            wallet::process_create_wallet(program_id, accounts)
+        },
+        FlexfiInstruction::DeactivateWallet => {
+            msg!("Instruction: Deactivate Wallet");
+            wallet::process_deactivate_wallet(program_id, accounts)
+        },
+        FlexfiInstruction::ReactivateWallet => {
+            msg!("Instruction: Reactivate Wallet");
+            wallet::process_reactivate_wallet(program_id, accounts)
+        },
+
+        // Wallet social recovery
+        FlexfiInstruction::SetWalletGuardians { guardians, threshold } => {
+            msg!("Instruction: Set Wallet Guardians");
+            wallet::process_set_wallet_guardians(program_id, accounts, guardians, threshold)
+        },
+        FlexfiInstruction::ApproveWalletRecovery { new_owner } => {
+            msg!("Instruction: Approve Wallet Recovery");
+            wallet::process_approve_wallet_recovery(program_id, accounts, new_owner)
+        },
+        FlexfiInstruction::CancelWalletRecovery => {
+            msg!("Instruction: Cancel Wallet Recovery");
+            wallet::process_cancel_wallet_recovery(program_id, accounts)
+        },
+        FlexfiInstruction::RecoverWallet => {
+            msg!("Instruction: Recover Wallet");
+            wallet::process_recover_wallet(program_id, accounts)
+        },
+        FlexfiInstruction::MigrateRecoveredProfile => {
+            msg!("Instruction: Migrate Recovered Profile");
+            wallet::process_migrate_recovered_profile(program_id, accounts)
+        },
+        FlexfiInstruction::MigrateRecoveredStaking => {
+            msg!("Instruction: Migrate Recovered Staking");
+            staking::process_migrate_recovered_staking(program_id, accounts)
+        },
+        FlexfiInstruction::LinkBackendId { backend_id_hash } => {
+            msg!("Instruction: Link Backend Id");
+            wallet::process_link_backend_id(program_id, accounts, backend_id_hash)
+        },
+        FlexfiInstruction::CloseWallet => {
+            msg!("Instruction: Close Wallet");
+            wallet::process_close_wallet(program_id, accounts)
+        },
+        FlexfiInstruction::RefreshWhitelistCache => {
+            msg!("Instruction: Refresh Whitelist Cache");
+            wallet::process_refresh_whitelist_cache(program_id, accounts)
+        },
+        FlexfiInstruction::RequestDataErasure => {
+            msg!("Instruction: Request Data Erasure");
+            wallet::process_request_data_erasure(program_id, accounts)
+        },
+        FlexfiInstruction::AcknowledgeDataErasure => {
+            msg!("Instruction: Acknowledge Data Erasure");
+            wallet::process_acknowledge_data_erasure(program_id, accounts)
+        },
+
+        // Charity registry
+        FlexfiInstruction::RegisterCharity => {
+            msg!("Instruction: Register Charity");
+            charity::process_register_charity(program_id, accounts)
+        },
+        FlexfiInstruction::DeactivateCharity => {
+            msg!("Instruction: Deactivate Charity");
+            charity::process_deactivate_charity(program_id, accounts)
+        },
+
+        // Insurance reserve
+        FlexfiInstruction::FundInsuranceReserve { amount } => {
+            msg!("Instruction: Fund Insurance Reserve");
+            reserve::process_fund_insurance_reserve(program_id, accounts, amount)
+        },
+        FlexfiInstruction::CoverInsuranceShortfall { amount } => {
+            msg!("Instruction: Cover Insurance Shortfall");
+            reserve::process_cover_insurance_shortfall(program_id, accounts, amount)
+        },
+
+        // Protocol status
+        FlexfiInstruction::GetProtocolStatus => {
+            msg!("Instruction: Get Protocol Status");
+            status::process_get_protocol_status(program_id, accounts)
+        },
+        FlexfiInstruction::GetActiveParameters => {
+            msg!("Instruction: Get Active Parameters");
+            status::process_get_active_parameters(program_id, accounts)
+        },
+        FlexfiInstruction::GetUserExposure => {
+            msg!("Instruction: Get User Exposure");
+            exposure::process_get_user_exposure(program_id, accounts)
+        },
     }
 }
@@ -0,0 +1,386 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::emissions::{EmissionsScheduleAccount, EpochActivityAccount, UserActivityAccount};
+use crate::constants::{EMISSIONS_SCHEDULE_SEED, EPOCH_ACTIVITY_SEED, USER_ACTIVITY_SEED};
+use crate::core::whitelist::require_whitelist_authority;
+use crate::token::transfer_checked;
+use crate::math::{mul_div, Rounding};
+
+// Load the emissions schedule after checking its PDA
+fn load_schedule(
+    program_id: &Pubkey,
+    schedule_account: &AccountInfo,
+) -> Result<EmissionsScheduleAccount, ProgramError> {
+    let (schedule_pda, _) = Pubkey::find_program_address(&[EMISSIONS_SCHEDULE_SEED], program_id);
+
+    if *schedule_account.key != schedule_pda || schedule_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    EmissionsScheduleAccount::try_from_slice(&schedule_account.data.borrow())
+        .map_err(|_| ProgramError::UninitializedAccount)
+}
+
+// Pro-rata part of a category emission
+fn get_share(emission: u64, user_activity: u64, total_activity: u64) -> u64 {
+    if total_activity == 0 {
+        return 0;
+    }
+
+    mul_div(emission, user_activity, total_activity, Rounding::Down).unwrap_or(emission)
+}
+
+// Create or update the per-epoch emissions and their split (admin only); epochs with recorded
+// activity keep the emissions they started with
+pub fn process_set_emissions_schedule(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    emission_per_epoch: u64,
+    staking_share_bps: u16,
+    repayment_share_bps: u16,
+    liquidity_share_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let schedule_account = next_account_info(account_info_iter)?;
+    let reward_mint = next_account_info(account_info_iter)?;
+    let whitelist_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify the admin authority
+    require_whitelist_authority(program_id, whitelist_account, authority)?;
+
+    // The split must cover the whole emission
+    let total_bps = staking_share_bps as u32 + repayment_share_bps as u32 + liquidity_share_bps as u32;
+    if total_bps != 10000 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check the schedule PDA
+    let (schedule_pda, schedule_bump) = Pubkey::find_program_address(&[EMISSIONS_SCHEDULE_SEED], program_id);
+
+    if *schedule_account.key != schedule_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the schedule on first use
+    if schedule_account.owner != program_id {
+        let rent = Rent::get()?;
+        let space = EmissionsScheduleAccount::SIZE;
+        let rent_lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                &schedule_pda,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[authority.clone(), schedule_account.clone(), system_program.clone()],
+            &[&[EMISSIONS_SCHEDULE_SEED, &[schedule_bump]]],
+        )?;
+    }
+
+    let schedule_data = EmissionsScheduleAccount {
+        reward_mint: *reward_mint.key,
+        emission_per_epoch,
+        staking_share_bps,
+        repayment_share_bps,
+        liquidity_share_bps,
+        bump: schedule_bump,
+    };
+
+    schedule_data.serialize(&mut *schedule_account.data.borrow_mut())?;
+
+    msg!("Emissions schedule set: {} per epoch ({}/{}/{} bps)",
+        emission_per_epoch, staking_share_bps, repayment_share_bps, liquidity_share_bps);
+    Ok(())
+}
+
+// Accounts an activity is recorded in: the emissions schedule, and the epoch and user totals created on first use
+pub(crate) struct ActivityAccounts<'a, 'b> {
+    pub schedule_account: &'b AccountInfo<'a>,
+    pub epoch_activity_account: &'b AccountInfo<'a>,
+    pub user_activity_account: &'b AccountInfo<'a>,
+    pub system_program: &'b AccountInfo<'a>,
+}
+
+// Activity earning a share of the epoch emissions
+pub(crate) enum Activity {
+    Staking(u64),
+    Repayment(u64),
+    Liquidity(u64),
+}
+
+// Add a user's activity to the current epoch's totals; the epoch keeps the category emissions of the
+// schedule in force when its first activity is recorded. Nothing is recorded before a schedule is set.
+pub(crate) fn record_epoch_activity<'a>(
+    program_id: &Pubkey,
+    accounts: &ActivityAccounts<'a, '_>,
+    user: &Pubkey,
+    payer: &AccountInfo<'a>,
+    activity: Activity,
+) -> ProgramResult {
+    let (schedule_pda, _) = Pubkey::find_program_address(&[EMISSIONS_SCHEDULE_SEED], program_id);
+
+    if *accounts.schedule_account.key != schedule_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if accounts.schedule_account.owner != program_id {
+        return Ok(());
+    }
+
+    let schedule_data = load_schedule(program_id, accounts.schedule_account)?;
+
+    let epoch = Clock::get()?.epoch;
+    let epoch_bytes = epoch.to_le_bytes();
+
+    // Check the activity PDAs
+    let (epoch_activity_pda, epoch_activity_bump) = Pubkey::find_program_address(
+        &[EPOCH_ACTIVITY_SEED, &epoch_bytes],
+        program_id,
+    );
+    let (user_activity_pda, user_activity_bump) = Pubkey::find_program_address(
+        &[USER_ACTIVITY_SEED, user.as_ref(), &epoch_bytes],
+        program_id,
+    );
+
+    if *accounts.epoch_activity_account.key != epoch_activity_pda
+        || *accounts.user_activity_account.key != user_activity_pda
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent = Rent::get()?;
+
+    let mut epoch_activity = if accounts.epoch_activity_account.owner != program_id {
+        let space = EpochActivityAccount::SIZE;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                &epoch_activity_pda,
+                rent.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), accounts.epoch_activity_account.clone(), accounts.system_program.clone()],
+            &[&[EPOCH_ACTIVITY_SEED, &epoch_bytes, &[epoch_activity_bump]]],
+        )?;
+
+        EpochActivityAccount {
+            epoch,
+            total_staking: 0,
+            total_repayment: 0,
+            total_liquidity: 0,
+            staking_emission: schedule_data.get_category_emission(schedule_data.staking_share_bps),
+            repayment_emission: schedule_data.get_category_emission(schedule_data.repayment_share_bps),
+            liquidity_emission: schedule_data.get_category_emission(schedule_data.liquidity_share_bps),
+            bump: epoch_activity_bump,
+        }
+    } else {
+        EpochActivityAccount::try_from_slice(&accounts.epoch_activity_account.data.borrow())?
+    };
+
+    let mut user_activity = if accounts.user_activity_account.owner != program_id {
+        let space = UserActivityAccount::SIZE;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                &user_activity_pda,
+                rent.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), accounts.user_activity_account.clone(), accounts.system_program.clone()],
+            &[&[USER_ACTIVITY_SEED, user.as_ref(), &epoch_bytes, &[user_activity_bump]]],
+        )?;
+
+        UserActivityAccount {
+            owner: *user,
+            epoch,
+            staking: 0,
+            repayment: 0,
+            liquidity: 0,
+            distributed: false,
+            bump: user_activity_bump,
+        }
+    } else {
+        UserActivityAccount::try_from_slice(&accounts.user_activity_account.data.borrow())?
+    };
+
+    match activity {
+        Activity::Staking(amount) => {
+            user_activity.staking = user_activity.staking.saturating_add(amount);
+            epoch_activity.total_staking = epoch_activity.total_staking.saturating_add(amount);
+        },
+        Activity::Repayment(amount) => {
+            user_activity.repayment = user_activity.repayment.saturating_add(amount);
+            epoch_activity.total_repayment = epoch_activity.total_repayment.saturating_add(amount);
+        },
+        Activity::Liquidity(amount) => {
+            user_activity.liquidity = user_activity.liquidity.saturating_add(amount);
+            epoch_activity.total_liquidity = epoch_activity.total_liquidity.saturating_add(amount);
+        },
+    }
+
+    user_activity.serialize(&mut *accounts.user_activity_account.data.borrow_mut())?;
+    epoch_activity.serialize(&mut *accounts.epoch_activity_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Pay a user's share of a finished epoch's emissions (permissionless crank)
+pub fn process_distribute_epoch_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    epoch: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let schedule_account = next_account_info(account_info_iter)?;
+    let epoch_activity_account = next_account_info(account_info_iter)?;
+    let user_activity_account = next_account_info(account_info_iter)?;
+    let emissions_vault_token_account = next_account_info(account_info_iter)?;
+    let user_reward_token_account = next_account_info(account_info_iter)?;
+    let reward_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    let schedule_data = load_schedule(program_id, schedule_account)?;
+
+    // Only finished epochs are distributed
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if epoch >= clock.epoch {
+        return Err(FlexfiError::EpochNotEnded.into());
+    }
+
+    let epoch_bytes = epoch.to_le_bytes();
+
+    let (epoch_activity_pda, _) = Pubkey::find_program_address(
+        &[EPOCH_ACTIVITY_SEED, &epoch_bytes],
+        program_id,
+    );
+
+    if *epoch_activity_account.key != epoch_activity_pda || epoch_activity_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if user_activity_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let epoch_activity = EpochActivityAccount::try_from_slice(&epoch_activity_account.data.borrow())?;
+    let mut user_activity = UserActivityAccount::try_from_slice(&user_activity_account.data.borrow())?;
+
+    let (user_activity_pda, _) = Pubkey::find_program_address(
+        &[USER_ACTIVITY_SEED, user_activity.owner.as_ref(), &epoch_bytes],
+        program_id,
+    );
+
+    if *user_activity_account.key != user_activity_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if user_activity.distributed {
+        return Err(FlexfiError::RewardsAlreadyDistributed.into());
+    }
+
+    // Verify the vault and the user's reward account
+    if *reward_mint.key != schedule_data.reward_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        schedule_account.key,
+        reward_mint.key,
+        token_program.key,
+    );
+    let user_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &user_activity.owner,
+        reward_mint.key,
+        token_program.key,
+    );
+
+    if *emissions_vault_token_account.key != vault_ata || *user_reward_token_account.key != user_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Each category is shared pro rata between the users active in it, at the epoch's emissions
+    let reward = get_share(
+        epoch_activity.staking_emission,
+        user_activity.staking,
+        epoch_activity.total_staking,
+    )
+    .saturating_add(get_share(
+        epoch_activity.repayment_emission,
+        user_activity.repayment,
+        epoch_activity.total_repayment,
+    ))
+    .saturating_add(get_share(
+        epoch_activity.liquidity_emission,
+        user_activity.liquidity,
+        epoch_activity.total_liquidity,
+    ));
+
+    user_activity.distributed = true;
+    user_activity.serialize(&mut *user_activity_account.data.borrow_mut())?;
+
+    if reward > 0 {
+        transfer_checked(
+            token_program,
+            emissions_vault_token_account,
+            reward_mint,
+            user_reward_token_account,
+            schedule_account,
+            hook_accounts,
+            reward,
+            &[&[EMISSIONS_SCHEDULE_SEED, &[schedule_data.bump]]],
+        )?;
+    }
+
+    msg!("Epoch {} rewards for {}: {}", epoch, user_activity.owner, reward);
+    Ok(())
+}
+
+pub struct EmissionsManager;
+
+impl EmissionsManager {
+    pub fn set_schedule(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        emission_per_epoch: u64,
+        staking_share_bps: u16,
+        repayment_share_bps: u16,
+        liquidity_share_bps: u16,
+    ) -> ProgramResult {
+        process_set_emissions_schedule(
+            program_id, accounts, emission_per_epoch, staking_share_bps, repayment_share_bps, liquidity_share_bps
+        )
+    }
+
+    pub fn distribute(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        epoch: u64,
+    ) -> ProgramResult {
+        process_distribute_epoch_rewards(program_id, accounts, epoch)
+    }
+}
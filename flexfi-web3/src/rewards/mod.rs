@@ -0,0 +1,6 @@
+pub mod emissions;
+
+pub use emissions::{
+    process_set_emissions_schedule,
+    process_distribute_epoch_rewards,
+};
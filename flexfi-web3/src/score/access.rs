@@ -0,0 +1,162 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FlexfiError;
+use crate::state::access::DataAccessGrant;
+use crate::constants::{DATA_ACCESS_SEED, DATA_SCOPE_ALL};
+
+// Check that the viewer is the owner or holds a valid grant for the scope
+pub fn require_data_access<'a>(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    viewer_account: &AccountInfo<'a>,
+    account_info_iter: &mut std::slice::Iter<'_, AccountInfo<'a>>,
+    scope: u8,
+) -> ProgramResult {
+    if !viewer_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if viewer_account.key == owner {
+        return Ok(());
+    }
+
+    // Third parties pass their grant after the viewer
+    let grant_account = next_account_info(account_info_iter)?;
+
+    let (grant_pda, _) = Pubkey::find_program_address(
+        &[DATA_ACCESS_SEED, owner.as_ref(), viewer_account.key.as_ref()],
+        program_id,
+    );
+
+    if *grant_account.key != grant_pda || grant_account.owner != program_id {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let grant_data = DataAccessGrant::try_from_slice(&grant_account.data.borrow())?;
+    let clock = Clock::get()?;
+
+    if !grant_data.allows(scope, clock.unix_timestamp) {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    Ok(())
+}
+
+// Let a lender or partner read the user's data within a scope until the expiry
+pub fn process_grant_data_access(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    viewer: Pubkey,
+    scope: u8,
+    expiry: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let grant_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    if scope == 0 || scope & !DATA_SCOPE_ALL != 0 || expiry <= clock.unix_timestamp {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check the grant PDA
+    let (grant_pda, grant_bump) = Pubkey::find_program_address(
+        &[DATA_ACCESS_SEED, user_account.key.as_ref(), viewer.as_ref()],
+        program_id,
+    );
+
+    if *grant_account.key != grant_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Create the grant on first use, otherwise replace its terms
+    if grant_account.owner != program_id {
+        let rent = Rent::get()?;
+        let space = DataAccessGrant::SIZE;
+        let rent_lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                user_account.key,
+                &grant_pda,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[user_account.clone(), grant_account.clone(), system_program.clone()],
+            &[&[DATA_ACCESS_SEED, user_account.key.as_ref(), viewer.as_ref(), &[grant_bump]]],
+        )?;
+    }
+
+    let grant_data = DataAccessGrant {
+        owner: *user_account.key,
+        viewer,
+        scope,
+        granted_at: clock.unix_timestamp,
+        expires_at: expiry,
+        bump: grant_bump,
+    };
+
+    grant_data.serialize(&mut *grant_account.data.borrow_mut())?;
+
+    msg!("Data access granted to {} (scope {}) until {}", viewer, scope, expiry);
+    Ok(())
+}
+
+// Withdraw a grant and refund its rent to the user
+pub fn process_revoke_data_access(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let grant_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+
+    // Check user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if grant_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let grant_data = DataAccessGrant::try_from_slice(&grant_account.data.borrow())?;
+
+    if grant_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Refund the rent to the user
+    let grant_lamports = grant_account.lamports();
+    **user_account.lamports.borrow_mut() = user_account.lamports()
+        .checked_add(grant_lamports)
+        .ok_or(FlexfiError::MathOverflow)?;
+    **grant_account.lamports.borrow_mut() = 0;
+
+    // Zero the account data
+    grant_account.data.borrow_mut().fill(0);
+
+    msg!("Data access revoked for {}", grant_data.viewer);
+    Ok(())
+}
@@ -9,10 +9,11 @@ use solana_program::{
     msg,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
-use crate::core::whitelist::require_whitelisted;
 use crate::error::FlexfiError;
 use crate::state::score::ScoreAccount;
+use crate::state::admin::AdminRole;
 use crate::constants::{SCORE_SEED, INITIAL_SCORE};
+use crate::core::admin::require_admin_role;
 
 pub fn process_initialize_score(
     program_id: &Pubkey,
@@ -22,7 +23,7 @@ pub fn process_initialize_score(
 
     let score_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
     let system_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
 
@@ -31,12 +32,6 @@ pub fn process_initialize_score(
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    require_whitelisted(
-        program_id,
-        user_account.key,
-        user_status_account
-    )?;
-
     // Create a PDA for the score account
     let seeds = [
         SCORE_SEED,
@@ -91,20 +86,22 @@ pub fn process_initialize_score(
 }
 
 pub fn process_update_score(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     change: i16,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let score_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
     let authority_account = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
 
-    // Check authority signature
-    // In a real implementation, check if the authority is authorized
-    if !authority_account.is_signer {
-        return Err(FlexfiError::Unauthorized.into());
+    // Check the authority holds the score role
+    require_admin_role(program_id, admin_list_account, authority_account, AdminRole::ScoreAuthority)?;
+
+    if score_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
     }
 
     // Load score data
@@ -125,18 +122,21 @@ pub fn process_update_score(
 }
 
 pub fn process_record_new_loan(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let score_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
     let authority_account = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
 
-    // Check authority signature
-    if !authority_account.is_signer {
-        return Err(FlexfiError::Unauthorized.into());
+    // Check the authority holds the score role
+    require_admin_role(program_id, admin_list_account, authority_account, AdminRole::ScoreAuthority)?;
+
+    if score_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
     }
 
     // Load score data
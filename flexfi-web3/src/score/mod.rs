@@ -1,5 +1,7 @@
 pub mod contract;
 pub mod query;
+pub mod access;
 
 pub use contract::{process_initialize_score, process_update_score, process_record_new_loan};
-pub use query::{process_get_score, process_check_score_threshold, process_get_payment_stats};
\ No newline at end of file
+pub use query::{process_get_score, process_check_score_threshold, process_get_payment_stats};
+pub use access::{process_grant_data_access, process_revoke_data_access, require_data_access};
@@ -9,7 +9,8 @@ use borsh::BorshDeserialize;
 
 use crate::error::FlexfiError;
 use crate::state::score::ScoreAccount;
-use crate::constants::SCORE_SEED;
+use crate::constants::{SCORE_SEED, DATA_SCOPE_SCORE, DATA_SCOPE_HISTORY};
+use crate::score::access::require_data_access;
 
 pub fn process_get_score(
     program_id: &Pubkey,
@@ -19,6 +20,7 @@ pub fn process_get_score(
 
     let score_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
+    let viewer_account = next_account_info(account_info_iter)?;
 
     // Check the score account
     let seeds = [
@@ -39,6 +41,9 @@ pub fn process_get_score(
         return Err(FlexfiError::Unauthorized.into());
     }
 
+    // The owner or a viewer holding a grant may read the data
+    require_data_access(program_id, user_account.key, viewer_account, account_info_iter, DATA_SCOPE_SCORE)?;
+
     // Display score information
     msg!("User score: {}", score_data.score);
     msg!("On-time payments: {}", score_data.on_time_payments);
@@ -49,6 +54,30 @@ pub fn process_get_score(
     Ok(())
 }
 
+// Load a user's score after checking its PDA and owner
+pub(crate) fn load_user_score(
+    program_id: &Pubkey,
+    user_pubkey: &Pubkey,
+    score_account: &AccountInfo,
+) -> Result<ScoreAccount, ProgramError> {
+    let (score_pda, _) = Pubkey::find_program_address(
+        &[SCORE_SEED, user_pubkey.as_ref()],
+        program_id,
+    );
+
+    if *score_account.key != score_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let score_data = ScoreAccount::try_from_slice(&score_account.data.borrow())?;
+
+    if score_data.owner != *user_pubkey {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    Ok(score_data)
+}
+
 pub fn process_check_score_threshold(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -58,28 +87,15 @@ pub fn process_check_score_threshold(
 
     let score_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
+    let viewer_account = next_account_info(account_info_iter)?;
 
-    // Check the score account
-    let seeds = [
-        SCORE_SEED,
-        user_account.key.as_ref(),
-    ];
-    let (score_pda, _) = Pubkey::find_program_address(&seeds, program_id);
-
-    if *score_account.key != score_pda {
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    // Load score data
-    let score_data = ScoreAccount::try_from_slice(&score_account.data.borrow())?;
+    let score_data = load_user_score(program_id, user_account.key, score_account)?;
 
-    // Verify ownership
-    if score_data.owner != *user_account.key {
-        return Err(FlexfiError::Unauthorized.into());
-    }
+    // The owner or a viewer holding a grant may read the data
+    require_data_access(program_id, user_account.key, viewer_account, account_info_iter, DATA_SCOPE_SCORE)?;
 
     // Check if the score meets the minimum threshold
-    let meets_threshold = score_data.score >= min_score;
+    let meets_threshold = score_data.meets_threshold(min_score);
 
     msg!("Score check: user score {} vs threshold {}: {}",
          score_data.score, min_score, meets_threshold);
@@ -95,6 +111,7 @@ pub fn process_get_payment_stats(
 
     let score_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
+    let viewer_account = next_account_info(account_info_iter)?;
 
     // Check the score account
     let seeds = [
@@ -115,6 +132,9 @@ pub fn process_get_payment_stats(
         return Err(FlexfiError::Unauthorized.into());
     }
 
+    // The owner or a viewer holding a grant may read the data
+    require_data_access(program_id, user_account.key, viewer_account, account_info_iter, DATA_SCOPE_HISTORY)?;
+
     // Calculate statistics
     let total_payments = score_data.on_time_payments + score_data.late_payments;
     let on_time_percentage = if total_payments > 0 {
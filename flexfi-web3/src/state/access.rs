@@ -0,0 +1,20 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct DataAccessGrant {
+    pub owner: Pubkey,
+    pub viewer: Pubkey,
+    pub scope: u8,       // Bitmask of DATA_SCOPE_* flags
+    pub granted_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl DataAccessGrant {
+    pub const SIZE: usize = 32 + 32 + 1 + 8 + 8 + 1; // 82 bytes
+
+    pub fn allows(&self, scope: u8, current_time: i64) -> bool {
+        self.scope & scope == scope && current_time < self.expires_at
+    }
+}
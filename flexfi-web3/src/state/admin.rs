@@ -0,0 +1,96 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::constants::MAX_ADMINS;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
+pub enum AdminRole {
+    WhitelistManager,
+    ScoreAuthority,
+    Pauser,
+    Treasurer,
+    StrategyKeeper,
+    Support,
+}
+
+impl AdminRole {
+    pub const ALL: u8 = 0b111111;
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            AdminRole::WhitelistManager => 0,
+            AdminRole::ScoreAuthority => 1,
+            AdminRole::Pauser => 2,
+            AdminRole::Treasurer => 3,
+            AdminRole::StrategyKeeper => 4,
+            AdminRole::Support => 5,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(AdminRole::WhitelistManager),
+            1 => Ok(AdminRole::ScoreAuthority),
+            2 => Ok(AdminRole::Pauser),
+            3 => Ok(AdminRole::Treasurer),
+            4 => Ok(AdminRole::StrategyKeeper),
+            5 => Ok(AdminRole::Support),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    }
+
+    // Bit of the role in an admin's role mask
+    pub fn mask(&self) -> u8 {
+        1 << self.to_u8()
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct AdminListAccount {
+    pub authority: Pubkey,            // Manages the list; holds no role unless granted one
+    pub admin_count: u8,
+    pub admins: [Pubkey; MAX_ADMINS],
+    pub roles: [u8; MAX_ADMINS],      // AdminRole bitmask per admin
+    pub bump: u8,
+}
+
+impl AdminListAccount {
+    pub const SIZE: usize = 32 + 1 + (32 * MAX_ADMINS) + MAX_ADMINS + 1; // 364 bytes
+
+    pub fn has_role(&self, admin: &Pubkey, role: AdminRole) -> bool {
+        self.admins[..self.admin_count as usize]
+            .iter()
+            .position(|a| a == admin)
+            .is_some_and(|index| self.roles[index] & role.mask() != 0)
+    }
+
+    // Replace an admin's roles; an empty mask removes the admin from the list
+    pub fn set_roles(&mut self, admin: Pubkey, roles: u8) -> bool {
+        let count = self.admin_count as usize;
+        match self.admins[..count].iter().position(|a| *a == admin) {
+            Some(index) if roles == 0 => {
+                // Keep the list compact by moving the last entry into the gap
+                self.admins[index] = self.admins[count - 1];
+                self.admins[count - 1] = Pubkey::default();
+                self.roles[index] = self.roles[count - 1];
+                self.roles[count - 1] = 0;
+                self.admin_count -= 1;
+                true
+            },
+            Some(index) => {
+                self.roles[index] = roles;
+                true
+            },
+            None if roles == 0 || count >= MAX_ADMINS => false,
+            None => {
+                self.admins[count] = admin;
+                self.roles[count] = roles;
+                self.admin_count += 1;
+                true
+            },
+        }
+    }
+}
@@ -1,5 +1,18 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
+use crate::constants::MAX_CREDIT_RESERVATIONS;
+
+// Credit held for a merchant's pending charge until the user approves it or it expires
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, Copy)]
+pub struct CreditReservation {
+    pub merchant: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+}
+
+impl CreditReservation {
+    pub const SIZE: usize = 32 + 8 + 8; // 48 bytes
+}
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct AuthorizationAccount {
@@ -10,17 +23,197 @@ pub struct AuthorizationAccount {
     pub is_active: bool,
     pub created_at: i64,
     pub expires_at: i64,
+    pub round_up_goal: Pubkey, // Savings goal receiving spend round-ups, default when off
+    pub donation_bps: u16, // Share of each spend donated to the user's charity, 0 when off
+    pub promo_code: Pubkey, // Redeemed promo code applied to the next spend, default when none
+    pub reservation_count: u8,
+    pub reservations: [CreditReservation; MAX_CREDIT_RESERVATIONS], // One per merchant, ignored once expired
     pub bump: u8,
 }
 
 impl AuthorizationAccount {
-    pub const SIZE: usize = 32 + 32 + 8 + 8 + 1 + 8 + 8 + 1; // 98 bytes
+    pub const SIZE: usize = 32 + 32 + 8 + 8 + 1 + 8 + 8 + 32 + 2 + 32 + 1 + (CreditReservation::SIZE * MAX_CREDIT_RESERVATIONS) + 1; // 357 bytes
     
     pub fn remaining_credit(&self) -> u64 {
         self.authorized_amount.saturating_sub(self.used_amount)
     }
     
+    pub fn has_round_up(&self) -> bool {
+        self.round_up_goal != Pubkey::default()
+    }
+
+    pub fn has_donation(&self) -> bool {
+        self.donation_bps > 0
+    }
+
+    pub fn has_promo(&self) -> bool {
+        self.promo_code != Pubkey::default()
+    }
+
     pub fn is_valid(&self, current_time: i64) -> bool {
         self.is_active && current_time < self.expires_at
     }
-}
\ No newline at end of file
+
+    // Credit held by unexpired reservations
+    pub fn reserved_amount(&self, current_time: i64) -> u64 {
+        self.reservations[..self.reservation_count as usize]
+            .iter()
+            .filter(|r| r.expires_at > current_time)
+            .fold(0u64, |sum, r| sum.saturating_add(r.amount))
+    }
+
+    // Credit that a new charge can use
+    pub fn available_credit(&self, current_time: i64) -> u64 {
+        self.remaining_credit().saturating_sub(self.reserved_amount(current_time))
+    }
+
+    // Drop the reservations that expired
+    pub fn prune_expired_reservations(&mut self, current_time: i64) {
+        let mut kept = 0;
+        for i in 0..self.reservation_count as usize {
+            if self.reservations[i].expires_at > current_time {
+                self.reservations[kept] = self.reservations[i];
+                kept += 1;
+            }
+        }
+
+        for reservation in self.reservations[kept..].iter_mut() {
+            *reservation = CreditReservation::default();
+        }
+        self.reservation_count = kept as u8;
+    }
+
+    // Hold credit for a merchant's pending charge; false when the merchant already holds some or
+    // every slot is taken
+    pub fn reserve(&mut self, merchant: Pubkey, amount: u64, expires_at: i64, current_time: i64) -> bool {
+        self.prune_expired_reservations(current_time);
+
+        let count = self.reservation_count as usize;
+        if count >= MAX_CREDIT_RESERVATIONS || self.reservations[..count].iter().any(|r| r.merchant == merchant) {
+            return false;
+        }
+
+        self.reservations[count] = CreditReservation { merchant, amount, expires_at };
+        self.reservation_count += 1;
+        true
+    }
+
+    // Give back a merchant's reservation; returns the amount that was still held
+    pub fn release_reservation(&mut self, merchant: &Pubkey, current_time: i64) -> u64 {
+        self.prune_expired_reservations(current_time);
+
+        let count = self.reservation_count as usize;
+        match self.reservations[..count].iter().position(|r| r.merchant == *merchant) {
+            Some(index) => {
+                let amount = self.reservations[index].amount;
+
+                // Keep the list compact by moving the last entry into the gap
+                self.reservations[index] = self.reservations[count - 1];
+                self.reservations[count - 1] = CreditReservation::default();
+                self.reservation_count -= 1;
+                amount
+            },
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn authorization(authorized_amount: u64) -> AuthorizationAccount {
+        AuthorizationAccount {
+            user: Pubkey::new_unique(),
+            flexfi_authority: Pubkey::new_unique(),
+            authorized_amount,
+            used_amount: 0,
+            is_active: true,
+            created_at: 0,
+            expires_at: 1_000_000,
+            round_up_goal: Pubkey::default(),
+            donation_bps: 0,
+            promo_code: Pubkey::default(),
+            reservation_count: 0,
+            reservations: [CreditReservation::default(); MAX_CREDIT_RESERVATIONS],
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn reservations_hold_credit_until_they_expire() {
+        let mut auth = authorization(1_000);
+
+        assert!(auth.reserve(Pubkey::new_unique(), 300, 100, 0));
+        assert!(auth.reserve(Pubkey::new_unique(), 200, 200, 0));
+        assert_eq!(auth.available_credit(50), 500);
+        assert_eq!(auth.available_credit(100), 800);
+        assert_eq!(auth.available_credit(200), 1_000);
+    }
+
+    #[test]
+    fn reserve_rejects_a_second_hold_for_the_same_merchant() {
+        let mut auth = authorization(1_000);
+        let merchant = Pubkey::new_unique();
+
+        assert!(auth.reserve(merchant, 100, 100, 0));
+        assert!(!auth.reserve(merchant, 100, 100, 0));
+
+        // Once the first hold expired the merchant can reserve again
+        assert!(auth.reserve(merchant, 50, 300, 100));
+        assert_eq!(auth.reservation_count, 1);
+        assert_eq!(auth.reserved_amount(100), 50);
+    }
+
+    #[test]
+    fn reserve_rejects_when_every_slot_is_taken() {
+        let mut auth = authorization(1_000);
+
+        for _ in 0..MAX_CREDIT_RESERVATIONS {
+            assert!(auth.reserve(Pubkey::new_unique(), 10, 100, 0));
+        }
+        assert!(!auth.reserve(Pubkey::new_unique(), 10, 100, 0));
+
+        // Expired holds free their slots
+        assert!(auth.reserve(Pubkey::new_unique(), 10, 200, 100));
+        assert_eq!(auth.reservation_count, 1);
+    }
+
+    #[test]
+    fn prune_keeps_live_reservations_in_order() {
+        let mut auth = authorization(1_000);
+        let merchants: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+
+        assert!(auth.reserve(merchants[0], 10, 100, 0));
+        assert!(auth.reserve(merchants[1], 20, 50, 0));
+        assert!(auth.reserve(merchants[2], 30, 100, 0));
+
+        auth.prune_expired_reservations(50);
+
+        assert_eq!(auth.reservation_count, 2);
+        assert_eq!(auth.reservations[0].merchant, merchants[0]);
+        assert_eq!(auth.reservations[1].merchant, merchants[2]);
+        assert_eq!(auth.reservations[2].amount, 0);
+    }
+
+    #[test]
+    fn release_returns_the_held_amount_and_compacts_the_list() {
+        let mut auth = authorization(1_000);
+        let merchants: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+
+        for (index, merchant) in merchants.iter().enumerate() {
+            assert!(auth.reserve(*merchant, 100 * (index as u64 + 1), 100, 0));
+        }
+
+        assert_eq!(auth.release_reservation(&merchants[0], 10), 100);
+        assert_eq!(auth.reservation_count, 2);
+        assert_eq!(auth.reservations[0].merchant, merchants[2]);
+        assert_eq!(auth.reservations[1].merchant, merchants[1]);
+        assert_eq!(auth.reserved_amount(10), 500);
+
+        // Unknown and expired reservations give nothing back
+        assert_eq!(auth.release_reservation(&merchants[0], 10), 0);
+        assert_eq!(auth.release_reservation(&merchants[1], 100), 0);
+        assert_eq!(auth.reservation_count, 0);
+    }
+}
@@ -4,6 +4,13 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+use crate::constants::{
+    MICRO_BNPL_INSTALLMENTS, MICRO_BNPL_INTERVAL_DAYS, LATE_FEE_DAILY_BPS,
+    PREPAYMENT_SHORTEN_SCHEDULE, PREPAYMENT_REDUCE_INSTALLMENTS,
+    MAX_BORROWER_CONTRACTS, FORGIVENESS_PERIOD_DAYS,
+};
+use crate::math::fee_amount;
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
 pub enum BNPLStatus {
     Active,
@@ -57,43 +64,6 @@ pub struct BNPLContractAccount {
 impl BNPLContractAccount {
     pub const SIZE: usize = 32 + 32 + 8 + 32 + 1 + 1 + 8 + 1 + 8 + 1 + 8 + 8 + 2 + 2 + 1 + 1 + 1; // 147 bytes
 
-    pub fn new(
-        borrower: Pubkey,
-        merchant: Pubkey,
-        amount: u64,
-        token_mint: Pubkey,
-        installments: u8,
-        payment_interval_days: u8,
-        amount_per_installment: u64,
-        fee_percentage: u16,
-        apr_percentage: u16,
-        card_type: u8,
-        nft_type: u8,
-        created_at: i64,
-        next_payment_due: i64,
-        bump: u8,
-    ) -> Self {
-        Self {
-            borrower,
-            merchant,
-            amount,
-            token_mint,
-            installments,
-            paid_installments: 0,
-            next_payment_due,
-            payment_interval_days,
-            amount_per_installment,
-            status: BNPLStatus::Active.to_u8(),
-            created_at,
-            last_payment_at: created_at,
-            fee_percentage,
-            apr_percentage,
-            card_type,
-            nft_type,
-            bump,
-        }
-    }
-
     pub fn get_status(&self) -> Result<BNPLStatus, ProgramError> {
         BNPLStatus::from_u8(self.status)
     }
@@ -106,6 +76,11 @@ impl BNPLContractAccount {
         current_time >= self.next_payment_due
     }
 
+    // Still owed by the borrower
+    pub fn is_open(&self) -> Result<bool, ProgramError> {
+        Ok(self.get_status()? == BNPLStatus::Active)
+    }
+
     pub fn update_after_payment(&mut self, current_time: i64) -> Result<(), ProgramError> {
         self.paid_installments += 1;
         self.last_payment_at = current_time;
@@ -120,8 +95,408 @@ impl BNPLContractAccount {
         Ok(())
     }
 
+    pub fn next_installment_amount(&self) -> u64 {
+        self.amount_per_installment
+    }
+
     pub fn remaining_amount(&self) -> u64 {
         let remaining_installments = self.installments.saturating_sub(self.paid_installments);
         self.amount_per_installment.saturating_mul(remaining_installments as u64)
     }
 }
+
+// Fee-free micro loan with fixed terms, kept smaller than a full BNPL contract and closed once repaid
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct MicroBNPLAccount {
+    pub borrower: Pubkey,
+    pub merchant: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub amount_repaid: u64,
+    pub paid_installments: u8,
+    pub next_payment_due: i64,
+    pub escrowed_amount: u64,  // Merchant payment held in the loan's vault until delivery, 0 once released
+    pub escrow_release_at: i64, // The escrow can be released by anyone from then on
+    pub created_at: i64,
+    pub reminded_due: i64,   // Due date the last PaymentDueSoon event was emitted for
+    pub snoozed: bool,       // The one-time snooze has been used
+    pub held_at: i64,        // Start of the current legal hold, 0 when not held
+    pub prepayment_mode: u8, // How the last prepayment was applied, PREPAYMENT_NONE until then
+    pub refunded_amount: u64, // Purchase amount returned by the merchant so far
+    pub accrued_late_fees: u64, // Owed with the next installment
+    pub days_overdue: u16,   // Grace period days the late fees were accrued for
+    pub grace_period_days: u8, // Of the borrower's card at creation, kept when the card terms change
+    pub previous_merchant: Pubkey, // Merchant before the last reassignment, default if never reassigned
+    pub staking_account: Pubkey, // Position backing the loan
+    pub stake_hold: u64,     // Part of that stake held until the loan is settled
+    pub installment_covered: u64, // Paid so far on the current installment, counted as repaid once it is settled
+    pub bump: u8,
+}
+
+impl MicroBNPLAccount {
+    pub const SIZE: usize = 32 + 32 + 32 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 1 + 8 + 8 + 2 + 1 + 32 + 32 + 8 + 8 + 1; // 263 bytes
+
+    // Owed by the borrower: the purchase, less what was repaid and what the merchant refunded
+    pub fn remaining_amount(&self) -> u64 {
+        self.scheduled_amount().saturating_sub(self.installment_covered)
+    }
+
+    // Left to pay over the installments not settled yet, the current one counted in full
+    fn scheduled_amount(&self) -> u64 {
+        self.amount.saturating_sub(self.amount_repaid.saturating_add(self.refunded_amount))
+    }
+
+    // Installment owed once `paid` installments are paid and `remaining` is left: equal installments
+    // where the last one takes the rounding remainder. After a prepayment that shortens the schedule the
+    // installments keep their amount and the loan ends early; after one that reduces them, what is left
+    // is spread over the remaining installments.
+    fn installment_for(&self, paid: u8, remaining: u64) -> u64 {
+        let installments_left = MICRO_BNPL_INSTALLMENTS.saturating_sub(paid) as u64;
+
+        if installments_left <= 1 {
+            return remaining;
+        }
+
+        match self.prepayment_mode {
+            PREPAYMENT_REDUCE_INSTALLMENTS => match remaining / installments_left {
+                0 => remaining,
+                installment => installment,
+            },
+            _ => std::cmp::min(self.amount / MICRO_BNPL_INSTALLMENTS as u64, remaining),
+        }
+    }
+
+    // Still owed on the current installment
+    pub fn next_installment(&self) -> u64 {
+        self.installment_for(self.paid_installments, self.scheduled_amount())
+            .saturating_sub(self.installment_covered)
+    }
+
+    // Pay towards the current installment; once nothing is owed on it, it counts as repaid and the
+    // next one is due. Returns whether the installment was settled.
+    pub fn pay_installment(&mut self, amount: u64) -> bool {
+        let owed = self.next_installment();
+
+        if amount < owed {
+            self.installment_covered += amount;
+            return false;
+        }
+
+        self.amount_repaid = self.amount_repaid.saturating_add(self.installment_covered + owed);
+        self.installment_covered = 0;
+        self.paid_installments += 1;
+        true
+    }
+
+    // Take an extra payment off the remaining amount, the mode picking how the installments left change
+    pub fn apply_prepayment(&mut self, amount: u64, mode: u8) -> Result<(), ProgramError> {
+        if mode != PREPAYMENT_SHORTEN_SCHEDULE && mode != PREPAYMENT_REDUCE_INSTALLMENTS {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if amount == 0 || amount > self.remaining_amount() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.amount_repaid += amount;
+        self.prepayment_mode = mode;
+        Ok(())
+    }
+
+    // Take a merchant refund off the remaining amount, the installments keeping their amount so the
+    // loan ends early. Returns the part already paid by the borrower that must be refunded.
+    pub fn apply_refund(&mut self, amount: u64) -> Result<u64, ProgramError> {
+        let refunded_amount = self.refunded_amount
+            .checked_add(amount)
+            .filter(|refunded| *refunded <= self.amount)
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        let owed_before = self.remaining_amount();
+        self.refunded_amount = refunded_amount;
+
+        Ok(amount - (owed_before - self.remaining_amount()))
+    }
+
+    pub fn is_payment_due(&self, current_time: i64) -> bool {
+        current_time >= self.next_payment_due
+    }
+
+    // Accrue a daily late fee on the overdue installment for each new full day of the grace period;
+    // returns the fee added
+    pub fn accrue_late_fees(&mut self, current_time: i64) -> u64 {
+        let days = current_time.saturating_sub(self.next_payment_due) / 86400;
+        if days <= 0 {
+            return 0;
+        }
+
+        let days_overdue = std::cmp::min(days, self.grace_period_days as i64) as u16;
+        let new_days = days_overdue.saturating_sub(self.days_overdue);
+
+        let late_fee = fee_amount(self.next_installment(), LATE_FEE_DAILY_BPS)
+            .saturating_mul(new_days as u64);
+
+        self.accrued_late_fees = self.accrued_late_fees.saturating_add(late_fee);
+        self.days_overdue = std::cmp::max(self.days_overdue, days_overdue);
+        late_fee
+    }
+
+    // Take the accrued late fees to be paid with the next payment; returns them
+    pub fn collect_late_fees(&mut self) -> u64 {
+        let late_fees = self.accrued_late_fees;
+        self.accrued_late_fees = 0;
+        self.days_overdue = 0;
+        late_fees
+    }
+
+    // Inside the reminder window before the next due date, and not reminded for it yet
+    pub fn needs_reminder(&self, current_time: i64, window_secs: i64) -> bool {
+        current_time < self.next_payment_due
+            && current_time >= self.next_payment_due - window_secs
+            && self.reminded_due != self.next_payment_due
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.held_at != 0
+    }
+
+    // Still unpaid once the grace period fixed at creation is over
+    pub fn is_defaulted(&self, current_time: i64) -> bool {
+        current_time >= self.next_payment_due.saturating_add(self.grace_period_days as i64 * 86400)
+    }
+
+    // Freeze the loan pending a dispute or legal resolution
+    pub fn place_hold(&mut self, current_time: i64) {
+        self.held_at = current_time;
+    }
+
+    // Resume the loan, pushing the due date back by the time spent on hold
+    pub fn release_hold(&mut self, current_time: i64) {
+        let held_for = current_time.saturating_sub(self.held_at);
+
+        self.next_payment_due = self.next_payment_due.saturating_add(held_for);
+        self.reminded_due = 0;
+        self.held_at = 0;
+    }
+
+    // (due date, amount) of every unpaid installment
+    pub fn upcoming_installments(&self) -> Vec<(i64, u64)> {
+        let mut installments = Vec::new();
+        let mut remaining = self.scheduled_amount();
+        let mut covered = self.installment_covered;
+        let mut due = self.next_payment_due;
+
+        for paid in self.paid_installments..MICRO_BNPL_INSTALLMENTS {
+            let installment = self.installment_for(paid, remaining);
+
+            // A shortened schedule ends once nothing is left
+            if installment == 0 {
+                break;
+            }
+
+            installments.push((due, installment.saturating_sub(covered)));
+            remaining = remaining.saturating_sub(installment);
+            covered = 0;
+            due += MICRO_BNPL_INTERVAL_DAYS * 86400;
+        }
+
+        installments
+    }
+}
+
+// A borrower's open micro loans and what is still owed on them, kept up to date by the loan flows so
+// exposure can be checked and loans listed without scanning program accounts. This is also the index
+// clients enumerate a borrower's loans from: loans are added on creation and removed once settled, and
+// one account is enough since a borrower has a single micro loan open at a time.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct BorrowerRegistryAccount {
+    pub borrower: Pubkey,
+    pub contract_count: u8,
+    pub contracts: [Pubkey; MAX_BORROWER_CONTRACTS],
+    pub outstanding_debt: u64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl BorrowerRegistryAccount {
+    pub const SIZE: usize = 32 + 1 + (32 * MAX_BORROWER_CONTRACTS) + 8 + 8 + 1; // 306 bytes
+
+    pub fn new(borrower: Pubkey, created_at: i64, bump: u8) -> Self {
+        Self {
+            borrower,
+            contract_count: 0,
+            contracts: [Pubkey::default(); MAX_BORROWER_CONTRACTS],
+            outstanding_debt: 0,
+            updated_at: created_at,
+            bump,
+        }
+    }
+
+    pub fn active_contracts(&self) -> &[Pubkey] {
+        &self.contracts[..self.contract_count as usize]
+    }
+
+    pub fn contains(&self, contract: &Pubkey) -> bool {
+        self.active_contracts().contains(contract)
+    }
+
+    // Track a new loan and the amount it adds to the debt; false when already tracked or full
+    pub fn register(&mut self, contract: Pubkey, amount: u64, current_time: i64) -> bool {
+        let count = self.contract_count as usize;
+        if self.contains(&contract) || count >= MAX_BORROWER_CONTRACTS {
+            return false;
+        }
+
+        self.contracts[count] = contract;
+        self.contract_count += 1;
+        self.outstanding_debt = self.outstanding_debt.saturating_add(amount);
+        self.updated_at = current_time;
+        true
+    }
+
+    // Take a repayment off the debt; a settled loan leaves the registry
+    pub fn record_repayment(&mut self, contract: &Pubkey, amount: u64, settled: bool, current_time: i64) {
+        self.outstanding_debt = self.outstanding_debt.saturating_sub(amount);
+        self.updated_at = current_time;
+
+        if !settled {
+            return;
+        }
+
+        let count = self.contract_count as usize;
+        if let Some(index) = self.contracts[..count].iter().position(|key| key == contract) {
+            // Keep the list compact by moving the last entry into the gap
+            self.contracts[index] = self.contracts[count - 1];
+            self.contracts[count - 1] = Pubkey::default();
+            self.contract_count -= 1;
+        }
+    }
+}
+
+// Goodwill budget support staff draw on to forgive late fees, capped per 30-day period
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ForgivenessBudgetAccount {
+    pub monthly_cap: u64,
+    pub period_start: i64,
+    pub forgiven_in_period: u64,
+    pub total_forgiven: u64,
+    pub updated_by: Pubkey,  // Treasurer who last set the cap
+    pub bump: u8,
+}
+
+impl ForgivenessBudgetAccount {
+    pub const SIZE: usize = 8 + 8 + 8 + 8 + 32 + 1; // 65 bytes
+
+    // Start a new period once the current one is over, keeping periods aligned on the first one
+    pub fn roll_period(&mut self, current_time: i64) {
+        let period = FORGIVENESS_PERIOD_DAYS * 86400;
+        let elapsed = current_time.saturating_sub(self.period_start);
+
+        if elapsed >= period {
+            self.period_start += elapsed - elapsed % period;
+            self.forgiven_in_period = 0;
+        }
+    }
+
+    pub fn remaining_budget(&self) -> u64 {
+        self.monthly_cap.saturating_sub(self.forgiven_in_period)
+    }
+
+    // Draw an amount from the current period's budget; false when it doesn't fit
+    pub fn spend(&mut self, amount: u64, current_time: i64) -> bool {
+        self.roll_period(current_time);
+
+        if amount > self.remaining_budget() {
+            return false;
+        }
+
+        self.forgiven_in_period += amount;
+        self.total_forgiven = self.total_forgiven.saturating_add(amount);
+        true
+    }
+}
+
+// Lamport pool paying keepers a bounty for repayment checks that accrue a late fee; funded by plain
+// transfers to its address, the rent-exempt reserve is never paid out
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct KeeperPoolAccount {
+    pub bounty_lamports: u64, // Paid per check that changes a contract
+    pub total_paid: u64,
+    pub updated_by: Pubkey,  // Treasurer who last set the bounty
+    pub bump: u8,
+}
+
+impl KeeperPoolAccount {
+    pub const SIZE: usize = 8 + 8 + 32 + 1; // 49 bytes
+}
+
+// Digest of a user's upcoming installments, anchored for the mobile reminder calendar
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct InstallmentCalendarAccount {
+    pub owner: Pubkey,
+    pub digest: [u8; 32],
+    pub installment_count: u16,
+    pub next_due: i64,       // Earliest upcoming due date, 0 when nothing is due
+    pub computed_at: i64,
+    pub bump: u8,
+}
+
+impl InstallmentCalendarAccount {
+    pub const SIZE: usize = 32 + 32 + 2 + 8 + 8 + 1; // 83 bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn micro_loan(amount: u64) -> MicroBNPLAccount {
+        MicroBNPLAccount {
+            borrower: Pubkey::new_unique(),
+            merchant: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            amount,
+            amount_repaid: 0,
+            paid_installments: 0,
+            next_payment_due: 0,
+            escrowed_amount: 0,
+            escrow_release_at: 0,
+            created_at: 0,
+            reminded_due: 0,
+            snoozed: false,
+            held_at: 0,
+            prepayment_mode: 0,
+            refunded_amount: 0,
+            accrued_late_fees: 0,
+            days_overdue: 0,
+            grace_period_days: 0,
+            previous_merchant: Pubkey::default(),
+            staking_account: Pubkey::new_unique(),
+            stake_hold: 0,
+            installment_covered: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn partial_payments_add_up_to_the_installment() {
+        let mut loan = micro_loan(1_000);
+
+        assert!(!loan.pay_installment(200));
+        assert_eq!(loan.next_installment(), 300);
+        assert_eq!(loan.remaining_amount(), 800);
+        assert_eq!(loan.paid_installments, 0);
+
+        assert!(loan.pay_installment(300));
+        assert_eq!(loan.paid_installments, 1);
+        assert_eq!(loan.amount_repaid, 500);
+        assert_eq!(loan.next_installment(), 500);
+    }
+
+    #[test]
+    fn covered_part_is_left_out_of_the_schedule() {
+        let mut loan = micro_loan(1_000);
+        loan.pay_installment(200);
+
+        let amounts: Vec<u64> = loan.upcoming_installments().iter().map(|(_, amount)| *amount).collect();
+        assert_eq!(amounts, vec![300, 500]);
+    }
+}
@@ -4,6 +4,8 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+use crate::constants::{CARD_TIER_REVIEW_PERIOD_DAYS, CARD_TIER_SPEND_THRESHOLDS};
+
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct CardAccount {
     pub owner: Pubkey,
@@ -12,11 +14,15 @@ pub struct CardAccount {
     pub expires_at: i64,
     pub is_active: bool,
     pub annual_fee_paid_until: i64,
+    pub verification_key: Pubkey, // Derived off-chain from the salted card data, default when unset
+    pub benefits_tier: u8,        // Effective benefits tier, reviewed yearly from spend
+    pub current_period_spend: u64,
+    pub last_tier_review: i64,
     pub bump: u8,
 }
 
 impl CardAccount {
-    pub const SIZE: usize = 32 + 1 + 8 + 8 + 1 + 8 + 1; // 59 bytes
+    pub const SIZE: usize = 32 + 1 + 8 + 8 + 1 + 8 + 32 + 1 + 8 + 8 + 1; // 108 bytes
 
     pub fn new(
         owner: Pubkey,
@@ -34,6 +40,10 @@ impl CardAccount {
             expires_at,
             is_active: true,
             annual_fee_paid_until: issued_at + (365 * 86400), // Paid for 1 year
+            verification_key: Pubkey::default(),
+            benefits_tier: card_type,
+            current_period_spend: 0,
+            last_tier_review: issued_at,
             bump,
         }
     }
@@ -46,8 +56,117 @@ impl CardAccount {
         current_time >= self.annual_fee_paid_until
     }
 
+    pub fn has_commitment(&self) -> bool {
+        self.verification_key != Pubkey::default()
+    }
+
+    pub fn record_spend(&mut self, amount: u64) {
+        self.current_period_spend = self.current_period_spend.saturating_add(amount);
+    }
+
+    pub fn is_tier_review_due(&self, current_time: i64) -> bool {
+        current_time >= self.last_tier_review + (CARD_TIER_REVIEW_PERIOD_DAYS * 86400)
+    }
+
+    // Move the benefits tier one step toward the tier earned by spend,
+    // never below the card type the user pays for
+    pub fn review_benefits_tier(&mut self, current_time: i64) {
+        let earned_tier = CARD_TIER_SPEND_THRESHOLDS
+            .iter()
+            .rposition(|threshold| self.current_period_spend >= *threshold)
+            .unwrap_or(0) as u8;
+        let target_tier = std::cmp::max(earned_tier, self.card_type);
+
+        if target_tier > self.benefits_tier {
+            self.benefits_tier += 1;
+        } else if target_tier < self.benefits_tier {
+            self.benefits_tier -= 1;
+        }
+
+        self.current_period_spend = 0;
+        self.last_tier_review = current_time;
+    }
+
     pub fn pay_annual_fee(&mut self, current_time: i64) {
         // Add 1 year to the fee expiration date
         self.annual_fee_paid_until = current_time + (365 * 86400);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{CARD_STANDARD, CARD_SILVER, CARD_GOLD, CARD_PLATINUM};
+
+    const YEAR: i64 = CARD_TIER_REVIEW_PERIOD_DAYS * 86400;
+
+    fn card(card_type: u8) -> CardAccount {
+        CardAccount::new(Pubkey::new_unique(), card_type, 0, 255)
+    }
+
+    #[test]
+    fn review_moves_one_tier_up_at_a_time() {
+        let mut card = card(CARD_STANDARD);
+
+        card.record_spend(CARD_TIER_SPEND_THRESHOLDS[CARD_PLATINUM as usize]);
+        card.review_benefits_tier(YEAR);
+        assert_eq!(card.benefits_tier, CARD_SILVER);
+
+        card.record_spend(CARD_TIER_SPEND_THRESHOLDS[CARD_PLATINUM as usize]);
+        card.review_benefits_tier(2 * YEAR);
+        assert_eq!(card.benefits_tier, CARD_GOLD);
+    }
+
+    #[test]
+    fn review_moves_one_tier_down_without_spend() {
+        let mut card = card(CARD_STANDARD);
+        card.benefits_tier = CARD_PLATINUM;
+
+        card.review_benefits_tier(YEAR);
+        assert_eq!(card.benefits_tier, CARD_GOLD);
+
+        // Spend matching the current tier keeps it
+        card.record_spend(CARD_TIER_SPEND_THRESHOLDS[CARD_GOLD as usize]);
+        card.review_benefits_tier(2 * YEAR);
+        assert_eq!(card.benefits_tier, CARD_GOLD);
+    }
+
+    #[test]
+    fn review_never_drops_below_the_card_type() {
+        let mut card = card(CARD_GOLD);
+
+        card.review_benefits_tier(YEAR);
+        assert_eq!(card.benefits_tier, CARD_GOLD);
+
+        card.benefits_tier = CARD_PLATINUM;
+        card.review_benefits_tier(2 * YEAR);
+        assert_eq!(card.benefits_tier, CARD_GOLD);
+    }
+
+    #[test]
+    fn review_uses_the_highest_threshold_reached() {
+        let mut card = card(CARD_SILVER);
+
+        card.record_spend(CARD_TIER_SPEND_THRESHOLDS[CARD_GOLD as usize] - 1);
+        card.review_benefits_tier(YEAR);
+        assert_eq!(card.benefits_tier, CARD_SILVER);
+
+        card.record_spend(CARD_TIER_SPEND_THRESHOLDS[CARD_GOLD as usize]);
+        card.review_benefits_tier(2 * YEAR);
+        assert_eq!(card.benefits_tier, CARD_GOLD);
+    }
+
+    #[test]
+    fn review_starts_a_new_period() {
+        let mut card = card(CARD_STANDARD);
+        card.record_spend(1_000);
+
+        assert!(!card.is_tier_review_due(YEAR - 1));
+        assert!(card.is_tier_review_due(YEAR));
+
+        card.review_benefits_tier(YEAR);
+        assert_eq!(card.current_period_spend, 0);
+        assert_eq!(card.last_tier_review, YEAR);
+        assert!(!card.is_tier_review_due(2 * YEAR - 1));
+    }
+}
@@ -0,0 +1,76 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::{CARD_TYPE_COUNT, SCORE_TIER_COUNT, MAX_ENABLED_REGIONS};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ConfigAccount {
+    pub authority: Pubkey,
+    // Minimum score to enter each tier above the lowest one
+    pub score_tier_thresholds: [u16; SCORE_TIER_COUNT - 1],
+    // Loan-to-value in basis points, indexed by [card_type][score_tier]
+    pub ltv_bps: [[u16; SCORE_TIER_COUNT]; CARD_TYPE_COUNT],
+    // Share of the treasury that may be deployed into yield strategies
+    pub treasury_deploy_cap_bps: u16,
+    // Micro-BNPL: largest fee-free loan and its relaxed collateral ratio
+    pub micro_bnpl_threshold: u64,
+    pub micro_bnpl_ltv_bps: u16,
+    // Minimum score to take a BNPL loan, per card type
+    pub min_bnpl_score: [u16; CARD_TYPE_COUNT],
+    // Phased rollout: when gating is on, only users of an enabled region may open loans or mint cards
+    pub region_gating: bool,
+    pub enabled_regions: [u16; MAX_ENABLED_REGIONS], // 0 for an empty slot
+    // Staking rewards: current APY and the cumulative rate index (APY bps × seconds),
+    // checkpointed whenever the APY changes
+    pub staking_reward_apy_bps: u16,
+    pub reward_index: u128,
+    pub reward_index_updated_at: i64,
+    // Performance fee withheld from harvested yield, per card type
+    pub yield_performance_fee_bps: [u16; CARD_TYPE_COUNT],
+    // Penalty on the still locked part of an emergency staking withdrawal
+    pub emergency_withdraw_penalty_bps: u16,
+    pub bump: u8,
+}
+
+impl ConfigAccount {
+    pub const SIZE: usize = 32 + (2 * (SCORE_TIER_COUNT - 1)) + (2 * SCORE_TIER_COUNT * CARD_TYPE_COUNT) + 2 + 8 + 2 + (2 * CARD_TYPE_COUNT) + 1 + (2 * MAX_ENABLED_REGIONS) + 2 + 16 + 8 + (2 * CARD_TYPE_COUNT) + 2 + 1; // 150 bytes
+
+    pub fn get_score_tier(&self, score: u16) -> usize {
+        self.score_tier_thresholds
+            .iter()
+            .filter(|threshold| score >= **threshold)
+            .count()
+    }
+
+    pub fn get_ltv_bps(&self, card_type: u8, score: u16) -> u16 {
+        let card_index = std::cmp::min(card_type as usize, CARD_TYPE_COUNT - 1);
+        self.ltv_bps[card_index][self.get_score_tier(score)]
+    }
+
+    pub fn get_yield_performance_fee_bps(&self, card_type: u8) -> u16 {
+        let card_index = std::cmp::min(card_type as usize, CARD_TYPE_COUNT - 1);
+        self.yield_performance_fee_bps[card_index]
+    }
+
+    pub fn get_min_bnpl_score(&self, card_type: u8) -> u16 {
+        let card_index = std::cmp::min(card_type as usize, CARD_TYPE_COUNT - 1);
+        self.min_bnpl_score[card_index]
+    }
+
+    pub fn is_region_enabled(&self, region: u16) -> bool {
+        !self.region_gating || (region != 0 && self.enabled_regions.contains(&region))
+    }
+
+    // Reward index at a given time: the checkpoint extended at the current APY
+    pub fn get_reward_index(&self, current_time: i64) -> u128 {
+        let elapsed = current_time.saturating_sub(self.reward_index_updated_at).max(0) as u128;
+        self.reward_index.saturating_add((self.staking_reward_apy_bps as u128).saturating_mul(elapsed))
+    }
+
+    // Checkpoint the index before changing the APY so past time keeps its old rate
+    pub fn set_staking_reward_apy(&mut self, apy_bps: u16, current_time: i64) {
+        self.reward_index = self.get_reward_index(current_time);
+        self.reward_index_updated_at = current_time;
+        self.staking_reward_apy_bps = apy_bps;
+    }
+}
@@ -0,0 +1,55 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::SECONDS_PER_YEAR;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CharityAccount {
+    pub wallet: Pubkey, // Owner of the token accounts receiving donations
+    pub is_active: bool,
+    pub registered_at: i64,
+    pub total_received: u64,
+    pub bump: u8,
+}
+
+impl CharityAccount {
+    pub const SIZE: usize = 32 + 1 + 8 + 8 + 1; // 50 bytes
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct DonationAccount {
+    pub owner: Pubkey,
+    pub charity: Pubkey, // Charity wallet receiving the donations
+    pub giving_year: u16,
+    pub year_donated: u64,
+    pub previous_year_donated: u64,
+    pub total_donated: u64,
+    pub bump: u8,
+}
+
+impl DonationAccount {
+    pub const SIZE: usize = 32 + 32 + 2 + 8 + 8 + 8 + 1; // 91 bytes
+
+    // Calendar year (approximate) of a timestamp
+    pub fn get_giving_year(current_time: i64) -> u16 {
+        (1970 + current_time / SECONDS_PER_YEAR) as u16
+    }
+
+    // Add a donation to the yearly summary, rolling the year over when needed
+    pub fn record_donation(&mut self, amount: u64, current_time: i64) {
+        let year = Self::get_giving_year(current_time);
+
+        if year != self.giving_year {
+            self.previous_year_donated = if year == self.giving_year + 1 {
+                self.year_donated
+            } else {
+                0
+            };
+            self.year_donated = 0;
+            self.giving_year = year;
+        }
+
+        self.year_donated = self.year_donated.saturating_add(amount);
+        self.total_donated = self.total_donated.saturating_add(amount);
+    }
+}
@@ -0,0 +1,56 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::math::{bps_share, Rounding};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct EmissionsScheduleAccount {
+    pub reward_mint: Pubkey,
+    pub emission_per_epoch: u64,
+    pub staking_share_bps: u16,
+    pub repayment_share_bps: u16,
+    pub liquidity_share_bps: u16,
+    pub bump: u8,
+}
+
+impl EmissionsScheduleAccount {
+    pub const SIZE: usize = 32 + 8 + 2 + 2 + 2 + 1; // 47 bytes
+
+    // Emission of one activity category for the epoch
+    pub fn get_category_emission(&self, share_bps: u16) -> u64 {
+        bps_share(self.emission_per_epoch, share_bps, Rounding::Down)
+    }
+}
+
+// Activity totals of every user for one epoch, with the category emissions of the schedule
+// in force when the epoch's first activity was recorded
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct EpochActivityAccount {
+    pub epoch: u64,
+    pub total_staking: u64,
+    pub total_repayment: u64,
+    pub total_liquidity: u64,
+    pub staking_emission: u64,
+    pub repayment_emission: u64,
+    pub liquidity_emission: u64,
+    pub bump: u8,
+}
+
+impl EpochActivityAccount {
+    pub const SIZE: usize = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1; // 57 bytes
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct UserActivityAccount {
+    pub owner: Pubkey,
+    pub epoch: u64,
+    pub staking: u64,       // Stake deposited in the epoch, valued in USD
+    pub repayment: u64,     // Micro loan repayments made in the epoch without late fees
+    pub liquidity: u64,     // Insurance reserve funding provided in the epoch
+    pub distributed: bool,
+    pub bump: u8,
+}
+
+impl UserActivityAccount {
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 8 + 1 + 1; // 66 bytes
+}
@@ -0,0 +1,52 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
+pub enum EscrowStatus {
+    Active,
+    Settled,
+}
+
+impl EscrowStatus {
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            EscrowStatus::Active => 0,
+            EscrowStatus::Settled => 1,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(EscrowStatus::Active),
+            1 => Ok(EscrowStatus::Settled),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct DepositEscrowAccount {
+    pub user: Pubkey,
+    pub beneficiary: Pubkey,    // Landlord or utility requiring the deposit
+    pub arbiter: Pubkey,
+    pub staking_account: Pubkey,
+    pub amount: u64,
+    pub status: u8,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl DepositEscrowAccount {
+    pub const SIZE: usize = 32 + 32 + 32 + 32 + 8 + 1 + 8 + 1; // 146 bytes
+
+    pub fn get_status(&self) -> Result<EscrowStatus, ProgramError> {
+        EscrowStatus::from_u8(self.status)
+    }
+
+    pub fn set_status(&mut self, status: EscrowStatus) {
+        self.status = status.to_u8();
+    }
+}
@@ -0,0 +1,93 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::constants::MAX_PROPOSAL_DATA_LEN;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
+pub enum ProposalStatus {
+    Voting,
+    Queued,
+    Executed,
+    Defeated,
+}
+
+impl ProposalStatus {
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            ProposalStatus::Voting => 0,
+            ProposalStatus::Queued => 1,
+            ProposalStatus::Executed => 2,
+            ProposalStatus::Defeated => 3,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(ProposalStatus::Voting),
+            1 => Ok(ProposalStatus::Queued),
+            2 => Ok(ProposalStatus::Executed),
+            3 => Ok(ProposalStatus::Defeated),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct GovernanceAccount {
+    pub authority: Pubkey,
+    pub proposal_count: u64,
+    pub voting_period: i64,    // Seconds
+    pub execution_delay: i64,  // Seconds between queueing and execution
+    pub quorum_votes: u64,     // Minimum total vote weight
+    pub bump: u8,
+}
+
+impl GovernanceAccount {
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 8 + 1; // 65 bytes
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProposalAccount {
+    pub proposer: Pubkey,
+    pub proposal_id: u64,
+    pub instruction_len: u8,
+    pub instruction_data: [u8; MAX_PROPOSAL_DATA_LEN], // Config instruction executed on success
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub voting_ends_at: i64,
+    pub executable_at: i64,    // 0 until queued
+    pub status: u8,
+    pub bump: u8,
+}
+
+impl ProposalAccount {
+    pub const SIZE: usize = 32 + 8 + 1 + MAX_PROPOSAL_DATA_LEN + 8 + 8 + 8 + 8 + 1 + 1; // 139 bytes
+
+    pub fn get_status(&self) -> Result<ProposalStatus, ProgramError> {
+        ProposalStatus::from_u8(self.status)
+    }
+
+    pub fn set_status(&mut self, status: ProposalStatus) {
+        self.status = status.to_u8();
+    }
+
+    pub fn instruction(&self) -> &[u8] {
+        &self.instruction_data[..self.instruction_len as usize]
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct VoteRecordAccount {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub weight: u64,
+    pub support: bool,
+    pub bump: u8,
+}
+
+impl VoteRecordAccount {
+    pub const SIZE: usize = 32 + 32 + 8 + 1 + 1; // 74 bytes
+}
@@ -0,0 +1,166 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::constants::{MERCHANT_SETTLEMENT_EPOCHS, MERCHANT_FEE_OVERRIDE_NONE};
+use crate::math::fee_amount;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
+pub enum MerchantStatus {
+    Pending,
+    Active,
+    Vetoed,
+    Suspended,
+}
+
+impl MerchantStatus {
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            MerchantStatus::Pending => 0,
+            MerchantStatus::Active => 1,
+            MerchantStatus::Vetoed => 2,
+            MerchantStatus::Suspended => 3,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(MerchantStatus::Pending),
+            1 => Ok(MerchantStatus::Active),
+            2 => Ok(MerchantStatus::Vetoed),
+            3 => Ok(MerchantStatus::Suspended),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct MerchantAccount {
+    pub owner: Pubkey,
+    pub status: u8,
+    pub bond_lamports: u64,
+    pub registered_at: i64,
+    pub challenge_period_end: i64,
+    pub name_hash: [u8; 32],              // Hash of the merchant's registered business name
+    pub settlement_token_account: Pubkey, // Where payments go; default means the owner's token account
+    pub fee_bps: u16,                     // Fee withheld from each payment for the treasury
+    pub fee_override_bps: u16,            // Negotiated fee replacing fee_bps, MERCHANT_FEE_OVERRIDE_NONE if none
+    pub bump: u8,
+}
+
+impl MerchantAccount {
+    pub const SIZE: usize = 32 + 1 + 8 + 8 + 8 + 32 + 32 + 2 + 2 + 1; // 126 bytes
+
+    pub fn new(
+        owner: Pubkey,
+        bond_lamports: u64,
+        registered_at: i64,
+        challenge_period_end: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            owner,
+            status: MerchantStatus::Pending.to_u8(),
+            bond_lamports,
+            registered_at,
+            challenge_period_end,
+            name_hash: [0; 32],
+            settlement_token_account: Pubkey::default(),
+            fee_bps: 0,
+            fee_override_bps: MERCHANT_FEE_OVERRIDE_NONE,
+            bump,
+        }
+    }
+
+    // A pending merchant becomes active once the challenge period passes without a veto
+    pub fn get_status(&self, current_time: i64) -> Result<MerchantStatus, ProgramError> {
+        match MerchantStatus::from_u8(self.status)? {
+            MerchantStatus::Pending if current_time >= self.challenge_period_end => Ok(MerchantStatus::Active),
+            status => Ok(status),
+        }
+    }
+
+    pub fn set_status(&mut self, status: MerchantStatus) {
+        self.status = status.to_u8();
+    }
+
+    pub fn is_active(&self, current_time: i64) -> bool {
+        matches!(self.get_status(current_time), Ok(MerchantStatus::Active))
+    }
+
+    pub fn has_fee_override(&self) -> bool {
+        self.fee_override_bps != MERCHANT_FEE_OVERRIDE_NONE
+    }
+
+    // Rate charged to the merchant: the negotiated one if set, its standard fee otherwise
+    pub fn fee_rate_bps(&self) -> u16 {
+        if self.has_fee_override() {
+            self.fee_override_bps
+        } else {
+            self.fee_bps
+        }
+    }
+
+    pub fn get_fee(&self, amount: u64) -> u64 {
+        fee_amount(amount, self.fee_rate_bps())
+    }
+
+    // Fee with its rate lowered by the borrower's lock discount (see StakingAccount::lock_fee_discount)
+    pub fn get_discounted_fee(&self, amount: u64, discount_bps: u16) -> u64 {
+        fee_amount(amount, self.fee_rate_bps().saturating_sub(discount_bps))
+    }
+}
+
+// Totals settled to a merchant during one epoch
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, Copy)]
+pub struct SettlementPeriod {
+    pub epoch: u64,
+    pub gross_volume: u64,
+    pub fees: u64,
+    pub refunds: u64,
+    pub net_payout: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct MerchantSettlementAccount {
+    pub merchant: Pubkey,
+    pub periods: [SettlementPeriod; MERCHANT_SETTLEMENT_EPOCHS], // Most recent epochs, oldest overwritten first
+    pub bump: u8,
+}
+
+impl MerchantSettlementAccount {
+    pub const SIZE: usize = 32 + (40 * MERCHANT_SETTLEMENT_EPOCHS) + 1; // 353 bytes
+
+    pub fn new(merchant: Pubkey, bump: u8) -> Self {
+        Self {
+            merchant,
+            periods: [SettlementPeriod::default(); MERCHANT_SETTLEMENT_EPOCHS],
+            bump,
+        }
+    }
+
+    pub fn get_period(&self, epoch: u64) -> Option<&SettlementPeriod> {
+        self.periods.iter().find(|period| period.epoch == epoch && period.gross_volume > 0)
+    }
+
+    // Add a payment to the epoch's totals, reusing the slot of the epoch that falls out of the window
+    pub fn record_payment(&mut self, epoch: u64, amount: u64, fee: u64) {
+        let index = (epoch % MERCHANT_SETTLEMENT_EPOCHS as u64) as usize;
+        let period = &mut self.periods[index];
+
+        if period.epoch != epoch {
+            *period = SettlementPeriod {
+                epoch,
+                ..SettlementPeriod::default()
+            };
+        }
+
+        period.gross_volume = period.gross_volume.saturating_add(amount);
+        period.fees = period.fees.saturating_add(fee);
+        period.net_payout = period.gross_volume
+            .saturating_sub(period.fees)
+            .saturating_sub(period.refunds);
+    }
+}
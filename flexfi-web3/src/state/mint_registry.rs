@@ -0,0 +1,86 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::MAX_ACCEPTED_MINTS;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct AcceptedMintsAccount {
+    pub authority: Pubkey,
+    pub mint_count: u8,
+    pub mints: [Pubkey; MAX_ACCEPTED_MINTS],
+    pub price_feeds: [Pubkey; MAX_ACCEPTED_MINTS], // Pyth feed per mint, default for stablecoins
+    pub payment_mints: [bool; MAX_ACCEPTED_MINTS], // BNPL loans can be denominated in the mint
+    pub bump: u8,
+}
+
+impl AcceptedMintsAccount {
+    pub const SIZE: usize = 32 + 1 + (32 * MAX_ACCEPTED_MINTS) + (32 * MAX_ACCEPTED_MINTS) + MAX_ACCEPTED_MINTS + 1; // 554 bytes
+
+    pub fn is_accepted(&self, mint: &Pubkey) -> bool {
+        self.mints[..self.mint_count as usize].contains(mint)
+    }
+
+    pub fn get_price_feed(&self, mint: &Pubkey) -> Option<Pubkey> {
+        self.mints[..self.mint_count as usize]
+            .iter()
+            .position(|m| m == mint)
+            .map(|index| self.price_feeds[index])
+    }
+
+    pub fn set_price_feed(&mut self, mint: &Pubkey, price_feed: Pubkey) -> bool {
+        match self.mints[..self.mint_count as usize].iter().position(|m| m == mint) {
+            Some(index) => {
+                self.price_feeds[index] = price_feed;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn is_payment_mint(&self, mint: &Pubkey) -> bool {
+        self.mints[..self.mint_count as usize]
+            .iter()
+            .position(|m| m == mint)
+            .is_some_and(|index| self.payment_mints[index])
+    }
+
+    pub fn set_payment_mint(&mut self, mint: &Pubkey, enabled: bool) -> bool {
+        match self.mints[..self.mint_count as usize].iter().position(|m| m == mint) {
+            Some(index) => {
+                self.payment_mints[index] = enabled;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn add_mint(&mut self, mint: Pubkey) -> bool {
+        if self.is_accepted(&mint) || self.mint_count as usize >= MAX_ACCEPTED_MINTS {
+            return false;
+        }
+
+        self.mints[self.mint_count as usize] = mint;
+        self.price_feeds[self.mint_count as usize] = Pubkey::default();
+        self.payment_mints[self.mint_count as usize] = false;
+        self.mint_count += 1;
+        true
+    }
+
+    pub fn remove_mint(&mut self, mint: &Pubkey) -> bool {
+        let count = self.mint_count as usize;
+        match self.mints[..count].iter().position(|m| m == mint) {
+            Some(index) => {
+                // Keep the list compact by moving the last entry into the gap
+                self.mints[index] = self.mints[count - 1];
+                self.mints[count - 1] = Pubkey::default();
+                self.price_feeds[index] = self.price_feeds[count - 1];
+                self.price_feeds[count - 1] = Pubkey::default();
+                self.payment_mints[index] = self.payment_mints[count - 1];
+                self.payment_mints[count - 1] = false;
+                self.mint_count -= 1;
+                true
+            },
+            None => false,
+        }
+    }
+}
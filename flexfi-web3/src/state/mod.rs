@@ -7,13 +7,45 @@ pub mod score;
 pub mod yield_;
 pub mod whitelist;
 pub mod authorization;  
+pub mod mint_registry;
+pub mod config;
+pub mod snapshot;
+pub mod merchant;
+pub mod escrow;
+pub mod staking_tier;
+pub mod savings;
+pub mod donation;
+pub mod recovery;
+pub mod promo;
+pub mod access;
+pub mod governance;
+pub mod emissions;
+pub mod admin;
+pub mod treasury;
+pub mod receipt;
 
-pub use wallet::WalletAccount;
+pub use wallet::{WalletAccount, BackendIdAccount};
 pub use staking::{StakingAccount, StakingStatus};
-pub use bnpl::{BNPLContractAccount, BNPLStatus};
+pub use bnpl::{BNPLContractAccount, BNPLStatus, MicroBNPLAccount, InstallmentCalendarAccount, BorrowerRegistryAccount, ForgivenessBudgetAccount, KeeperPoolAccount};
 pub use card::CardAccount;
 pub use nft::{NFTMetadataAccount, NFTAttachmentAccount, NFTType};
 pub use score::ScoreAccount;
-pub use yield_::{YieldAccount, YieldStrategy};
-pub use whitelist::{WhitelistAccount, UserWhitelistStatus};
-pub use authorization::AuthorizationAccount;  
\ No newline at end of file
+pub use yield_::{YieldAccount, YieldStrategy, TreasuryYieldAccount};
+pub use whitelist::{WhitelistAccount, UserWhitelistStatus, BlocklistStatus, BlocklistReason};
+pub use authorization::{AuthorizationAccount, CreditReservation};
+pub use mint_registry::AcceptedMintsAccount;
+pub use config::ConfigAccount;
+pub use snapshot::StakingSnapshotAccount;
+pub use merchant::{MerchantAccount, MerchantStatus, MerchantSettlementAccount, SettlementPeriod};
+pub use escrow::{DepositEscrowAccount, EscrowStatus};
+pub use staking_tier::{StakingTierConfig, StakingTier};
+pub use savings::SavingsGoalAccount;
+pub use donation::{CharityAccount, DonationAccount};
+pub use recovery::WalletRecoveryAccount;
+pub use promo::PromoCodeAccount;
+pub use access::DataAccessGrant;
+pub use governance::{GovernanceAccount, ProposalAccount, ProposalStatus, VoteRecordAccount};
+pub use emissions::{EmissionsScheduleAccount, EpochActivityAccount, UserActivityAccount};
+pub use admin::{AdminListAccount, AdminRole};
+pub use treasury::{TreasuryStreamAccount, TreasuryStateAccount};
+pub use receipt::PurchaseReceiptAccount;
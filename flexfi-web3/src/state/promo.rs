@@ -0,0 +1,30 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::math::{bps_share, Rounding};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PromoCodeAccount {
+    pub authority: Pubkey,
+    pub code_hash: [u8; 32],  // Hash of the code; the code itself stays off-chain until redeemed
+    pub mint: Pubkey,         // Mint of the campaign budget held by the promo vault
+    pub discount_bps: u16,
+    pub max_discount: u64,    // Cap per redemption
+    pub max_uses: u32,
+    pub use_count: u32,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl PromoCodeAccount {
+    pub const SIZE: usize = 32 + 32 + 32 + 2 + 8 + 4 + 4 + 8 + 1; // 123 bytes
+
+    pub fn is_redeemable(&self, current_time: i64) -> bool {
+        self.use_count < self.max_uses && current_time < self.expires_at
+    }
+
+    pub fn get_discount(&self, amount: u64) -> u64 {
+        let discount = bps_share(amount, self.discount_bps, Rounding::Down);
+        std::cmp::min(discount, self.max_discount)
+    }
+}
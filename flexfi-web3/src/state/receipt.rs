@@ -0,0 +1,43 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    keccak::hashv,
+    pubkey::Pubkey,
+};
+
+// Proof of purchase of a BNPL loan. The commitment lets a merchant check a receipt shown by the
+// customer offline, by recomputing the hash of its fields
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PurchaseReceiptAccount {
+    pub contract: Pubkey,
+    pub borrower: Pubkey,
+    pub merchant: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub purchased_at: i64,
+    pub issued_at: i64,
+    pub commitment: [u8; 32],
+    pub bump: u8,
+}
+
+impl PurchaseReceiptAccount {
+    pub const SIZE: usize = 32 + 32 + 32 + 32 + 8 + 8 + 8 + 32 + 1; // 185 bytes
+
+    // keccak256(contract || borrower || merchant || token_mint || amount (u64 LE) || purchased_at (i64 LE))
+    pub fn compute_commitment(
+        contract: &Pubkey,
+        borrower: &Pubkey,
+        merchant: &Pubkey,
+        token_mint: &Pubkey,
+        amount: u64,
+        purchased_at: i64,
+    ) -> [u8; 32] {
+        hashv(&[
+            contract.as_ref(),
+            borrower.as_ref(),
+            merchant.as_ref(),
+            token_mint.as_ref(),
+            &amount.to_le_bytes(),
+            &purchased_at.to_le_bytes(),
+        ]).to_bytes()
+    }
+}
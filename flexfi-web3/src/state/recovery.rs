@@ -0,0 +1,40 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::MAX_WALLET_GUARDIANS;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct WalletRecoveryAccount {
+    pub wallet: Pubkey,
+    pub guardian_count: u8,
+    pub guardians: [Pubkey; MAX_WALLET_GUARDIANS],
+    pub threshold: u8,             // Guardian approvals needed to start a recovery
+    pub pending_owner: Pubkey,     // Default when no recovery is in progress
+    pub approvals: u8,             // Bitmask over the guardian list
+    pub recovery_unlocks_at: i64,  // 0 until the threshold is reached
+    pub bump: u8,
+}
+
+impl WalletRecoveryAccount {
+    pub const SIZE: usize = 32 + 1 + (32 * MAX_WALLET_GUARDIANS) + 1 + 32 + 1 + 8 + 1; // 236 bytes
+
+    pub fn guardian_index(&self, guardian: &Pubkey) -> Option<usize> {
+        self.guardians[..self.guardian_count as usize]
+            .iter()
+            .position(|g| g == guardian)
+    }
+
+    pub fn approval_count(&self) -> u8 {
+        self.approvals.count_ones() as u8
+    }
+
+    pub fn has_pending_recovery(&self) -> bool {
+        self.pending_owner != Pubkey::default()
+    }
+
+    pub fn clear_recovery(&mut self) {
+        self.pending_owner = Pubkey::default();
+        self.approvals = 0;
+        self.recovery_unlocks_at = 0;
+    }
+}
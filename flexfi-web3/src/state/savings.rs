@@ -0,0 +1,24 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+// Goal-based savings, never counted as BNPL collateral
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SavingsGoalAccount {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub name_hash: [u8; 32],
+    pub target_amount: u64,
+    pub target_date: i64,
+    pub amount_saved: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl SavingsGoalAccount {
+    pub const SIZE: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1; // 129 bytes
+
+    // Savings stay locked until the target date or the target amount is reached
+    pub fn is_unlocked(&self, current_time: i64) -> bool {
+        current_time >= self.target_date || self.amount_saved >= self.target_amount
+    }
+}
@@ -37,6 +37,10 @@ impl ScoreAccount {
         }
     }
 
+    pub fn meets_threshold(&self, min_score: u16) -> bool {
+        self.score >= min_score
+    }
+
     pub fn update_score(&mut self, change: i16, current_time: i64) {
         if change > 0 {
             // Increase the score, maximum 1000
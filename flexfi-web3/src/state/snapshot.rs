@@ -0,0 +1,16 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct StakingSnapshotAccount {
+    pub owner: Pubkey,
+    pub epoch: u64,
+    pub amount_staked: u64,
+    pub position_count: u8,
+    pub taken_at: i64,
+    pub bump: u8,
+}
+
+impl StakingSnapshotAccount {
+    pub const SIZE: usize = 32 + 8 + 8 + 1 + 8 + 1; // 58 bytes
+}
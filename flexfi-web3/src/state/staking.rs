@@ -6,6 +6,8 @@ use solana_program::{
     entrypoint::ProgramResult,
 };
 use crate::core::staking::process_deposit_staking;
+use crate::math::{mul_div, rate_index_interest, Rounding};
+use crate::constants::{FREEZE_REASON_NONE, MAX_LOCK_TRANCHES, get_lock_fee_discount};
 
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
@@ -37,38 +39,104 @@ impl StakingStatus {
     }
 }
 
+// A deposit locked until its own end date; in a vesting position it unlocks linearly from start
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, Copy)]
+pub struct LockTranche {
+    pub amount: u64,
+    pub start: i64,
+    pub lock_end: i64,
+}
+
+impl LockTranche {
+    pub const SIZE: usize = 8 + 8 + 8; // 24 bytes
+
+    // Part of the tranche still locked at `time`: all of it until the end, or what hasn't vested yet
+    pub fn locked_at(&self, time: i64, vesting: bool) -> u64 {
+        if time >= self.lock_end {
+            return 0;
+        }
+
+        if !vesting || time <= self.start {
+            return self.amount;
+        }
+
+        let remaining = (self.lock_end - time) as u64;
+        let duration = (self.lock_end - self.start) as u64;
+        mul_div(self.amount, remaining, duration, Rounding::Up).unwrap_or(self.amount)
+    }
+
+    // Restart a vesting tranche at `time` with what is still locked; it keeps unlocking at the same pace
+    fn rebase(&mut self, time: i64) {
+        if time > self.start {
+            self.amount = self.locked_at(time, true);
+            self.start = time;
+        }
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct StakingAccount {
     pub owner: Pubkey,
     pub usdc_mint: Pubkey,
+    pub position_id: u8,
     pub amount_staked: u64,
     pub status: u8,
     pub lock_period_end: i64,
     pub created_at: i64,
     pub last_update: i64,
+    pub accrued_rewards: u64,
+    pub last_reward_update: i64,
+    pub reward_index_snapshot: u128, // Config reward index at the last accrual
+    pub stake_days: u64,        // Staked amount times days held since staking emissions were last recorded
+    pub freeze_reason: u8,
+    pub delegate: Pubkey,
+    pub vesting: bool,          // Each locked deposit unlocks linearly until its own end date
+    pub escrowed_amount: u64,   // Part of the stake backing deposit escrows
+    pub loan_hold: u64,         // Part of the stake held for an open micro loan
+    pub auto_compound: bool,    // Keepers may fold accrued rewards into the stake
+    pub convert_on_unlock: bool, // Keepers may move the stake into the owner's yield strategy once unlocked
+    pub vote_locked_until: i64, // End of the latest vote the stake weighed in; the lock can't be broken before
+    pub tranche_count: u8,
+    pub tranches: [LockTranche; MAX_LOCK_TRANCHES], // Locked deposits
     pub bump: u8,
 }
 
 impl StakingAccount {
-    pub const SIZE: usize = 32 + 32 + 8 + 1 + 8 + 8 + 8 + 1; // 98 bytes
+    pub const SIZE: usize = 32 + 32 + 1 + 8 + 1 + 8 + 8 + 8 + 8 + 8 + 16 + 8 + 1 + 32 + 1 + 8 + 8 + 1 + 1 + 8 + 1 + (LockTranche::SIZE * MAX_LOCK_TRANCHES) + 1; // 392 bytes
     
+    // Empty locked position; deposits are added with add_locked_deposit
     pub fn new(
         owner: Pubkey,
         usdc_mint: Pubkey,
-        amount_staked: u64,
-        status: StakingStatus,
-        lock_period_end: i64,
+        position_id: u8,
+        vesting: bool,
         created_at: i64,
+        reward_index: u128,
         bump: u8,
     ) -> Self {
         Self {
             owner,
             usdc_mint,
-            amount_staked,
-            status: status.to_u8(),
-            lock_period_end,
+            position_id,
+            amount_staked: 0,
+            status: StakingStatus::Locked.to_u8(),
+            lock_period_end: created_at,
             created_at,
             last_update: created_at,
+            accrued_rewards: 0,
+            last_reward_update: created_at,
+            reward_index_snapshot: reward_index,
+            stake_days: 0,
+            freeze_reason: FREEZE_REASON_NONE,
+            delegate: Pubkey::default(),
+            vesting,
+            escrowed_amount: 0,
+            loan_hold: 0,
+            auto_compound: false,
+            convert_on_unlock: false,
+            vote_locked_until: 0,
+            tranche_count: 0,
+            tranches: [LockTranche::default(); MAX_LOCK_TRANCHES],
             bump,
         }
     }
@@ -80,6 +148,176 @@ impl StakingAccount {
     pub fn set_status(&mut self, status: StakingStatus) {
         self.status = status.to_u8();
     }
+
+    // Check if the key is the delegate allowed to deposit and extend for the owner
+    pub fn is_delegate(&self, key: &Pubkey) -> bool {
+        self.delegate != Pubkey::default() && self.delegate == *key
+    }
+
+    // BNPL fee discount earned by the remaining lock duration
+    pub fn lock_fee_discount(&self, current_time: i64) -> u16 {
+        let remaining_days = self.lock_period_end.saturating_sub(current_time) / 86400;
+        get_lock_fee_discount(remaining_days)
+    }
+
+    // Staked amount not backing a deposit escrow or held for a micro loan
+    pub fn available_amount(&self) -> u64 {
+        self.amount_staked
+            .saturating_sub(self.escrowed_amount)
+            .saturating_sub(self.loan_hold)
+    }
+
+    // Drop the tranches whose lock has ended
+    pub fn prune_matured_tranches(&mut self, current_time: i64) {
+        let mut kept = 0;
+        for i in 0..self.tranche_count as usize {
+            if self.tranches[i].lock_end > current_time {
+                self.tranches[kept] = self.tranches[i];
+                kept += 1;
+            }
+        }
+
+        for tranche in self.tranches[kept..].iter_mut() {
+            *tranche = LockTranche::default();
+        }
+        self.tranche_count = kept as u8;
+    }
+
+    // Lock a deposit until its own end date, vesting from now in a vesting position. When every slot
+    // is used, the deposit joins the latest-ending tranche, restarted from now with what it still locks.
+    pub fn add_tranche(&mut self, amount: u64, lock_end: i64, current_time: i64) {
+        self.prune_matured_tranches(current_time);
+
+        let vesting = self.vesting;
+        let count = self.tranche_count as usize;
+        if let Some(tranche) = self.tranches[..count]
+            .iter_mut()
+            .find(|t| t.lock_end == lock_end && (!vesting || t.start == current_time))
+        {
+            tranche.amount = tranche.amount.saturating_add(amount);
+        } else if count < MAX_LOCK_TRANCHES {
+            self.tranches[count] = LockTranche { amount, start: current_time, lock_end };
+            self.tranche_count += 1;
+        } else if let Some(latest) = self.tranches.iter_mut().max_by_key(|t| t.lock_end) {
+            if vesting {
+                latest.rebase(current_time);
+            }
+            latest.amount = latest.amount.saturating_add(amount);
+            latest.lock_end = std::cmp::max(latest.lock_end, lock_end);
+        }
+
+        if lock_end > self.lock_period_end {
+            self.lock_period_end = lock_end;
+        }
+    }
+
+    // Add a deposit locked until lock_end; the rest of the balance keeps its own locks and vesting
+    pub fn add_locked_deposit(&mut self, amount: u64, lock_end: i64, current_time: i64) {
+        self.amount_staked = self.amount_staked.saturating_add(amount);
+        self.add_tranche(amount, lock_end, current_time);
+    }
+
+    // Replace the tranches with a single one covering the whole balance, vesting from now in a vesting position
+    pub fn relock_all(&mut self, lock_end: i64, current_time: i64) {
+        self.tranches = [LockTranche::default(); MAX_LOCK_TRANCHES];
+        self.tranches[0] = LockTranche { amount: self.amount_staked, start: current_time, lock_end };
+        self.tranche_count = 1;
+
+        if lock_end > self.lock_period_end {
+            self.lock_period_end = lock_end;
+        }
+    }
+
+    // Amount still held by unmatured tranches, less what their vesting has released
+    pub fn locked_amount(&self, current_time: i64) -> u64 {
+        let locked = self.tranches[..self.tranche_count as usize]
+            .iter()
+            .fold(0u64, |sum, t| sum.saturating_add(t.locked_at(current_time, self.vesting)));
+
+        std::cmp::min(locked, self.amount_staked)
+    }
+
+    // Whether the whole stake is past its lock, vesting or tranches
+    pub fn is_fully_unlocked(&self, current_time: i64) -> bool {
+        current_time >= self.lock_period_end && self.locked_amount(current_time) == 0
+    }
+
+    // Stake that stays locked until at least `time`, unvested or held by tranches ending later
+    pub fn locked_through(&self, time: i64) -> u64 {
+        std::cmp::min(self.locked_amount(time), self.available_amount())
+    }
+
+    // Whether the stake weighs in a vote still running
+    pub fn is_vote_locked(&self, current_time: i64) -> bool {
+        current_time < self.vote_locked_until
+    }
+
+    // Amount that can be withdrawn without breaking a lock
+    pub fn unlocked_amount(&self, current_time: i64) -> u64 {
+        self.available_amount().saturating_sub(self.locked_amount(current_time))
+    }
+
+    // Remove an amount from the locked tranches, soonest-ending first; vesting tranches give up
+    // their unvested part
+    pub fn release_locked(&mut self, amount: u64, current_time: i64) {
+        self.prune_matured_tranches(current_time);
+
+        let count = self.tranche_count as usize;
+        if self.vesting {
+            for tranche in self.tranches[..count].iter_mut() {
+                tranche.rebase(current_time);
+            }
+        }
+        self.tranches[..count].sort_by_key(|t| t.lock_end);
+
+        let mut remaining = amount;
+        for tranche in self.tranches[..count].iter_mut() {
+            let taken = std::cmp::min(tranche.amount, remaining);
+            tranche.amount -= taken;
+            remaining -= taken;
+        }
+
+        // Emptied tranches are removed like matured ones
+        for tranche in self.tranches[..count].iter_mut() {
+            if tranche.amount == 0 {
+                tranche.lock_end = 0;
+            }
+        }
+        self.prune_matured_tranches(current_time);
+    }
+
+    // Portion of a vesting position unlocked so far and not yet withdrawn
+    pub fn vested_withdrawable(&self, current_time: i64) -> u64 {
+        if !self.vesting {
+            return 0;
+        }
+
+        self.unlocked_amount(current_time)
+    }
+
+    // Rewards earned on the staked amount since the last accrual, priced with the config reward
+    // index so APY changes in between are applied exactly
+    pub fn pending_rewards(&self, reward_index: u128) -> u64 {
+        let index_delta = reward_index.saturating_sub(self.reward_index_snapshot);
+        rate_index_interest(self.amount_staked, index_delta, Rounding::Down).unwrap_or(u64::MAX)
+    }
+
+    // Accrue rewards and stake-days on the staked amount since the last update
+    pub fn accrue_rewards(&mut self, reward_index: u128, current_time: i64) {
+        let reward = self.pending_rewards(reward_index);
+        let elapsed = current_time.saturating_sub(self.last_reward_update).max(0) as u64;
+        let stake_days = mul_div(self.amount_staked, elapsed, 86400, Rounding::Down).unwrap_or(u64::MAX);
+
+        self.accrued_rewards = self.accrued_rewards.saturating_add(reward);
+        self.stake_days = self.stake_days.saturating_add(stake_days);
+        self.reward_index_snapshot = std::cmp::max(self.reward_index_snapshot, reward_index);
+        self.last_reward_update = current_time;
+    }
+
+    // Take the stake-days accrued so far, to be recorded for staking emissions
+    pub fn take_stake_days(&mut self) -> u64 {
+        std::mem::take(&mut self.stake_days)
+    }
 }
 
 pub struct StakingManager;
@@ -90,7 +328,122 @@ impl StakingManager {
         accounts: &[AccountInfo],
         amount: u64,
         lock_days: u16,
+        position_id: u8,
+        vesting: bool,
     ) -> ProgramResult {
-        process_deposit_staking(program_id, accounts, amount, lock_days)
+        process_deposit_staking(program_id, accounts, amount, lock_days, position_id, vesting)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86400;
+
+    fn vesting_position(amount: u64, lock_days: i64) -> StakingAccount {
+        let mut staking = StakingAccount::new(Pubkey::new_unique(), Pubkey::new_unique(), 0, true, 0, 0, 255);
+        staking.add_locked_deposit(amount, lock_days * DAY, 0);
+        staking
+    }
+
+    #[test]
+    fn vesting_unlocks_linearly() {
+        let staking = vesting_position(1_000, 100);
+
+        assert_eq!(staking.vested_withdrawable(0), 0);
+        assert_eq!(staking.vested_withdrawable(25 * DAY), 250);
+        assert_eq!(staking.vested_withdrawable(50 * DAY), 500);
+        assert_eq!(staking.vested_withdrawable(100 * DAY), 1_000);
+        assert_eq!(staking.vested_withdrawable(200 * DAY), 1_000);
+    }
+
+    #[test]
+    fn vesting_rounds_down_and_ignores_time_before_creation() {
+        let staking = vesting_position(1_000, 3);
+
+        assert_eq!(staking.vested_withdrawable(DAY), 333);
+        assert_eq!(staking.vested_withdrawable(-DAY), 0);
+    }
+
+    #[test]
+    fn vesting_deducts_withdrawals() {
+        let mut staking = vesting_position(1_000, 100);
+        staking.amount_staked -= 300;
+
+        assert_eq!(staking.vested_withdrawable(25 * DAY), 0);
+        assert_eq!(staking.vested_withdrawable(50 * DAY), 200);
+        assert_eq!(staking.vested_withdrawable(100 * DAY), 700);
+    }
+
+    #[test]
+    fn vesting_top_ups_vest_on_their_own_schedule() {
+        let mut staking = vesting_position(1_000, 100);
+        staking.add_locked_deposit(1_000, 60 * DAY, 10 * DAY);
+
+        // Nothing of the top-up is vested when it lands
+        assert_eq!(staking.vested_withdrawable(10 * DAY), 100);
+        assert_eq!(staking.lock_period_end, 100 * DAY);
+        assert_eq!(staking.vested_withdrawable(35 * DAY), 350 + 500);
+        assert_eq!(staking.vested_withdrawable(60 * DAY), 600 + 1_000);
+        assert_eq!(staking.vested_withdrawable(100 * DAY), 2_000);
+    }
+
+    #[test]
+    fn vesting_top_ups_leave_the_existing_balance_alone() {
+        let mut staking = vesting_position(1_000, 100);
+        staking.add_locked_deposit(1_000, 250 * DAY, 50 * DAY);
+
+        assert_eq!(staking.lock_period_end, 250 * DAY);
+        assert_eq!(staking.vested_withdrawable(50 * DAY), 500);
+        assert_eq!(staking.vested_withdrawable(100 * DAY), 1_000 + 250);
+    }
+
+    #[test]
+    fn broken_vesting_locks_give_up_unvested_stake() {
+        let mut staking = vesting_position(1_000, 100);
+        staking.release_locked(200, 50 * DAY);
+        staking.amount_staked -= 200;
+
+        assert_eq!(staking.locked_amount(50 * DAY), 300);
+        assert_eq!(staking.vested_withdrawable(50 * DAY), 500);
+        assert_eq!(staking.locked_amount(75 * DAY), 150);
+    }
+
+    #[test]
+    fn vesting_with_no_duration_is_fully_unlocked() {
+        let staking = vesting_position(1_000, 0);
+
+        assert_eq!(staking.vested_withdrawable(0), 1_000);
+    }
+
+    #[test]
+    fn positions_without_vesting_have_nothing_vested() {
+        let mut staking = StakingAccount::new(Pubkey::new_unique(), Pubkey::new_unique(), 0, false, 0, 0, 255);
+        staking.add_locked_deposit(1_000, 100 * DAY, 0);
+
+        assert_eq!(staking.vested_withdrawable(200 * DAY), 0);
+    }
+
+    #[test]
+    fn loan_holds_stay_in_the_position() {
+        let mut staking = vesting_position(1_000, 100);
+        staking.loan_hold = 300;
+
+        assert_eq!(staking.available_amount(), 700);
+        assert_eq!(staking.vested_withdrawable(50 * DAY), 200);
+        assert_eq!(staking.vested_withdrawable(100 * DAY), 700);
+    }
+
+    #[test]
+    fn stake_days_follow_the_balance_held() {
+        let mut staking = vesting_position(1_000, 100);
+
+        staking.accrue_rewards(0, 2 * DAY);
+        staking.amount_staked = 400;
+        staking.accrue_rewards(0, 5 * DAY);
+
+        assert_eq!(staking.take_stake_days(), 2 * 1_000 + 3 * 400);
+        assert_eq!(staking.stake_days, 0);
+    }
+}
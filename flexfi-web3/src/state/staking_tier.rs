@@ -0,0 +1,37 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::MAX_STAKING_TIERS;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, Copy)]
+pub struct StakingTier {
+    pub min_staked: u64,
+    pub fee_discount_bps: u16,        // Discount on BNPL and card fees
+    pub max_bnpl_multiplier_bps: u16, // Cap on BNPL amount relative to the stake
+}
+
+impl StakingTier {
+    pub const SIZE: usize = 8 + 2 + 2; // 12 bytes
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct StakingTierConfig {
+    pub authority: Pubkey,
+    pub tier_count: u8,
+    pub tiers: [StakingTier; MAX_STAKING_TIERS], // Sorted by min_staked
+    pub bump: u8,
+}
+
+impl StakingTierConfig {
+    pub const SIZE: usize = 32 + 1 + (StakingTier::SIZE * MAX_STAKING_TIERS) + 1; // 130 bytes
+
+    // Highest tier whose threshold the staked amount reaches
+    pub fn get_tier(&self, amount_staked: u64) -> StakingTier {
+        self.tiers[..self.tier_count as usize]
+            .iter()
+            .rev()
+            .find(|tier| amount_staked >= tier.min_staked)
+            .copied()
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,143 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::math::{mul_div_u128, Rounding};
+use crate::constants::{MAX_DAILY_INSTANT_TREASURY_WITHDRAWAL, TREASURY_WITHDRAWAL_WINDOW_SECS};
+
+// Treasury withdrawal paid out linearly to the recipient between start_time and end_time
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct TreasuryStreamAccount {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,     // Owner of the token account the stream pays to
+    pub stream_id: u64,
+    pub total_amount: u64,     // Lowered to the vested amount when the stream is cancelled
+    pub claimed_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub created_by: Pubkey,    // Treasurer who opened the stream; gets the rent back on close
+    pub bump: u8,
+}
+
+impl TreasuryStreamAccount {
+    pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 8 + 8 + 32 + 1; // 137 bytes
+
+    pub fn vested_amount(&self, current_time: i64) -> u64 {
+        if current_time >= self.end_time {
+            return self.total_amount;
+        }
+
+        let elapsed = current_time.saturating_sub(self.start_time).max(0);
+        let duration = self.end_time.saturating_sub(self.start_time);
+
+        mul_div_u128(self.total_amount as u128, elapsed as u128, duration as u128, Rounding::Down)
+            .and_then(|vested| u64::try_from(vested).ok())
+            .unwrap_or(self.total_amount)
+    }
+
+    pub fn claimable_amount(&self, current_time: i64) -> u64 {
+        self.vested_amount(current_time).saturating_sub(self.claimed_amount)
+    }
+
+    // Stop vesting: what has vested stays claimable, the rest stays in the treasury
+    pub fn cancel(&mut self, current_time: i64) {
+        if current_time < self.end_time {
+            self.total_amount = self.vested_amount(current_time);
+            self.end_time = std::cmp::max(current_time, self.start_time);
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.claimed_amount >= self.total_amount
+    }
+}
+
+// Treasury bookkeeping per mint: instant withdrawals of the current day, and what open streams
+// still have to pay out, so streams never promise more than the treasury holds
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct TreasuryStateAccount {
+    pub mint: Pubkey,
+    pub window_start: i64,
+    pub withdrawn_in_window: u64,  // Instant withdrawals since window_start
+    pub reserved_for_streams: u64, // Streamed amounts not claimed yet
+    pub bump: u8,
+}
+
+impl TreasuryStateAccount {
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 1; // 57 bytes
+
+    // Start a new window once the current one is over, keeping windows aligned on the first one
+    pub fn roll_window(&mut self, current_time: i64) {
+        let elapsed = current_time.saturating_sub(self.window_start);
+
+        if elapsed >= TREASURY_WITHDRAWAL_WINDOW_SECS {
+            self.window_start += elapsed - elapsed % TREASURY_WITHDRAWAL_WINDOW_SECS;
+            self.withdrawn_in_window = 0;
+        }
+    }
+
+    pub fn remaining_instant_budget(&self) -> u64 {
+        MAX_DAILY_INSTANT_TREASURY_WITHDRAWAL.saturating_sub(self.withdrawn_in_window)
+    }
+
+    // Count an instant withdrawal against the day's budget; false when it doesn't fit
+    pub fn spend_instant(&mut self, amount: u64, current_time: i64) -> bool {
+        self.roll_window(current_time);
+
+        if amount > self.remaining_instant_budget() {
+            return false;
+        }
+
+        self.withdrawn_in_window += amount;
+        true
+    }
+
+    // Part of the treasury balance no open stream is owed
+    pub fn unreserved_balance(&self, treasury_balance: u64) -> u64 {
+        treasury_balance.saturating_sub(self.reserved_for_streams)
+    }
+
+    pub fn release_stream(&mut self, amount: u64) {
+        self.reserved_for_streams = self.reserved_for_streams.saturating_sub(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instant_withdrawals_share_a_daily_budget() {
+        let mut state = TreasuryStateAccount {
+            mint: Pubkey::new_unique(),
+            window_start: 1_000,
+            withdrawn_in_window: 0,
+            reserved_for_streams: 0,
+            bump: 255,
+        };
+
+        assert!(state.spend_instant(MAX_DAILY_INSTANT_TREASURY_WITHDRAWAL - 1, 1_000));
+        assert!(!state.spend_instant(2, 1_000 + TREASURY_WITHDRAWAL_WINDOW_SECS - 1));
+
+        // The next window starts on the boundary of the first one
+        assert!(state.spend_instant(2, 1_000 + TREASURY_WITHDRAWAL_WINDOW_SECS + 5));
+        assert_eq!(state.window_start, 1_000 + TREASURY_WITHDRAWAL_WINDOW_SECS);
+        assert_eq!(state.withdrawn_in_window, 2);
+    }
+
+    #[test]
+    fn streamed_amounts_stay_reserved_until_claimed() {
+        let mut state = TreasuryStateAccount {
+            mint: Pubkey::new_unique(),
+            window_start: 0,
+            withdrawn_in_window: 0,
+            reserved_for_streams: 700,
+            bump: 255,
+        };
+
+        assert_eq!(state.unreserved_balance(1_000), 300);
+        assert_eq!(state.unreserved_balance(500), 0);
+
+        state.release_stream(200);
+        assert_eq!(state.unreserved_balance(1_000), 500);
+    }
+}
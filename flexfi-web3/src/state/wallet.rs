@@ -1,15 +1,57 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
+use crate::state::whitelist::UserLimits;
+
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct WalletAccount {
     pub owner: Pubkey,
     pub is_active: bool,
     pub card_type: u8,
     pub created_at: i64,
+    pub backend_id_hash: [u8; 32], // Hash of the off-chain customer id, zeroed until linked
+    // Owner's whitelist approval as of `whitelist_version`, so hot instructions can skip the status and blocklist PDAs
+    pub whitelist_cached: bool,
+    pub whitelist_version: u64,
+    pub whitelist_expires_at: i64,
+    pub whitelist_limits: UserLimits, // Region and risk limits of the status, cached with the approval
+    // GDPR erasure: requested by the owner, carried out once the backend authority acknowledges it
+    pub erasure_requested_at: i64, // 0 when no erasure is pending
+    pub erased: bool,
+    pub staking_positions: u8, // Staking accounts of the owner not closed yet
+    // Owner the wallet was recovered from, whose staking, score and card can be migrated; default otherwise
+    pub recovered_from: Pubkey,
     pub bump: u8,
 }
 
 impl WalletAccount {
-    pub const SIZE: usize = 32 + 1 + 1 + 8 + 1; // 43 bytes
-}
\ No newline at end of file
+    pub const SIZE: usize = 32 + 1 + 1 + 8 + 32 + 1 + 8 + 8 + UserLimits::SIZE + 8 + 1 + 1 + 32 + 1; // 145 bytes
+
+    pub fn is_recovered(&self) -> bool {
+        self.recovered_from != Pubkey::default()
+    }
+
+    pub fn has_backend_id(&self) -> bool {
+        self.backend_id_hash != [0u8; 32]
+    }
+
+    pub fn clear_whitelist_cache(&mut self) {
+        self.whitelist_cached = false;
+        self.whitelist_version = 0;
+        self.whitelist_expires_at = 0;
+        self.whitelist_limits = UserLimits::default();
+    }
+}
+
+// Reverse lookup from a backend id hash to the wallet it is linked to
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct BackendIdAccount {
+    pub wallet: Pubkey,
+    pub owner: Pubkey,
+    pub linked_at: i64,
+    pub bump: u8,
+}
+
+impl BackendIdAccount {
+    pub const SIZE: usize = 32 + 32 + 8 + 1; // 73 bytes
+}
@@ -1,16 +1,23 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::constants::MAX_AUTHORIZED_KEYS;
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct WhitelistAccount {
     pub authority: Pubkey,
     pub is_active: bool,
     pub total_users: u64,
+    pub attestation_signer: Pubkey, // Backend key for signature-based whitelisting, default when disabled
+    pub version: u64,               // Bumped on every revocation, invalidating the wallet caches
     pub bump: u8,
 }
 
 impl WhitelistAccount {
-    pub const SIZE: usize = 32 + 1 + 8 + 1; // 42 bytes
+    pub const SIZE: usize = 32 + 1 + 8 + 32 + 8 + 1; // 82 bytes
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -19,9 +26,97 @@ pub struct UserWhitelistStatus {
     pub is_whitelisted: bool,
     pub whitelisted_at: i64,
     pub whitelisted_by: Pubkey,
+    pub expires_at: i64,      // KYC approval must be renewed before this time
+    pub authorized_keys: [Pubkey; MAX_AUTHORIZED_KEYS], // Device/session keys acting for the user, default when unused
+    pub max_bnpl_amount: u64,      // Compliance cap per BNPL loan, 0 for no cap
+    pub max_active_contracts: u8,  // Compliance cap on open BNPL loans, 0 for no cap
+    pub region: u16,               // ISO 3166-1 numeric country code, 0 when not assigned
     pub bump: u8,
 }
 
 impl UserWhitelistStatus {
-    pub const SIZE: usize = 32 + 1 + 8 + 32 + 1; // 74 bytes
-}
\ No newline at end of file
+    pub const SIZE: usize = 32 + 1 + 8 + 32 + 8 + (32 * MAX_AUTHORIZED_KEYS) + 8 + 1 + 2 + 1; // 189 bytes
+
+    pub fn is_valid(&self, current_time: i64) -> bool {
+        self.is_whitelisted && current_time < self.expires_at
+    }
+
+    pub fn limits(&self) -> UserLimits {
+        UserLimits {
+            region: self.region,
+            max_bnpl_amount: self.max_bnpl_amount,
+            max_active_contracts: self.max_active_contracts,
+        }
+    }
+
+    // The user's own key or one of its registered device keys
+    pub fn is_authorized_key(&self, key: &Pubkey) -> bool {
+        *key == self.user_pubkey
+            || (*key != Pubkey::default() && self.authorized_keys.contains(key))
+    }
+}
+
+// Region and risk limits of a user, as set on the user status and cached on the wallet
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq, Clone, Copy)]
+pub struct UserLimits {
+    pub region: u16,
+    pub max_bnpl_amount: u64,
+    pub max_active_contracts: u8,
+}
+
+impl UserLimits {
+    pub const SIZE: usize = 2 + 8 + 1; // 11 bytes
+
+    // Check a new loan against the user's risk limits
+    pub fn allows_bnpl(&self, amount: u64, active_contracts: u8) -> bool {
+        (self.max_bnpl_amount == 0 || amount <= self.max_bnpl_amount)
+            && (self.max_active_contracts == 0 || active_contracts < self.max_active_contracts)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
+pub enum BlocklistReason {
+    Fraud,
+    Chargeback,
+    Sanctions,
+}
+
+impl BlocklistReason {
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            BlocklistReason::Fraud => 0,
+            BlocklistReason::Chargeback => 1,
+            BlocklistReason::Sanctions => 2,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(BlocklistReason::Fraud),
+            1 => Ok(BlocklistReason::Chargeback),
+            2 => Ok(BlocklistReason::Sanctions),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    }
+}
+
+// Kept after removal so the reason and the admins involved stay on record
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct BlocklistStatus {
+    pub user_pubkey: Pubkey,
+    pub is_blocked: bool,
+    pub reason: u8,
+    pub blocked_at: i64,
+    pub blocked_by: Pubkey,
+    pub unblocked_at: i64,
+    pub unblocked_by: Pubkey,
+    pub bump: u8,
+}
+
+impl BlocklistStatus {
+    pub const SIZE: usize = 32 + 1 + 1 + 8 + 32 + 8 + 32 + 1; // 115 bytes
+
+    pub fn get_reason(&self) -> Result<BlocklistReason, ProgramError> {
+        BlocklistReason::from_u8(self.reason)
+    }
+}
@@ -44,13 +44,14 @@ pub struct YieldAccount {
     pub auto_reinvest: bool,
     pub total_yield_earned: u64,
     pub total_yield_claimed: u64,
+    pub principal: u64, // Stake moved in from unlocked staking positions, withdrawable after the yield
     pub last_yield_claimed: i64,
     pub created_at: i64,
     pub bump: u8,
 }
 
 impl YieldAccount {
-    pub const SIZE: usize = 32 + 1 + 32 + 1 + 8 + 8 + 8 + 8 + 1; // 99 bytes
+    pub const SIZE: usize = 32 + 1 + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 1; // 107 bytes
     
     pub fn new(
         owner: Pubkey,
@@ -67,6 +68,7 @@ impl YieldAccount {
             auto_reinvest,
             total_yield_earned: 0,
             total_yield_claimed: 0,
+            principal: 0,
             last_yield_claimed: created_at,
             created_at,
             bump,
@@ -99,4 +101,50 @@ impl YieldAccount {
     pub fn get_unclaimed_yield(&self) -> u64 {
         self.total_yield_earned.saturating_sub(self.total_yield_claimed)
     }
-}
\ No newline at end of file
+
+    pub fn get_withdrawable_amount(&self) -> u64 {
+        self.get_unclaimed_yield().saturating_add(self.principal)
+    }
+
+    // Take a withdrawal out of the unclaimed yield first, then out of the principal
+    pub fn record_withdrawal(&mut self, amount: u64, current_time: i64) -> Result<(), ProgramError> {
+        if amount > self.get_withdrawable_amount() {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        let from_yield = std::cmp::min(amount, self.get_unclaimed_yield());
+        self.record_yield_claimed(from_yield, current_time)?;
+        self.principal -= amount - from_yield;
+
+        Ok(())
+    }
+}
+// Treasury balances deployed into a yield strategy, principal kept apart from earned yield
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct TreasuryYieldAccount {
+    pub mint: Pubkey,
+    pub strategy: u8,
+    pub principal_deployed: u64,
+    pub yield_realized: u64,   // Yield brought back to the treasury so far
+    pub last_deployed_at: i64,
+    pub reported_value: u64,   // Position value last posted by the strategy keeper
+    pub reconciled_at: i64,
+    pub is_paused: bool,       // Set when a reported value disagrees with the books; blocks deployments
+    pub bump: u8,
+}
+
+impl TreasuryYieldAccount {
+    pub const SIZE: usize = 32 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 1; // 75 bytes
+
+    // Split a recall from the strategy vault into (yield, principal); yield is taken out first
+    pub fn record_recall(&mut self, amount: u64, vault_balance: u64) -> (u64, u64) {
+        let unrealized_yield = vault_balance.saturating_sub(self.principal_deployed);
+        let from_yield = std::cmp::min(amount, unrealized_yield);
+        let from_principal = std::cmp::min(amount - from_yield, self.principal_deployed);
+
+        self.yield_realized = self.yield_realized.saturating_add(from_yield);
+        self.principal_deployed -= from_principal;
+
+        (from_yield, from_principal)
+    }
+}
@@ -0,0 +1,101 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::AccountMeta,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    state::{Account, Mint},
+};
+
+// Check that the account is either the SPL Token or the Token-2022 program
+pub fn check_token_program(token_program: &AccountInfo) -> ProgramResult {
+    if *token_program.key != spl_token::id() && *token_program.key != spl_token_2022::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Ok(())
+}
+
+// Read the decimals of a mint owned by either token program
+pub fn get_mint_decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+    let mint_data = mint.data.borrow();
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    Ok(mint_state.base.decimals)
+}
+
+// Read the balance of a token account owned by either token program
+pub fn get_token_account_amount(token_account: &AccountInfo) -> Result<u64, ProgramError> {
+    let account_data = token_account.data.borrow();
+    let account_state = StateWithExtensions::<Account>::unpack(&account_data)?;
+    Ok(account_state.base.amount)
+}
+
+// Check the owner and mint of a token account owned by either token program
+pub fn check_token_account(
+    token_account: &AccountInfo,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> ProgramResult {
+    let account_data = token_account.data.borrow();
+    let account_state = StateWithExtensions::<Account>::unpack(&account_data)?;
+
+    if account_state.base.owner != *owner || account_state.base.mint != *mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+// Transfer tokens with transfer_checked so Token-2022 mints are supported.
+// Extra accounts (e.g. transfer hook accounts) are forwarded to the token program.
+// Takes the accounts of the token instruction in its order, hence the argument count.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked<'a>(
+    token_program: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    extra_accounts: &[AccountInfo<'a>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    check_token_program(token_program)?;
+
+    let decimals = get_mint_decimals(mint)?;
+
+    let mut transfer_ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        source.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+
+    let mut account_infos = vec![
+        source.clone(),
+        mint.clone(),
+        destination.clone(),
+        authority.clone(),
+    ];
+
+    for account in extra_accounts {
+        transfer_ix.accounts.push(AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        });
+        account_infos.push(account.clone());
+    }
+
+    account_infos.push(token_program.clone());
+
+    invoke_signed(&transfer_ix, &account_infos, signer_seeds)
+}
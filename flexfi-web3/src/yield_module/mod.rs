@@ -1,5 +1,7 @@
 pub mod router;
 pub mod tracker;
+pub mod treasury;
 
 pub use router::{process_set_yield_strategy, process_route_yield};
-pub use tracker::{process_claim_yield, process_get_yield_stats};
\ No newline at end of file
+pub use tracker::{process_claim_yield, process_restake_yield, process_get_yield_stats};
+pub use treasury::{process_deploy_treasury, process_recall_treasury};
@@ -2,14 +2,14 @@ use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program::{invoke, invoke_signed},
+    program::invoke_signed,
     pubkey::Pubkey,
     system_instruction,
     sysvar::{clock::Clock, Sysvar, rent::Rent},
     msg,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
-use crate::core::whitelist::require_whitelisted;
+use crate::token::transfer_checked;
 use crate::error::FlexfiError;
 use crate::state::yield_::{YieldAccount, YieldStrategy};
 use crate::constants::{YIELD_CONFIG_SEED};
@@ -24,7 +24,7 @@ pub fn process_set_yield_strategy(
 
     let yield_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
     let system_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
 
@@ -33,12 +33,6 @@ pub fn process_set_yield_strategy(
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    require_whitelisted(
-        program_id,
-        user_account.key,
-        user_status_account
-    )?;
-
     // Convert u8 to YieldStrategy
     let yield_strategy = YieldStrategy::from_u8(strategy)?;
 
@@ -134,8 +128,10 @@ pub fn process_route_yield(
     let user_account = next_account_info(account_info_iter)?;
     let source_token_account = next_account_info(account_info_iter)?;
     let destination_token_account = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
 
     // Verify user signature
     if !user_account.is_signer {
@@ -160,23 +156,15 @@ pub fn process_route_yield(
             msg!("Routing yield to AutoCompound strategy: {}", amount);
 
             // Transfer to auto-compound strategy
-            let transfer_ix = spl_token::instruction::transfer(
-                token_program.key,
-                source_token_account.key,
-                destination_token_account.key,
-                user_account.key,
-                &[],
+            transfer_checked(
+                token_program,
+                source_token_account,
+                token_mint,
+                destination_token_account,
+                user_account,
+                hook_accounts,
                 amount,
-            )?;
-
-            invoke(
-                &transfer_ix,
-                &[
-                    source_token_account.clone(),
-                    destination_token_account.clone(),
-                    user_account.clone(),
-                    token_program.clone(),
-                ],
+                &[],
             )?;
         },
         YieldStrategy::StableCoin => {
@@ -184,23 +172,15 @@ pub fn process_route_yield(
             msg!("Routing yield to StableCoin strategy: {}", amount);
 
             // Similar transfer
-            let transfer_ix = spl_token::instruction::transfer(
-                token_program.key,
-                source_token_account.key,
-                destination_token_account.key,
-                user_account.key,
-                &[],
+            transfer_checked(
+                token_program,
+                source_token_account,
+                token_mint,
+                destination_token_account,
+                user_account,
+                hook_accounts,
                 amount,
-            )?;
-
-            invoke(
-                &transfer_ix,
-                &[
-                    source_token_account.clone(),
-                    destination_token_account.clone(),
-                    user_account.clone(),
-                    token_program.clone(),
-                ],
+                &[],
             )?;
         },
         YieldStrategy::HighYield => {
@@ -208,23 +188,15 @@ pub fn process_route_yield(
             msg!("Routing yield to HighYield strategy: {}", amount);
 
             // Transfer to high yield strategy
-            let transfer_ix = spl_token::instruction::transfer(
-                token_program.key,
-                source_token_account.key,
-                destination_token_account.key,
-                user_account.key,
-                &[],
+            transfer_checked(
+                token_program,
+                source_token_account,
+                token_mint,
+                destination_token_account,
+                user_account,
+                hook_accounts,
                 amount,
-            )?;
-
-            invoke(
-                &transfer_ix,
-                &[
-                    source_token_account.clone(),
-                    destination_token_account.clone(),
-                    user_account.clone(),
-                    token_program.clone(),
-                ],
+                &[],
             )?;
         },
         YieldStrategy::RealWorldAssets => {
@@ -232,23 +204,15 @@ pub fn process_route_yield(
             msg!("Routing yield to RealWorldAssets strategy: {}", amount);
 
             // Transfer to real world assets strategy
-            let transfer_ix = spl_token::instruction::transfer(
-                token_program.key,
-                source_token_account.key,
-                destination_token_account.key,
-                user_account.key,
-                &[],
+            transfer_checked(
+                token_program,
+                source_token_account,
+                token_mint,
+                destination_token_account,
+                user_account,
+                hook_accounts,
                 amount,
-            )?;
-
-            invoke(
-                &transfer_ix,
-                &[
-                    source_token_account.clone(),
-                    destination_token_account.clone(),
-                    user_account.clone(),
-                    token_program.clone(),
-                ],
+                &[],
             )?;
         },
         YieldStrategy::Custom => {
@@ -257,23 +221,15 @@ pub fn process_route_yield(
                  yield_data.custom_strategy_address, amount);
 
             // Transfer to custom strategy
-            let transfer_ix = spl_token::instruction::transfer(
-                token_program.key,
-                source_token_account.key,
-                destination_token_account.key,
-                user_account.key,
-                &[],
+            transfer_checked(
+                token_program,
+                source_token_account,
+                token_mint,
+                destination_token_account,
+                user_account,
+                hook_accounts,
                 amount,
-            )?;
-
-            invoke(
-                &transfer_ix,
-                &[
-                    source_token_account.clone(),
-                    destination_token_account.clone(),
-                    user_account.clone(),
-                    token_program.clone(),
-                ],
+                &[],
             )?;
         },
     }
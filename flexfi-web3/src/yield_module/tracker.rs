@@ -7,12 +7,41 @@ use solana_program::{
     msg,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
-use crate::core::whitelist::require_whitelisted;
+use crate::token::transfer_checked;
 use crate::error::FlexfiError;
 use crate::state::yield_::YieldAccount;
+use crate::state::staking::{StakingAccount, StakingStatus};
+use crate::state::wallet::WalletAccount;
+use crate::constants::{YIELD_CONFIG_SEED, STAKING_SEED, USDC_VAULT_SEED, WALLET_SEED, CARD_STANDARD};
+use crate::core::staking::get_staking_reward_index;
+use crate::core::config::load_config;
+use crate::bnpl::micro::check_treasury_token_account;
+use crate::math::fee_amount;
 
+// Card type of the user's FlexFi wallet, Standard without one
+fn get_wallet_card_type(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wallet_account: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (wallet_pda, _) = Pubkey::find_program_address(&[WALLET_SEED, owner.as_ref()], program_id);
+
+    if *wallet_account.key != wallet_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if wallet_account.owner != program_id {
+        return Ok(CARD_STANDARD);
+    }
+
+    let wallet_data = WalletAccount::try_from_slice(&wallet_account.data.borrow())?;
+    Ok(wallet_data.card_type)
+}
+
+// Withdraw yield (then converted principal); the performance fee of the user's card type is withheld
+// from the yield part and sent to the treasury
 pub fn process_claim_yield(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
 ) -> ProgramResult {
@@ -21,22 +50,21 @@ pub fn process_claim_yield(
     let yield_account = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
     let user_token_account = next_account_info(account_info_iter)?;
-    let user_status_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
     let yield_token_account = next_account_info(account_info_iter)?;
+    let yield_mint = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     let clock_sysvar = next_account_info(account_info_iter)?;
+    let wallet_account = next_account_info(account_info_iter)?; // The user's wallet, for the card type
+    let config_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?; // Receives the performance fee
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
 
     // Verify user signature
     if !user_account.is_signer {
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    require_whitelisted(
-        _program_id,
-        user_account.key,
-        user_status_account
-    )?;
-
     // Load yield data
     let mut yield_data = YieldAccount::try_from_slice(&yield_account.data.borrow())?;
 
@@ -45,9 +73,9 @@ pub fn process_claim_yield(
         return Err(FlexfiError::Unauthorized.into());
     }
 
-    // Check if the requested amount is available
+    // Check if the requested amount is available (yield, then converted principal)
     let unclaimed_yield = yield_data.get_unclaimed_yield();
-    if amount > unclaimed_yield {
+    if amount > yield_data.get_withdrawable_amount() {
         return Err(FlexfiError::NoYieldToClaim.into());
     }
 
@@ -57,45 +85,57 @@ pub fn process_claim_yield(
 
     // If auto_reinvest is enabled and the amount is below a threshold,
     // automatically reinvest
-    if yield_data.auto_reinvest && amount < 1_000_000 {
+    if yield_data.auto_reinvest && amount < 1_000_000 && amount <= unclaimed_yield {
         // Auto-reinvest (simplified logic)
         yield_data.record_yield_claimed(amount, current_time)?;
         yield_data.record_yield_earned(amount);
 
         msg!("Yield auto-reinvested: {}", amount);
     } else {
-        // Transfer yield from yield account to user account
-        let transfer_ix = spl_token::instruction::transfer(
-            token_program.key,
-            yield_token_account.key,
-            user_token_account.key,
-            yield_account.key, // Authority is the yield PDA
-            &[],
-            amount,
-        )?;
-
         // Get seeds for signing
-        let seeds = [
+        let seeds: &[&[u8]] = &[
             b"yield_config",
             user_account.key.as_ref(),
             &[yield_data.bump],
         ];
 
-        solana_program::program::invoke_signed(
-            &transfer_ix,
-            &[
-                yield_token_account.clone(),
-                user_token_account.clone(),
-                yield_account.clone(),
-                token_program.clone(),
-            ],
-            &[&seeds],
+        // Performance fee on the yield part only, reduced for the higher card tiers
+        let card_type = get_wallet_card_type(program_id, user_account.key, wallet_account)?;
+        let config_data = load_config(program_id, config_account)?;
+        let fee_bps = config_data.get_yield_performance_fee_bps(card_type);
+        let fee = fee_amount(std::cmp::min(amount, unclaimed_yield), fee_bps);
+
+        // Transfer yield from yield account to user account
+        transfer_checked(
+            token_program,
+            yield_token_account,
+            yield_mint,
+            user_token_account,
+            yield_account, // Authority is the yield PDA
+            hook_accounts,
+            amount - fee,
+            &[seeds],
         )?;
 
+        if fee > 0 {
+            check_treasury_token_account(program_id, yield_mint.key, token_program.key, treasury_token_account)?;
+
+            transfer_checked(
+                token_program,
+                yield_token_account,
+                yield_mint,
+                treasury_token_account,
+                yield_account,
+                hook_accounts,
+                fee,
+                &[seeds],
+            )?;
+        }
+
         // Record claimed yield
-        yield_data.record_yield_claimed(amount, current_time)?;
+        yield_data.record_withdrawal(amount, current_time)?;
 
-        msg!("Yield claimed: {}", amount);
+        msg!("Yield claimed: {}, {} performance fee ({} bps, card type {})", amount - fee, fee, fee_bps, card_type);
     }
 
     // Save changes
@@ -104,6 +144,133 @@ pub fn process_claim_yield(
     Ok(())
 }
 
+// Claim unclaimed yield straight into the user's staking vault
+pub fn process_restake_yield(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let yield_account = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let _user_status_account = next_account_info(account_info_iter)?; // Checked by the processor guard
+    let staking_account = next_account_info(account_info_iter)?;
+    let yield_token_account = next_account_info(account_info_iter)?;
+    let vault_token_account = next_account_info(account_info_iter)?;
+    let yield_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?; // Holds the reward index
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Verify user signature
+    if !user_account.is_signer {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check the yield account
+    let (yield_pda, _) = Pubkey::find_program_address(
+        &[YIELD_CONFIG_SEED, user_account.key.as_ref()],
+        program_id,
+    );
+
+    if *yield_account.key != yield_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Load yield data
+    let mut yield_data = YieldAccount::try_from_slice(&yield_account.data.borrow())?;
+
+    // Verify ownership
+    if yield_data.owner != *user_account.key {
+        return Err(FlexfiError::Unauthorized.into());
+    }
+
+    // Check if the requested amount is available
+    if amount > yield_data.get_unclaimed_yield() {
+        return Err(FlexfiError::NoYieldToClaim.into());
+    }
+
+    // Load staking data
+    let mut staking_data = StakingAccount::try_from_slice(&staking_account.data.borrow())?;
+
+    // Check the staking account
+    let (staking_pda, _) = Pubkey::find_program_address(
+        &[
+            STAKING_SEED,
+            user_account.key.as_ref(),
+            staking_data.usdc_mint.as_ref(),
+            &[staking_data.position_id],
+        ],
+        program_id,
+    );
+
+    if *staking_account.key != staking_pda || staking_data.owner != *user_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // The yield must be paid in the staking mint
+    if *yield_mint.key != staking_data.usdc_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check staking status
+    let status = staking_data.get_status()?;
+    if status == StakingStatus::Frozen || status == StakingStatus::Closed {
+        return Err(FlexfiError::StakingFrozen.into());
+    }
+
+    // Verify the staking vault
+    let (vault_pda, _) = Pubkey::find_program_address(
+        &[USDC_VAULT_SEED, staking_account.key.as_ref()],
+        program_id,
+    );
+    let vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &vault_pda,
+        yield_mint.key,
+        token_program.key,
+    );
+
+    if *vault_token_account.key != vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let current_time = clock.unix_timestamp;
+    let reward_index = get_staking_reward_index(program_id, config_account, current_time)?;
+
+    // Update both accounts
+    yield_data.record_yield_claimed(amount, current_time)?;
+
+    staking_data.accrue_rewards(reward_index, current_time);
+    staking_data.amount_staked = staking_data.amount_staked.saturating_add(amount);
+    staking_data.last_update = current_time;
+
+    yield_data.serialize(&mut *yield_account.data.borrow_mut())?;
+    staking_data.serialize(&mut *staking_account.data.borrow_mut())?;
+
+    // Transfer yield from the yield account to the staking vault
+    transfer_checked(
+        token_program,
+        yield_token_account,
+        yield_mint,
+        vault_token_account,
+        yield_account, // Authority is the yield PDA
+        hook_accounts,
+        amount,
+        &[&[YIELD_CONFIG_SEED, user_account.key.as_ref(), &[yield_data.bump]]],
+    )?;
+
+    msg!("Yield restaked: {}, new staked amount: {}", amount, staking_data.amount_staked);
+    Ok(())
+}
+
 pub fn process_get_yield_stats(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -142,4 +309,12 @@ impl YieldTracker {
     ) -> ProgramResult {
         process_claim_yield(program_id, accounts, amount)
     }
+
+    pub fn restake_yield(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        process_restake_yield(program_id, accounts, amount)
+    }
 }
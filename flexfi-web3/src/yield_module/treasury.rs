@@ -0,0 +1,385 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar, rent::Rent},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::token::{transfer_checked, get_token_account_amount};
+use crate::error::FlexfiError;
+use crate::state::yield_::{TreasuryYieldAccount, YieldStrategy};
+use crate::state::admin::AdminRole;
+use crate::constants::{TREASURY_SEED, TREASURY_YIELD_SEED, STRATEGY_RECONCILE_TOLERANCE_BPS};
+use crate::core::admin::require_admin_role;
+use crate::core::config::load_config;
+use crate::core::mint_registry::get_mint_price_feed;
+use crate::math::{mul_div_u128, apply_bps, Rounding, BPS_DENOMINATOR};
+use crate::error_context::{amount_mismatch, account_error};
+
+// Check the treasury and strategy vault token accounts of a mint
+fn check_treasury_accounts(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    treasury_authority: &AccountInfo,
+    treasury_token_account: &AccountInfo,
+    treasury_yield_account: &AccountInfo,
+    strategy_vault_token_account: &AccountInfo,
+) -> Result<(u8, u8), ProgramError> {
+    let (treasury_pda, treasury_bump) = Pubkey::find_program_address(
+        &[TREASURY_SEED, mint.as_ref()],
+        program_id
+    );
+
+    if *treasury_authority.key != treasury_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (treasury_yield_pda, treasury_yield_bump) = Pubkey::find_program_address(
+        &[TREASURY_YIELD_SEED, mint.as_ref()],
+        program_id
+    );
+
+    if *treasury_yield_account.key != treasury_yield_pda {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let treasury_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &treasury_pda,
+        mint,
+        token_program,
+    );
+    let strategy_vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &treasury_yield_pda,
+        mint,
+        token_program,
+    );
+
+    if *treasury_token_account.key != treasury_ata || *strategy_vault_token_account.key != strategy_vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok((treasury_bump, treasury_yield_bump))
+}
+
+// Deploy idle treasury funds into the StableCoin strategy, up to the governance cap (treasurer only)
+pub fn process_deploy_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let treasury_yield_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let accepted_mints_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let treasury_authority = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let strategy_vault_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Verify the authority holds the treasurer role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Treasurer)?;
+
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Only stablecoins (accepted mints without a price feed) go into the StableCoin strategy
+    if get_mint_price_feed(program_id, mint.key, accepted_mints_account)? != Pubkey::default() {
+        msg!("Mint {} is not a stablecoin", mint.key);
+        return Err(FlexfiError::MintNotAccepted.into());
+    }
+
+    let (treasury_bump, treasury_yield_bump) = check_treasury_accounts(
+        program_id,
+        mint.key,
+        token_program.key,
+        treasury_authority,
+        treasury_token_account,
+        treasury_yield_account,
+        strategy_vault_token_account,
+    )?;
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    // Create the deployment record on first use
+    if treasury_yield_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let space = TreasuryYieldAccount::SIZE;
+        let rent_lamports = rent.minimum_balance(space);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                treasury_yield_account.key,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[authority.clone(), treasury_yield_account.clone(), system_program.clone()],
+            &[&[TREASURY_YIELD_SEED, mint.key.as_ref(), &[treasury_yield_bump]]],
+        )?;
+
+        let treasury_yield_data = TreasuryYieldAccount {
+            mint: *mint.key,
+            strategy: YieldStrategy::StableCoin.to_u8(),
+            principal_deployed: 0,
+            yield_realized: 0,
+            last_deployed_at: 0,
+            reported_value: 0,
+            reconciled_at: 0,
+            is_paused: false,
+            bump: treasury_yield_bump,
+        };
+
+        treasury_yield_data.serialize(&mut *treasury_yield_account.data.borrow_mut())?;
+    }
+
+    let mut treasury_yield_data = TreasuryYieldAccount::try_from_slice(&treasury_yield_account.data.borrow())?;
+
+    if treasury_yield_data.is_paused {
+        msg!("Strategy for {} is paused until reconciled", mint.key);
+        return Err(account_error(treasury_yield_account, FlexfiError::StrategyPaused));
+    }
+
+    // Cap the deployed principal to a share of the whole treasury (idle + deployed)
+    let config_data = load_config(program_id, config_account)?;
+    let idle_balance = get_token_account_amount(treasury_token_account)?;
+    let total_treasury = (idle_balance as u128) + (treasury_yield_data.principal_deployed as u128);
+    let max_deployed = mul_div_u128(total_treasury, config_data.treasury_deploy_cap_bps as u128, BPS_DENOMINATOR as u128, Rounding::Down)
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    let principal_after = treasury_yield_data.principal_deployed
+        .checked_add(amount)
+        .ok_or(FlexfiError::MathOverflow)?;
+
+    if principal_after as u128 > max_deployed {
+        msg!("Deployment exceeds the treasury cap of {} bps", config_data.treasury_deploy_cap_bps);
+        let max_deployed = u64::try_from(max_deployed).unwrap_or(u64::MAX);
+        return Err(amount_mismatch(Some(treasury_token_account), max_deployed, principal_after, ProgramError::InsufficientFunds));
+    }
+
+    transfer_checked(
+        token_program,
+        treasury_token_account,
+        mint,
+        strategy_vault_token_account,
+        treasury_authority,
+        hook_accounts,
+        amount,
+        &[&[TREASURY_SEED, mint.key.as_ref(), &[treasury_bump]]],
+    )?;
+
+    treasury_yield_data.principal_deployed = principal_after;
+    treasury_yield_data.last_deployed_at = clock.unix_timestamp;
+    treasury_yield_data.serialize(&mut *treasury_yield_account.data.borrow_mut())?;
+
+    msg!("Treasury deployed {} to the StableCoin strategy, principal now {}", amount, principal_after);
+    Ok(())
+}
+
+// Bring funds back from the strategy vault to the treasury, realizing earned yield first (treasurer only)
+pub fn process_recall_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let treasury_yield_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let treasury_authority = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let strategy_vault_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let hook_accounts = account_info_iter.as_slice(); // Transfer hook extra accounts
+
+    // Verify the authority holds the treasurer role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Treasurer)?;
+
+    if treasury_yield_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (_, treasury_yield_bump) = check_treasury_accounts(
+        program_id,
+        mint.key,
+        token_program.key,
+        treasury_authority,
+        treasury_token_account,
+        treasury_yield_account,
+        strategy_vault_token_account,
+    )?;
+
+    let mut treasury_yield_data = TreasuryYieldAccount::try_from_slice(&treasury_yield_account.data.borrow())?;
+
+    let vault_balance = get_token_account_amount(strategy_vault_token_account)?;
+
+    if amount == 0 || amount > vault_balance {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    transfer_checked(
+        token_program,
+        strategy_vault_token_account,
+        mint,
+        treasury_token_account,
+        treasury_yield_account,
+        hook_accounts,
+        amount,
+        &[&[TREASURY_YIELD_SEED, mint.key.as_ref(), &[treasury_yield_bump]]],
+    )?;
+
+    let (from_yield, from_principal) = treasury_yield_data.record_recall(amount, vault_balance);
+    treasury_yield_data.serialize(&mut *treasury_yield_account.data.borrow_mut())?;
+
+    msg!(
+        "Treasury recalled {}: {} yield, {} principal; principal still deployed {}",
+        amount,
+        from_yield,
+        from_principal,
+        treasury_yield_data.principal_deployed
+    );
+    Ok(())
+}
+
+// Post the external value of the strategy position (strategy keeper only); a value further from
+// the vault balance than the tolerance pauses deployments until a treasurer resumes them
+pub fn process_reconcile_strategy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reported_value: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let treasury_yield_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let strategy_vault_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the strategy keeper role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::StrategyKeeper)?;
+
+    if treasury_yield_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (treasury_yield_pda, _) = Pubkey::find_program_address(
+        &[TREASURY_YIELD_SEED, mint.key.as_ref()],
+        program_id
+    );
+    let strategy_vault_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &treasury_yield_pda,
+        mint.key,
+        token_program.key,
+    );
+
+    if *treasury_yield_account.key != treasury_yield_pda || *strategy_vault_token_account.key != strategy_vault_ata {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut treasury_yield_data = TreasuryYieldAccount::try_from_slice(&treasury_yield_account.data.borrow())?;
+
+    // The books value the position at the vault balance
+    let book_value = get_token_account_amount(strategy_vault_token_account)?;
+    let tolerance = apply_bps(book_value, STRATEGY_RECONCILE_TOLERANCE_BPS, Rounding::Down)
+        .ok_or(FlexfiError::MathOverflow)?;
+    let discrepancy = reported_value.abs_diff(book_value);
+
+    let clock = Clock::from_account_info(clock_sysvar)?;
+
+    treasury_yield_data.reported_value = reported_value;
+    treasury_yield_data.reconciled_at = clock.unix_timestamp;
+
+    if discrepancy > tolerance {
+        treasury_yield_data.is_paused = true;
+        msg!("Strategy for {} paused: reported {}, books {}", mint.key, reported_value, book_value);
+    } else {
+        msg!("Strategy for {} reconciled at {}", mint.key, reported_value);
+    }
+
+    treasury_yield_data.serialize(&mut *treasury_yield_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// Lift a reconciliation pause once the discrepancy was investigated (treasurer only)
+pub fn process_resume_strategy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let treasury_yield_account = next_account_info(account_info_iter)?;
+    let admin_list_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+
+    // Verify the authority holds the treasurer role
+    require_admin_role(program_id, admin_list_account, authority, AdminRole::Treasurer)?;
+
+    if treasury_yield_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut treasury_yield_data = TreasuryYieldAccount::try_from_slice(&treasury_yield_account.data.borrow())?;
+
+    if !treasury_yield_data.is_paused {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    treasury_yield_data.is_paused = false;
+    treasury_yield_data.serialize(&mut *treasury_yield_account.data.borrow_mut())?;
+
+    msg!("Strategy for {} resumed", treasury_yield_data.mint);
+    Ok(())
+}
+
+pub struct TreasuryYield;
+
+impl TreasuryYield {
+    pub fn deploy(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        process_deploy_treasury(program_id, accounts, amount)
+    }
+
+    pub fn recall(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        process_recall_treasury(program_id, accounts, amount)
+    }
+
+    pub fn reconcile(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reported_value: u64,
+    ) -> ProgramResult {
+        process_reconcile_strategy(program_id, accounts, reported_value)
+    }
+
+    pub fn resume(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        process_resume_strategy(program_id, accounts)
+    }
+}
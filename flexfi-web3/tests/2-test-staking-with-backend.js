@@ -34,6 +34,15 @@ const BACKEND_URL = 'http://localhost:3000';
 const WHITELIST_SEED = 'whitelist';
 const STAKING_SEED = 'staking';
 const USDC_VAULT_SEED = 'usdc_vault';
+const ACCEPTED_MINTS_SEED = 'accepted_mints';
+const CONFIG_SEED = 'config';
+const WALLET_SEED = 'wallet';
+const EMISSIONS_SCHEDULE_SEED = 'emissions_schedule';
+const EPOCH_ACTIVITY_SEED = 'epoch_activity';
+const USER_ACTIVITY_SEED = 'user_activity';
+
+// Staking position used by these tests
+const STAKING_POSITION_ID = 0;
 
 // Discriminants
 const DISCRIMINANTS = {
@@ -42,8 +51,8 @@ const DISCRIMINANTS = {
 };
 
 // Helper to create staking instruction
-function createDepositStakingInstruction(amount, lockDays) {
-  const buffer = Buffer.alloc(11); // 1 (discriminant) + 8 (u64) + 2 (u16)
+function createDepositStakingInstruction(amount, lockDays, positionId = STAKING_POSITION_ID, vesting = false) {
+  const buffer = Buffer.alloc(13); // 1 (discriminant) + 8 (u64) + 2 (u16) + 1 (u8) + 1 (bool)
 
   // Discriminant 0 for DepositStaking
   buffer.writeUInt8(DISCRIMINANTS.DEPOSIT_STAKING, 0);
@@ -55,6 +64,12 @@ function createDepositStakingInstruction(amount, lockDays) {
   // lock_days as u16 little endian
   buffer.writeUInt16LE(lockDays, 9);
 
+  // position_id as u8
+  buffer.writeUInt8(positionId, 11);
+
+  // vesting as bool
+  buffer.writeUInt8(vesting ? 1 : 0, 12);
+
   return buffer;
 }
 
@@ -245,7 +260,8 @@ class StakingTester {
       [
         Buffer.from(STAKING_SEED),
         userKeypair.publicKey.toBuffer(),
-        usdcMint.toBuffer()
+        usdcMint.toBuffer(),
+        Buffer.from([STAKING_POSITION_ID])
       ],
       this.programId
     );
@@ -266,16 +282,62 @@ class StakingTester {
       true // allowOwnerOffCurve
     );
 
+    // Accepted mints registry PDA
+    const [acceptedMintsAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from(ACCEPTED_MINTS_SEED)],
+      this.programId
+    );
+
+    // Protocol config PDA (staking reward index)
+    const [configAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from(CONFIG_SEED)],
+      this.programId
+    );
+
+    // Wallet PDA, counting the user's staking positions
+    const [walletAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from(WALLET_SEED), userKeypair.publicKey.toBuffer()],
+      this.programId
+    );
+
+    // Emissions PDAs, recording the deposit as staking activity of the current epoch
+    const [emissionsScheduleAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from(EMISSIONS_SCHEDULE_SEED)],
+      this.programId
+    );
+
+    const { epoch } = await this.connection.getEpochInfo();
+    const epochBytes = Buffer.alloc(8);
+    epochBytes.writeBigUInt64LE(BigInt(epoch));
+
+    const [epochActivityAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from(EPOCH_ACTIVITY_SEED), epochBytes],
+      this.programId
+    );
+
+    const [userActivityAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from(USER_ACTIVITY_SEED), userKeypair.publicKey.toBuffer(), epochBytes],
+      this.programId
+    );
+
     console.log(`   User Status PDA: ${userStatusAccount.toBase58()}`);
     console.log(`   Staking Account: ${stakingAccount.toBase58()}`);
     console.log(`   Vault Account: ${vaultAccount.toBase58()}`);
     console.log(`   Vault ATA: ${vaultATA.toBase58()}`);
+    console.log(`   Accepted Mints PDA: ${acceptedMintsAccount.toBase58()}`);
+    console.log(`   Wallet PDA: ${walletAccount.toBase58()}`);
 
     return {
       userStatusAccount,
       stakingAccount,
       vaultAccount,
-      vaultATA
+      vaultATA,
+      acceptedMintsAccount,
+      configAccount,
+      walletAccount,
+      emissionsScheduleAccount,
+      epochActivityAccount,
+      userActivityAccount
     };
   }
 
@@ -306,7 +368,18 @@ class StakingTester {
   }
 
   async performStaking(userKeypair, pdas, usdcInfo, amount = 100000000, lockDays = 30) {
-    const { userStatusAccount, stakingAccount, vaultAccount, vaultATA } = pdas;
+    const {
+      userStatusAccount,
+      stakingAccount,
+      vaultAccount,
+      vaultATA,
+      acceptedMintsAccount,
+      configAccount,
+      walletAccount,
+      emissionsScheduleAccount,
+      epochActivityAccount,
+      userActivityAccount
+    } = pdas;
     const { usdcMint, userUsdcAccount } = usdcInfo;
 
     console.log(`\n💰 Staking test: ${amount / 1000000} USDC for ${lockDays} days`);
@@ -347,6 +420,14 @@ class StakingTester {
         { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
         { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
         { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: configAccount, isSigner: false, isWritable: false },
+        { pubkey: acceptedMintsAccount, isSigner: false, isWritable: false },
+        // Price feed (ignored for USDC, which has no feed)
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: walletAccount, isSigner: false, isWritable: true },
+        { pubkey: emissionsScheduleAccount, isSigner: false, isWritable: false },
+        { pubkey: epochActivityAccount, isSigner: false, isWritable: true },
+        { pubkey: userActivityAccount, isSigner: false, isWritable: true },
       ],
       programId: this.programId,
       data: createDepositStakingInstruction(amount, lockDays)
@@ -528,14 +609,15 @@ async function main() {
 console.log('📝 FlexFi ARCHITECTURE:');
 console.log('=======================');
 console.log('🔑 USER signs: Staking, Withdraw, Critical authorizations');
-console.log('🔑 FLEXFI ADMIN signs: Whitelist, BNPL spend, Automated operations');
+console.log('🔑 USER or DEVICE KEY signs: BNPL spend, Micro-BNPL repayment, Split purchase');
+console.log('🔑 FLEXFI ADMIN signs: Whitelist, Automated operations');
 console.log('💾 STORAGE: User private keys encrypted in the backend');
 console.log('🧪 TESTS: Hardcoded wallets for simplicity/reproducibility');
 console.log('');
 console.log('📋 NEXT TESTS TO ADAPT:');
 console.log('==============================');
 console.log('1️⃣ Score: Use hardcoded wallet + verify backend creation');
-console.log('2️⃣ BNPL: User signs authorization, FlexFi ADMIN signs spend');
+console.log('2️⃣ BNPL: User signs authorization, user or device key signs spend');
 console.log('3️⃣ NFT/Cards: Integrate with backend wallets + managed cards');
 
 main().catch(error => {
@@ -210,7 +210,8 @@ class ScoreTester {
       const getScoreIx = {
         keys: [
           { pubkey: scoreAccount, isSigner: false, isWritable: false },
-          { pubkey: userKeypair.publicKey, isSigner: false, isWritable: false }
+          { pubkey: userKeypair.publicKey, isSigner: false, isWritable: false },
+          { pubkey: userKeypair.publicKey, isSigner: true, isWritable: false } // Viewer (owner)
         ],
         programId: this.programId,
         data: instructionData